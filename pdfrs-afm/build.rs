@@ -10,11 +10,14 @@ use regex::Regex;
 
 fn main() -> io::Result<()> {
     // winansi_characters.txt source: https://github.com/prawnpdf/prawn
+    // some glyphs are mapped to multiple codes (e.g. `space` to 32 and 160)
     let name_to_code = include_str!("./fonts/winansi_characters.txt")
         .split_whitespace()
         .enumerate()
-        .map(|(i, c)| (c, i as u32))
-        .collect::<HashMap<_, _>>();
+        .fold(HashMap::new(), |mut map, (i, c)| {
+            map.entry(c).or_insert_with(Vec::new).push(i as u32);
+            map
+        });
 
     let out_dir = env::var("OUT_DIR").unwrap();
     let out_dir = Path::new(&out_dir);
@@ -126,7 +129,7 @@ fn main() -> io::Result<()> {
 }
 
 fn build_font(
-    name_to_code: &HashMap<&str, u32>,
+    name_to_code: &HashMap<&str, Vec<u32>>,
     name: &str,
     afm: &str,
     out_path: PathBuf,
@@ -159,7 +162,7 @@ fn build_font(
             let caps = re_char_metrics.captures(&line).unwrap();
             let name = caps.name("name").unwrap().as_str();
             let width = caps.name("width").unwrap().as_str().parse::<u32>().unwrap();
-            if let Some(code) = name_to_code.get(name) {
+            for code in name_to_code.get(name).into_iter().flatten() {
                 writeln!(out, "        ({}, {}),", code, width)?;
             }
 
@@ -175,7 +178,9 @@ fn build_font(
             let width = caps.name("width").unwrap().as_str().parse::<i32>().unwrap();
 
             if let (Some(left), Some(right)) = (name_to_code.get(left), name_to_code.get(right)) {
-                writeln!(out, "        (({}, {}), {}),", left, right, width)?;
+                for (left, right) in left.iter().cartesian_product(right) {
+                    writeln!(out, "        (({}, {}), {}),", left, right, width)?;
+                }
             }
 
             continue;
@@ -48,14 +48,32 @@ fn convert(mut input: syn::ItemFn, args: syn::AttributeArgs) -> Result<TokenStre
         }
     };
 
-    let compressed = match args.get(2) {
-        Some(syn::NestedMeta::Meta(meta)) => match meta {
-            syn::Meta::NameValue(syn::MetaNameValue {
+    let mut compressed = false;
+    let mut compact = false;
+    let mut conformance = None;
+    let mut lang = None;
+    let mut encryption = None;
+    for arg in args.iter().skip(2) {
+        match arg {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
                 path,
                 eq_token: syn::token::Eq { .. },
-                lit: syn::Lit::Bool(syn::LitBool { value, .. }),
-            }) => match path.get_ident().map(|i| i.to_string()).as_deref() {
-                Some("compressed") => *value,
+                lit,
+            })) => match (path.get_ident().map(|i| i.to_string()).as_deref(), lit) {
+                (Some("compressed"), syn::Lit::Bool(syn::LitBool { value, .. })) => {
+                    compressed = *value
+                }
+                (Some("compact"), syn::Lit::Bool(syn::LitBool { value, .. })) => compact = *value,
+                (Some("conformance"), syn::Lit::Str(level)) => {
+                    conformance = Some(syn::Ident::new(&level.value(), level.span()))
+                }
+                (Some("lang"), syn::Lit::Str(value)) => lang = Some(value.clone()),
+                (Some("encryption"), syn::Lit::Str(encryption_fn)) => {
+                    encryption = Some(syn::Ident::new(
+                        &encryption_fn.value(),
+                        encryption_fn.span(),
+                    ))
+                }
                 _ => {
                     return Err(syn::Error::new_spanned(
                         path,
@@ -66,18 +84,14 @@ fn convert(mut input: syn::ItemFn, args: syn::AttributeArgs) -> Result<TokenStre
             other => {
                 return Err(syn::Error::new_spanned(
                     other,
-                    "Unsupported attribute type inside the macro",
-                ))
+                    "Unsupported attribute inside the macro",
+                ));
             }
-        },
-        None => false,
-        other => {
-            return Err(syn::Error::new_spanned(
-                other,
-                "Unsupported attribute inside the macro",
-            ));
         }
-    };
+    }
+    let conformance = conformance.map(|level| quote! { .with_conformance(Conformance::#level) });
+    let lang = lang.map(|lang| quote! { .with_lang(#lang) });
+    let encryption = encryption.map(|encryption_fn| quote! { .with_encryption(#encryption_fn()) });
 
     let result = quote! {
         #[async_std::test]
@@ -105,6 +119,10 @@ fn convert(mut input: syn::ItemFn, args: syn::AttributeArgs) -> Result<TokenStre
                 .with_creation_date(Utc.ymd(2019, 6, 2).and_hms(14, 28, 0))
                 .with_producer("pdfrs [test] (github.com/rkusa/pdfrs)")
                 .with_compressed(#compressed)
+                .with_compact(#compact)
+                #conformance
+                #lang
+                #encryption
                 .start(&mut result).await.unwrap();
 
             {
@@ -0,0 +1,275 @@
+use crate::destination::{Destination, ResolvedDestination};
+use crate::document::Error;
+use crate::page::{Page, Rect};
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, PdfStr, PdfText, Reference};
+
+/// A hyperlink to a URI or to a destination within the document, added to a page with
+/// [`Document::add_link`](crate::Document::add_link) or
+/// [`Document::text_link`](crate::Document::text_link).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    target: LinkTarget,
+    border: Border,
+}
+
+/// What a [`Link`] points to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    Uri(String),
+    /// A destination within the document (which may be on a page that has not been started yet).
+    Destination(Destination),
+    /// A named destination (see
+    /// [`Document::add_named_destination`](crate::Document::add_named_destination)).
+    Named(String),
+}
+
+/// The border drawn around the area of a link. All values are in points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Border {
+    width: f64,
+    style: BorderStyle,
+}
+
+/// The style of a [`Border`] (PDF spec 1.7 page 611).
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorderStyle {
+    Solid,
+    /// A dashed border with the given lengths of alternating dashes and gaps.
+    Dashed(Vec<f64>),
+    /// A simulated embossed rectangle that appears to be raised above the page.
+    Beveled,
+    /// A simulated engraved rectangle that appears to be recessed below the page.
+    Inset,
+    /// A single line along the bottom of the link area.
+    Underline,
+}
+
+/// A type used to create PDF references to annotations.
+pub(crate) type AnnotationRef = ();
+
+/// A link annotation (PDF spec 1.7 page 622).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "Annot")]
+pub(crate) struct LinkAnnotation<'a> {
+    subtype: Name<'a>,
+    rect: Rect,
+    /// Annotation flags (bit 3 to print the annotation, which PDF/A requires).
+    #[serde(rename = "F")]
+    f: u32,
+    #[serde(rename = "BS")]
+    bs: BorderStyleDict<'a>,
+    #[serde(rename = "A")]
+    a: ActionDict<'a>,
+}
+
+/// A border style dictionary (PDF spec 1.7 page 611).
+#[derive(Serialize)]
+#[serde(rename = "Border")]
+struct BorderStyleDict<'a> {
+    #[serde(rename = "W")]
+    w: f64,
+    #[serde(rename = "S")]
+    s: &'a BorderStyle,
+    #[serde(rename = "D", skip_serializing_if = "Option::is_none")]
+    d: Option<&'a [f64]>,
+}
+
+/// A URI action (PDF spec 1.7 page 662) or a go-to action (PDF spec 1.7 page 654).
+#[derive(Serialize)]
+#[serde(rename = "Action")]
+struct ActionDict<'a> {
+    #[serde(rename = "S")]
+    s: Name<'a>,
+    #[serde(rename = "URI", skip_serializing_if = "Option::is_none")]
+    uri: Option<PdfStr<'a>>,
+    #[serde(rename = "D", skip_serializing_if = "Option::is_none")]
+    d: Option<GoToTarget<'a>>,
+}
+
+enum GoToTarget<'a> {
+    Explicit(ResolvedDestination<'a>),
+    Named(PdfText<'a>),
+}
+
+impl Link {
+    /// Creates a link to the given `uri`, which has to be a 7-bit ASCII string (i.e. all other
+    /// characters must already be percent-encoded). Links have no visible border by default.
+    pub fn new(uri: impl Into<String>) -> Self {
+        Link::with_target(LinkTarget::Uri(uri.into()))
+    }
+
+    /// Creates a link that jumps to the given `destination` within the document.
+    pub fn destination(destination: Destination) -> Self {
+        Link::with_target(LinkTarget::Destination(destination))
+    }
+
+    /// Creates a link that jumps to the named destination with the given `name`.
+    pub fn named(name: impl Into<String>) -> Self {
+        Link::with_target(LinkTarget::Named(name.into()))
+    }
+
+    fn with_target(target: LinkTarget) -> Self {
+        Link {
+            target,
+            border: Border::default(),
+        }
+    }
+
+    pub fn with_border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    pub fn target(&self) -> &LinkTarget {
+        &self.target
+    }
+
+    pub fn border(&self) -> &Border {
+        &self.border
+    }
+
+    /// The link annotation covering the given `rect` (in PDF's default user space). Destinations
+    /// are resolved among the given `pages` (with their heights), which fails if the destination's
+    /// page does not exist (yet).
+    pub(crate) fn to_annotation<'a>(
+        &'a self,
+        rect: Rect,
+        pages: &'a [(Reference<Page>, f64)],
+    ) -> Result<LinkAnnotation<'a>, Error> {
+        let a = match &self.target {
+            LinkTarget::Uri(uri) => ActionDict {
+                s: Name("URI"),
+                uri: Some(PdfStr::Literal(uri)),
+                d: None,
+            },
+            LinkTarget::Destination(destination) => ActionDict {
+                s: Name("GoTo"),
+                uri: None,
+                d: Some(GoToTarget::Explicit(
+                    destination
+                        .resolve(pages)
+                        .ok_or(Error::InvalidDestination(destination.page_index()))?,
+                )),
+            },
+            LinkTarget::Named(name) => ActionDict {
+                s: Name("GoTo"),
+                uri: None,
+                d: Some(GoToTarget::Named(PdfText(name))),
+            },
+        };
+        Ok(LinkAnnotation {
+            subtype: Name("Link"),
+            rect,
+            f: 4,
+            bs: BorderStyleDict {
+                w: self.border.width,
+                s: &self.border.style,
+                d: match &self.border.style {
+                    BorderStyle::Dashed(dashes) => Some(dashes),
+                    _ => None,
+                },
+            },
+            a,
+        })
+    }
+}
+
+impl Border {
+    /// A solid border of the given `width`.
+    pub fn new(width: f64) -> Self {
+        Border {
+            width,
+            style: BorderStyle::Solid,
+        }
+    }
+
+    /// No visible border at all.
+    pub fn none() -> Self {
+        Border::new(0.0)
+    }
+
+    pub fn with_style(mut self, style: BorderStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn style(&self) -> &BorderStyle {
+        &self.style
+    }
+}
+
+impl Default for Border {
+    fn default() -> Self {
+        Border::none()
+    }
+}
+
+impl<'a> Serialize for GoToTarget<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            GoToTarget::Explicit(destination) => destination.serialize(serializer),
+            GoToTarget::Named(name) => name.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for BorderStyle {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Name(match self {
+            BorderStyle::Solid => "S",
+            BorderStyle::Dashed(_) => "D",
+            BorderStyle::Beveled => "B",
+            BorderStyle::Inset => "I",
+            BorderStyle::Underline => "U",
+        })
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize_link_annotation() {
+        let link = Link::new("https://example.com/pay?id=42")
+            .with_border(Border::new(1.5).with_style(BorderStyle::Dashed(vec![3.0, 2.0])));
+        assert_eq!(
+            serde_pdf::to_string(&link.to_annotation((10.0, 20.0, 110.0, 35.0), &[]).unwrap())
+                .unwrap(),
+            "<<\n\t/Type /Annot\n\t/Subtype /Link\n\t/Rect [10 20 110 35]\n\t/F 4\n\
+             \t/BS <<\n\t\t/Type /Border\n\t\t/W 1.5\n\t\t/S /D\n\t\t/D [3 2]\n\t>>\n\
+             \t/A <<\n\t\t/Type /Action\n\t\t/S /URI\n\t\t/URI (https://example.com/pay?id=42)\n\t>>\n>>"
+        );
+    }
+    #[test]
+    fn test_serialize_go_to_annotation() {
+        let link = Link::named("intro");
+        assert_eq!(
+            serde_pdf::to_string(&link.to_annotation((0.0, 0.0, 10.0, 10.0), &[]).unwrap())
+                .unwrap(),
+            "<<\n\t/Type /Annot\n\t/Subtype /Link\n\t/Rect [0 0 10 10]\n\t/F 4\n\
+             \t/BS <<\n\t\t/Type /Border\n\t\t/W 0\n\t\t/S /S\n\t>>\n\
+             \t/A <<\n\t\t/Type /Action\n\t\t/S /GoTo\n\t\t/D (intro)\n\t>>\n>>"
+        );
+
+        // pages that have not been written yet cannot be resolved
+        let link = Link::destination(Destination::Page(2));
+        assert!(matches!(
+            link.to_annotation((0.0, 0.0, 10.0, 10.0), &[]),
+            Err(Error::InvalidDestination(2))
+        ));
+    }
+}
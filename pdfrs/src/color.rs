@@ -0,0 +1,196 @@
+use crate::stream::Filter;
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, ObjectId, Reference};
+
+/// The device color spaces (PDF spec 1.7 page 238).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceColorSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+}
+
+/// A handle to an ICC profile added to a document with
+/// [`Document::add_icc_profile`](crate::Document::add_icc_profile).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IccProfile(pub(crate) ObjectId);
+
+/// A handle to a spot color (e.g. a Pantone color) added to a document with
+/// [`Document::add_spot_color`](crate::Document::add_spot_color).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpotColor(pub(crate) ObjectId);
+
+/// A color used to fill or stroke text and paths. All components are between `0.0` and `1.0`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Color {
+    Gray(f64),
+    Rgb(f64, f64, f64),
+    Cmyk(f64, f64, f64, f64),
+    /// A color given by the components of the color space of an ICC profile (as many as the
+    /// profile's color space has).
+    IccBased(IccProfile, Vec<f64>),
+    /// The tint of a spot color (`1.0` being the full color).
+    Spot(SpotColor, f64),
+}
+
+/// The definition of a color space that is written as a resource of the pages it is used on.
+pub(crate) enum ColorSpace {
+    IccBased {
+        data: Vec<u8>,
+        alternate: DeviceColorSpace,
+    },
+    Separation {
+        name: String,
+        alternate: DeviceColorSpace,
+        components: Vec<f64>,
+    },
+}
+
+impl ColorSpace {
+    /// The number of color components of the color space (the `/N` of ICC profiles).
+    pub(crate) fn components(&self) -> usize {
+        match self {
+            ColorSpace::IccBased { alternate, .. } => alternate.components(),
+            ColorSpace::Separation { .. } => 1,
+        }
+    }
+}
+
+/// A type used to create PDF references to color space objects.
+pub(crate) type ColorSpaceRef = ();
+
+/// The parameters of the ICC profile stream (PDF spec 1.7 page 252).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct IccStream {
+    pub n: usize,
+    pub alternate: DeviceColorSpace,
+    pub length: Reference<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filter: Vec<Filter>,
+}
+
+/// An exponential interpolation function (PDF spec 1.7 page 170) used to map the tint of a spot
+/// color to its alternate color.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct TintTransform {
+    function_type: u8,
+    domain: (f64, f64),
+    c0: Vec<f64>,
+    c1: Vec<f64>,
+    n: f64,
+}
+
+impl DeviceColorSpace {
+    pub fn name(self) -> &'static str {
+        match self {
+            DeviceColorSpace::Gray => "DeviceGray",
+            DeviceColorSpace::Rgb => "DeviceRGB",
+            DeviceColorSpace::Cmyk => "DeviceCMYK",
+        }
+    }
+
+    /// The number of color components of the color space.
+    pub fn components(self) -> usize {
+        match self {
+            DeviceColorSpace::Gray => 1,
+            DeviceColorSpace::Rgb => 3,
+            DeviceColorSpace::Cmyk => 4,
+        }
+    }
+}
+
+impl Serialize for DeviceColorSpace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Name(self.name()).serialize(serializer)
+    }
+}
+
+impl Color {
+    pub const BLACK: Color = Color::Gray(0.0);
+
+    /// The device color space of the color, or `None` if the color is not in a device color
+    /// space.
+    pub fn device_color_space(&self) -> Option<DeviceColorSpace> {
+        match self {
+            Color::Gray(_) => Some(DeviceColorSpace::Gray),
+            Color::Rgb(..) => Some(DeviceColorSpace::Rgb),
+            Color::Cmyk(..) => Some(DeviceColorSpace::Cmyk),
+            Color::IccBased(..) | Color::Spot(..) => None,
+        }
+    }
+
+    /// The components of the color in its color space.
+    pub fn components(&self) -> Vec<f64> {
+        match self {
+            Color::Gray(g) => vec![*g],
+            Color::Rgb(r, g, b) => vec![*r, *g, *b],
+            Color::Cmyk(c, m, y, k) => vec![*c, *m, *y, *k],
+            Color::IccBased(_, components) => components.clone(),
+            Color::Spot(_, tint) => vec![*tint],
+        }
+    }
+
+    /// The object id of the color's color space if it is not a device color space.
+    pub(crate) fn color_space_id(&self) -> Option<&ObjectId> {
+        match self {
+            Color::IccBased(IccProfile(id), _) | Color::Spot(SpotColor(id), _) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The name used to refer to the color's color space from within content streams.
+    pub(crate) fn color_space_name(&self) -> Option<String> {
+        self.color_space_id().map(color_space_resource_name)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::BLACK
+    }
+}
+
+pub(crate) fn color_space_resource_name(id: &ObjectId) -> String {
+    format!("CS{}", id.id())
+}
+
+impl TintTransform {
+    /// Constructs a tint transform from no ink at all (tint `0.0`) to the given `alternate` color
+    /// (tint `1.0`).
+    pub fn new(alternate: DeviceColorSpace, c1: Vec<f64>) -> Self {
+        let c0 = match alternate {
+            // white
+            DeviceColorSpace::Gray | DeviceColorSpace::Rgb => vec![1.0; c1.len()],
+            // no ink
+            DeviceColorSpace::Cmyk => vec![0.0; c1.len()],
+        };
+        TintTransform {
+            function_type: 2,
+            domain: (0.0, 1.0),
+            c0,
+            c1,
+            n: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize_tint_transform() {
+        let f = TintTransform::new(DeviceColorSpace::Cmyk, vec![0.0, 0.5, 1.0, 0.0]);
+        assert_eq!(
+            serde_pdf::to_string(&f).unwrap(),
+            "<<\n\t/FunctionType 2\n\t/Domain [0 1]\n\t/C0 [0 0 0 0]\n\t/C1 [0 0.5 1 0]\n\t/N 1\n>>"
+        );
+    }
+}
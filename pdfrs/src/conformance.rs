@@ -0,0 +1,110 @@
+use crate::color::IccStream;
+use crate::document::Error;
+use crate::stream::{Stream, StreamRef};
+use crate::writer::DocWriter;
+use crate::DeviceColorSpace;
+use async_std::io::prelude::{Write, WriteExt};
+use serde::Serialize;
+use serde_pdf::{Name, Object, PdfStr, Reference};
+
+/// The sRGB profile used as output intent of PDF/A documents.
+const SRGB_PROFILE: &[u8] = include_bytes!("../icc/sRGB.icc");
+const SRGB_IDENTIFIER: &str = "sRGB IEC61966-2.1";
+
+/// An archival standard a document is created to conform to (see
+/// [`DocumentBuilder::with_conformance`](crate::DocumentBuilder::with_conformance)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conformance {
+    /// PDF/A-2b (ISO 19005-2, level B).
+    PdfA2b,
+    /// PDF/A-3b (ISO 19005-3, level B), which additionally allows arbitrary embedded files (e.g.
+    /// the XML data of e-invoices).
+    PdfA3b,
+}
+
+/// A violation of the rules of the [`Conformance`] level a document is created with.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    #[error("the font {0} is not embedded (use an OpenType font instead)")]
+    FontNotEmbedded(String),
+    #[error("DeviceCMYK colors require a CMYK output intent (use an ICC based color instead)")]
+    DeviceCmyk,
+    #[error("embedded files are only allowed in PDF/A-3")]
+    EmbeddedFile,
+    #[error("the embedded file {0} has no MIME type (use EmbeddedFile::with_mime_type)")]
+    EmbeddedFileMimeType(String),
+    #[error("encryption is not allowed")]
+    Encryption,
+}
+
+/// A type used to create PDF references to output intents.
+pub(crate) type OutputIntentRef = ();
+
+/// An output intent dictionary (PDF spec 1.7 page 633).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "OutputIntent")]
+struct OutputIntent<'a> {
+    #[serde(rename = "S")]
+    s: Name<'a>,
+    output_condition_identifier: PdfStr<'a>,
+    info: PdfStr<'a>,
+    dest_output_profile: Reference<StreamRef>,
+}
+
+impl Conformance {
+    /// The part of the PDF/A standard.
+    fn part(self) -> u8 {
+        match self {
+            Conformance::PdfA2b => 2,
+            Conformance::PdfA3b => 3,
+        }
+    }
+
+    pub(crate) fn allows_embedded_files(self) -> bool {
+        self == Conformance::PdfA3b
+    }
+
+    /// The PDF/A identification schema of the document's XMP metadata.
+    pub(crate) fn to_xmp(self) -> String {
+        format!(
+            "<rdf:Description rdf:about=\"\" xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\">\n\
+             <pdfaid:part>{}</pdfaid:part>\n<pdfaid:conformance>B</pdfaid:conformance>\n\
+             </rdf:Description>\n",
+            self.part()
+        )
+    }
+
+    /// Writes the sRGB output intent every PDF/A document needs to define how its device colors
+    /// are to be interpreted.
+    pub(crate) async fn write_output_intent<W: Write + Unpin>(
+        self,
+        mut doc: DocWriter<W>,
+        compressed: bool,
+    ) -> Result<(Reference<OutputIntentRef>, DocWriter<W>), Error> {
+        let profile_id = doc.reserve_object_id();
+        let mut stream =
+            Stream::start_with_dict(doc, profile_id.clone(), compressed, |length, filter| {
+                IccStream {
+                    n: DeviceColorSpace::Rgb.components(),
+                    alternate: DeviceColorSpace::Rgb,
+                    length,
+                    filter,
+                }
+            })
+            .await?;
+        stream.write_all(SRGB_PROFILE).await?;
+        let mut doc = stream.end().await?;
+
+        let id = doc.reserve_object_id();
+        let output_intent = OutputIntent {
+            s: Name("GTS_PDFA1"),
+            output_condition_identifier: PdfStr::Literal(SRGB_IDENTIFIER),
+            info: PdfStr::Literal(SRGB_IDENTIFIER),
+            dest_output_profile: Reference::new(profile_id),
+        };
+        doc.write_object(Object::new(id.id(), id.rev(), output_intent))
+            .await?;
+        Ok((Reference::new(id), doc))
+    }
+}
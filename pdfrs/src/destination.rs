@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crate::document::Error;
+use crate::name_tree::NameTree;
+use crate::page::Page;
+use serde::ser::SerializeTuple;
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, Reference};
+
+/// A position within the document that can be jumped to (e.g. from the document outline). Pages
+/// are referred to by their (zero-based) index, and may also be pages that have not been started
+/// yet (see [`Document::page_index`](crate::Document::page_index)).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Destination {
+    /// Displays the whole page with the given index.
+    Page(usize),
+    /// Displays the page with the given index with `x`, `y` (measured from the top-left corner of
+    /// the page) in the top-left corner of the window, keeping the current zoom.
+    Position { page: usize, x: f64, y: f64 },
+}
+
+/// A destination with its page resolved to the page's object (PDF spec 1.7 page 582).
+pub(crate) struct ResolvedDestination<'a> {
+    page: &'a Reference<Page>,
+    page_height: f64,
+    destination: Destination,
+}
+
+impl Destination {
+    /// The index of the page the destination points to.
+    pub fn page_index(&self) -> usize {
+        match *self {
+            Destination::Page(page) | Destination::Position { page, .. } => page,
+        }
+    }
+
+    /// Resolves the destination's page among the given `pages` (with their heights). Returns
+    /// `None` if there is no page with the destination's index.
+    pub(crate) fn resolve<'a>(
+        &self,
+        pages: &'a [(Reference<Page>, f64)],
+    ) -> Option<ResolvedDestination<'a>> {
+        pages
+            .get(self.page_index())
+            .map(|(page, page_height)| ResolvedDestination {
+                page,
+                page_height: *page_height,
+                destination: *self,
+            })
+    }
+}
+
+impl<'a> Serialize for ResolvedDestination<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.destination {
+            Destination::Page(_) => (self.page, Name("Fit")).serialize(serializer),
+            Destination::Position { x, y, .. } => {
+                // [page /XYZ left top zoom], where a `null` zoom keeps the current one
+                let mut s = serializer.serialize_tuple(5)?;
+                s.serialize_element(self.page)?;
+                s.serialize_element(&Name("XYZ"))?;
+                s.serialize_element(&x)?;
+                s.serialize_element(&(self.page_height - y))?;
+                s.serialize_element(&Option::<f64>::None)?;
+                s.end()
+            }
+        }
+    }
+}
+
+/// The name tree mapping the names of the given named `destinations` to their destinations, whose
+/// pages are resolved among the given `pages` (with their heights).
+pub(crate) fn destination_name_tree<'a>(
+    destinations: &'a BTreeMap<String, Destination>,
+    pages: &'a [(Reference<Page>, f64)],
+) -> Result<NameTree<'a, ResolvedDestination<'a>>, Error> {
+    let names = destinations
+        .iter()
+        .map(|(name, destination)| {
+            let resolved = destination
+                .resolve(pages)
+                .ok_or(Error::InvalidDestination(destination.page_index()))?;
+            Ok((name.as_str(), resolved))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(NameTree::new(names))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_pdf::ObjectId;
+
+    #[test]
+    fn test_destination_name_tree() {
+        let pages = vec![(Reference::new(ObjectId::new(3, 0)), 842.0)];
+        let mut destinations = BTreeMap::new();
+        destinations.insert("b".to_string(), Destination::Page(0));
+        destinations.insert(
+            "a".to_string(),
+            Destination::Position {
+                page: 0,
+                x: 10.0,
+                y: 42.0,
+            },
+        );
+        let tree = destination_name_tree(&destinations, &pages).unwrap();
+        assert_eq!(
+            serde_pdf::to_string(&tree).unwrap(),
+            "<<\n\t/Names [(a) [3 0 R /XYZ 10 800 null] (b) [3 0 R /Fit]]\n>>"
+        );
+
+        destinations.insert("c".to_string(), Destination::Page(1));
+        assert!(matches!(
+            destination_name_tree(&destinations, &pages),
+            Err(Error::InvalidDestination(1))
+        ));
+    }
+}
@@ -1,44 +1,126 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io;
 use std::mem;
 
+use crate::annotation::{Link, LinkTarget};
+use crate::color::{
+    color_space_resource_name, Color, ColorSpace, ColorSpaceRef, DeviceColorSpace, IccProfile,
+    IccStream, SpotColor, TintTransform,
+};
+use crate::conformance::{Conformance, OutputIntentRef, Violation};
+use crate::destination::{destination_name_tree, Destination};
+use crate::embedded_file::{EmbeddedFile, FileSpecRef};
+use crate::encryption::{EncryptDict, Encryption, SecurityHandler};
+use crate::field::{AcroForm, AcroFormDict, Field, Widget};
 use crate::fonts::{FontCollection, SubsetRef};
+use crate::form::{Form, FormData, FormRecorder};
+use crate::graphics::{ExtGState, LineCap, LineJoin, Transform};
 use crate::idseq::IdSeq;
-use crate::page::{FontRef, Page, Pages, Resources};
-use crate::stream::{to_async_writer, Stream, StreamRef};
+use crate::image::{Image, ImageData, ImageError, XObjectRef};
+use crate::layout::{fit, render_lines, Line, Paragraph};
+use crate::metadata::{Info, Metadata, MetadataRef};
+use crate::name_tree::{NameTree, NameTreeRef};
+use crate::outline::{Outline, OutlineEntry, OutlineItem, OutlineRef};
+use crate::page::{FontRef, Page, PageLayout, Pages, Rect, Resources};
+use crate::path::{FillRule, Paint, Path};
+use crate::stream::{to_async_writer, ContentStream, Stream, StreamRef};
+use crate::structure::{Artifact, StructTree, StructTreeRef, Tag};
+use crate::text::TextOptions;
 use crate::writer::DocWriter;
 use async_std::io::prelude::{Write, WriteExt};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use serde_pdf::{Object, ObjectId, PdfStr, Reference};
+use serde_pdf::{Name, Object, ObjectId, PdfStr, PdfText, Reference};
 use uuid::Uuid;
 
 /// A type used to generate a PDF document.
 pub struct Document<F: FontCollection, W> {
     page_stream: Option<Stream<W>>,
+    /// The document writer while there is no active page (otherwise it is owned by the
+    /// `page_stream`).
+    doc_writer: Option<DocWriter<W>>,
     pages_obj_id: ObjectId,
-    pages: Vec<Reference<Page>>,
+    /// The finished pages with their heights.
+    pages: Vec<(Reference<Page>, f64)>,
     id: String,
-    creation_date: DateTime<Utc>,
-    producer: String,
+    metadata: Metadata,
+    conformance: Option<Conformance>,
     compressed: bool,
+    default_page_layout: PageLayout,
+    page_layout: PageLayout,
+    /// The vertical position (measured from the top of the current page) at which flowed content
+    /// continues.
+    cursor: f64,
     page_state: PageState,
+    /// The form XObject currently being recorded (see [`Document::begin_form`]) and its resources.
+    form: Option<(FormRecorder, PageState)>,
     font_collection: F,
     subsets: HashMap<F::FontRef, HashMap<SubsetRef, ObjectId>>,
+    ext_g_states: HashMap<ExtGState, ObjectId>,
+    color_spaces: Vec<(ObjectId, ColorSpace)>,
+    images: Vec<(ObjectId, ImageData)>,
+    forms: Vec<(ObjectId, FormData)>,
+    outline: Outline,
+    named_destinations: BTreeMap<String, Destination>,
+    /// Link annotations whose destination is on a page that has not been written yet. They are
+    /// written once the document ends.
+    pending_annotations: Vec<(ObjectId, Rect, Link)>,
+    /// The names of all named destinations links point to.
+    linked_names: BTreeSet<String>,
+    embedded_files: Vec<(ObjectId, EmbeddedFile)>,
+    acro_form: AcroForm,
+    lang: Option<String>,
+    structure: StructTree,
+    /// The kind of artifact all subsequent content is marked as (see [`Document::begin_artifact`]).
+    artifact: Option<Artifact>,
 }
 
 pub struct DocumentBuilder<F: FontCollection> {
     id: Option<String>,
     creation_date: Option<DateTime<Utc>>,
+    modification_date: Option<DateTime<Utc>>,
     producer: Option<String>,
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
+    keywords: Vec<String>,
+    creator: Option<String>,
+    conformance: Option<Conformance>,
+    encryption: Option<Encryption>,
+    lang: Option<String>,
     compressed: bool,
+    compact: bool,
+    page_layout: PageLayout,
     font_collection: F,
 }
 
-#[derive(Default, Eq, PartialEq)]
+#[derive(Default, PartialEq)]
 pub(crate) struct PageState {
     fonts: HashMap<SubsetRef, Reference<FontRef>>,
+    ext_g_states: BTreeMap<String, Reference<ExtGState>>,
+    color_spaces: BTreeMap<String, Reference<ColorSpaceRef>>,
+    x_objects: BTreeMap<String, Reference<XObjectRef>>,
     contents: Vec<Reference<StreamRef>>,
+    /// The links of the page with their object id and area (in PDF's default user space).
+    annotations: Vec<(ObjectId, Rect, Link)>,
+    /// The object ids of the page's form field widgets.
+    widgets: Vec<ObjectId>,
+}
+
+impl PageState {
+    /// Converts the collected resources into a resource dictionary.
+    fn into_resources(self) -> Resources {
+        Resources {
+            font: self
+                .fonts
+                .into_iter()
+                .map(|(s, r)| (format!("F{}", s.font_id()), r))
+                .collect(),
+            ext_g_state: self.ext_g_states,
+            color_space: self.color_spaces,
+            x_object: self.x_objects,
+        }
+    }
 }
 
 impl<F> Document<F, ()>
@@ -57,55 +139,866 @@ where
 {
     /// Ends the current active page (if there is any), and adds the finished page to the document
     /// catalog.
-    async fn end_page(&mut self) -> Result<DocWriter<W>, Error> {
-        let page_stream = self.page_stream.take().ok_or(Error::StreamGone)?;
+    pub async fn end_page(&mut self) -> Result<(), Error> {
+        let page_stream = match self.page_stream.take() {
+            Some(page_stream) => page_stream,
+            None => return Ok(()),
+        };
 
         let mut page_state = mem::take(&mut self.page_state);
-        page_state.contents.push(page_stream.to_reference());
+        let mut contents = mem::take(&mut page_state.contents);
+        contents.push(page_stream.to_reference());
+        let annotations = mem::take(&mut page_state.annotations);
+        let widgets = mem::take(&mut page_state.widgets);
+
+        let mut doc = page_stream.end().await?;
+        let mut annots = Vec::with_capacity(annotations.len());
+        for (id, rect, link) in annotations {
+            match link.to_annotation(rect, &self.pages) {
+                Ok(annotation) => {
+                    doc.write_object(Object::new(id.id(), id.rev(), annotation))
+                        .await?
+                }
+                // the destination is on a page that has not been written yet (e.g. the current
+                // one), so the annotation is written once the document ends
+                Err(_) => self
+                    .pending_annotations
+                    .push((id.clone(), rect, link.clone())),
+            }
+            annots.push(Reference::new(id));
+        }
+        annots.extend(widgets.into_iter().map(Reference::new));
+
+        let page_index = self.pages.len();
+        let layout = &self.page_layout;
+        let media_box = layout.media_box();
         let page = Page {
             parent: Reference::new(self.pages_obj_id.clone()),
-            resources: Resources {
-                font: page_state
-                    .fonts
-                    .into_iter()
-                    .map(|(s, r)| (format!("F{}", s.font_id()), r))
-                    .collect(),
+            media_box: if media_box != self.default_page_layout.media_box() {
+                Some(media_box)
+            } else {
+                None
+            },
+            crop_box: layout.crop_box(),
+            bleed_box: layout.bleed_box(),
+            trim_box: layout.trim_box(),
+            rotate: layout.rotation(),
+            resources: page_state.into_resources(),
+            contents,
+            annots,
+            struct_parents: if self.structure.has_content_on(page_index) {
+                Some(page_index)
+            } else {
+                None
             },
-            contents: page_state.contents,
         };
-
-        let mut doc = page_stream.end().await?;
         let page_ref = doc.serialize_object(page).await?;
-        self.pages.push(page_ref);
+        self.pages.push((page_ref, layout.dimensions().1));
+        self.doc_writer = Some(doc);
+        // the next page is started with the default page layout unless another one is chosen
+        self.cursor = self.default_page_layout.margins().top;
+
+        Ok(())
+    }
+
+    /// Ends the current active page (if there is any) and starts a new one using the document's
+    /// default page layout. All subsequent content is added to the new page.
+    pub async fn new_page(&mut self) -> Result<(), Error> {
+        let layout = self.default_page_layout.clone();
+        self.new_page_with_layout(layout).await
+    }
 
-        Ok(doc)
+    /// Ends the current active page (if there is any) and starts a new one with the given
+    /// `layout` (size, orientation and page boxes).
+    pub async fn new_page_with_layout(
+        &mut self,
+        layout: impl Into<PageLayout>,
+    ) -> Result<(), Error> {
+        self.end_page().await?;
+        self.page_layout = layout.into();
+        self.cursor = self.page_layout.margins().top;
+        self.start_page().await
     }
 
+    /// Starts a page using the default page layout if there is no active one. Unlike
+    /// [`Document::new_page`], this keeps the cursor, so that it can be positioned before the page
+    /// is started.
+    async fn ensure_page(&mut self) -> Result<(), Error> {
+        if self.page_stream.is_none() {
+            self.page_layout = self.default_page_layout.clone();
+            self.start_page().await?;
+        }
+        Ok(())
+    }
+
+    async fn start_page(&mut self) -> Result<(), Error> {
+        let doc = self.doc_writer.take().ok_or(Error::StreamGone)?;
+        self.page_stream = Some(doc.start_stream(self.compressed).await?);
+        Ok(())
+    }
+
+    /// The layout of the current page.
+    pub fn page_layout(&self) -> &PageLayout {
+        &self.page_layout
+    }
+
+    /// The (zero-based) index of the current page, or of the page started next if there is no
+    /// active page. Used to create [`Destination`]s.
+    pub fn page_index(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The vertical position (measured from the top of the current page) at which the next flowed
+    /// content (e.g. [`Document::flow_paragraph`]) is placed.
+    pub fn cursor(&self) -> f64 {
+        self.cursor
+    }
+
+    /// Moves the cursor of the current page to `y` (measured from the top of the page).
+    pub fn set_cursor(&mut self, y: f64) {
+        self.cursor = y;
+    }
+
+    /// Writes the given `text` using the default [`TextOptions`] (11pt black text in the top-left
+    /// corner of the page).
     pub async fn text(&mut self, text: &str, font_ref: Option<F::FontRef>) -> Result<(), Error> {
+        self.text_with(text, font_ref, &TextOptions::default())
+            .await
+    }
+
+    /// Writes the given `text` at the position and with the font size, line height and color of
+    /// the provided `options`. Multiple lines (separated by `\n`) are written below each other.
+    pub async fn text_with(
+        &mut self,
+        text: &str,
+        font_ref: Option<F::FontRef>,
+        options: &TextOptions,
+    ) -> Result<(), Error> {
         if text.is_empty() {
             return Ok(());
         }
 
         let font_ref = font_ref.unwrap_or_default();
+        self.check_font(font_ref)?;
+        self.content().await?;
+        self.use_color_space(options.color())?;
+        let marked = self.begin_marked_content().await?;
         let font = self.font_collection.font(font_ref);
-        let subsets = self
-            .subsets
-            .entry(font_ref)
-            .or_insert_with(Default::default);
+        let height = self.content_height();
+        let content = active_content(&mut self.form, &mut self.page_stream)?;
+        let subset_refs = crate::text::write_text(text, font, options, height, content).await?;
+        self.add_subsets(font_ref, subset_refs)?;
+        self.end_marked_content(marked).await?;
 
-        let page_stream = self.page_stream.as_mut().ok_or(Error::StreamGone)?;
-        let subset_refs = crate::text::write_text(text, font, page_stream).await?;
+        Ok(())
+    }
 
-        for subset_ref in &subset_refs {
-            if !subsets.contains_key(&subset_ref) {
-                subsets.insert(*subset_ref, page_stream.reserve_object_id());
+    /// Writes the given `text` like [`Document::text_with`] and turns the area covered by it into
+    /// the given `link`.
+    pub async fn text_link(
+        &mut self,
+        text: &str,
+        font_ref: Option<F::FontRef>,
+        options: &TextOptions,
+        link: &Link,
+    ) -> Result<(), Error> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        if self.form.is_some() {
+            return Err(Error::FormNotEnded);
+        }
+
+        self.text_with(text, font_ref, options).await?;
+        let font = self.font_collection.font(font_ref.unwrap_or_default());
+        let (_, page_height) = self.page_layout.dimensions();
+        let rect = crate::text::text_extent(text, font, options, page_height);
+        self.add_annotation(rect, link).await
+    }
+
+    /// Lays out the given `paragraph` into lines of at most `width` points, and writes it with its
+    /// top-left corner at `x`, `y` (measured from the top-left corner of the page). Returns the
+    /// height of the written paragraph.
+    pub async fn paragraph(
+        &mut self,
+        paragraph: &Paragraph<'_, F::FontRef>,
+        x: f64,
+        y: f64,
+        width: f64,
+    ) -> Result<f64, Error> {
+        self.content().await?;
+
+        let lines = paragraph.layout(&self.font_collection, width);
+        self.write_lines(&lines, x, y).await?;
+
+        Ok(lines.iter().map(|line| line.height()).sum())
+    }
+
+    /// Lays out the given `paragraph` into the width between the margins of the current page, and
+    /// writes it at the current cursor position. Whenever a line does not fit above the bottom
+    /// margin anymore, a new page (using the default page layout) is started and the remaining
+    /// lines continue there (laid out anew into the width of that page). A line that does not
+    /// even fit onto an empty page is placed anyway. The cursor is moved below the paragraph
+    /// afterwards.
+    pub async fn flow_paragraph(
+        &mut self,
+        paragraph: &Paragraph<'_, F::FontRef>,
+    ) -> Result<(), Error> {
+        // flowed content depends on the pages, and can thus not be recorded into forms
+        if self.form.is_some() {
+            return Err(Error::FormNotEnded);
+        }
+        self.ensure_page().await?;
+
+        let mut lines = paragraph.layout(&self.font_collection, self.page_layout.content_width());
+        loop {
+            let margins = self.page_layout.margins();
+            let count = fit(
+                &lines,
+                self.cursor,
+                margins.top,
+                self.page_layout.content_bottom(),
+            );
+            let remaining = lines.split_off(count);
+            if !lines.is_empty() {
+                self.write_lines(&lines, margins.left, self.cursor).await?;
+                self.cursor += lines.iter().map(|line| line.height()).sum::<f64>();
             }
+            if remaining.is_empty() {
+                return Ok(());
+            }
+
+            // the remaining lines are broken anew, since the new page's margins may differ
+            self.new_page().await?;
+            lines = paragraph.relayout(
+                remaining,
+                &self.font_collection,
+                self.page_layout.content_width(),
+            );
         }
-        self.page_state.fonts.extend(
-            subset_refs
-                .into_iter()
-                .filter_map(|s| subsets.get(&s).map(|o| (s, Reference::new(o.clone())))),
-        );
+    }
+
+    /// Writes the given `lines` with their top-left corner at `x`, `y` (measured from the top-left
+    /// corner of the current page or form).
+    async fn write_lines(
+        &mut self,
+        lines: &[Line<'_, F::FontRef>],
+        x: f64,
+        y: f64,
+    ) -> Result<(), Error> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        for line in lines {
+            for (_, style) in line.runs() {
+                self.check_font(style.font)?;
+                self.use_color_space(&style.color)?;
+            }
+        }
+
+        let marked = self.begin_marked_content().await?;
+        let height = self.content_height();
+        let content = active_content(&mut self.form, &mut self.page_stream)?;
+        let subset_refs =
+            render_lines(lines, &self.font_collection, x, height - y, content).await?;
+        for (font_ref, subset_refs) in subset_refs {
+            self.add_subsets(font_ref, subset_refs)?;
+        }
+        self.end_marked_content(marked).await?;
+
+        Ok(())
+    }
+
+    /// Paints the given `path` onto the current page.
+    pub async fn draw(&mut self, path: &Path, paint: Paint) -> Result<(), Error> {
+        let marked = self.begin_marked_content().await?;
+        let content = self.path(path).await?;
+        match paint {
+            Paint::Stroke => content.stroke().await?,
+            Paint::Fill(rule) => content.fill(rule).await?,
+            Paint::FillStroke(rule) => content.fill_stroke(rule).await?,
+        }
+        self.end_marked_content(marked).await?;
+
+        Ok(())
+    }
+
+    /// Restricts all subsequent content of the current page to the inside of the given `path`.
+    pub async fn clip(&mut self, path: &Path, rule: FillRule) -> Result<(), Error> {
+        let content = self.path(path).await?;
+        content.clip(rule).await?;
+        content.end_path().await?;
+
+        Ok(())
+    }
+
+    /// Saves the current graphics state (transformation, colors, line styles, opacity and clipping
+    /// path), so that it can be restored with [`Document::restore_state`].
+    pub async fn save_state(&mut self) -> Result<(), Error> {
+        self.content().await?.save_state().await?;
+        Ok(())
+    }
+
+    /// Restores the graphics state most recently saved with [`Document::save_state`].
+    pub async fn restore_state(&mut self) -> Result<(), Error> {
+        self.content().await?.restore_state().await?;
+        Ok(())
+    }
+
+    /// Applies the given `transform` to all subsequent content of the current page (until the
+    /// graphics state is restored).
+    pub async fn transform(&mut self, transform: Transform) -> Result<(), Error> {
+        let height = self.content_height();
+        self.content()
+            .await?
+            .transform(transform.to_user_space(height))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_line_width(&mut self, width: f64) -> Result<(), Error> {
+        self.content().await?.set_line_width(width).await?;
+        Ok(())
+    }
+
+    pub async fn set_line_cap(&mut self, cap: LineCap) -> Result<(), Error> {
+        self.content().await?.set_line_cap(cap).await?;
+        Ok(())
+    }
+
+    pub async fn set_line_join(&mut self, join: LineJoin) -> Result<(), Error> {
+        self.content().await?.set_line_join(join).await?;
+        Ok(())
+    }
+
+    /// Sets the limit of the ratio between the length of a miter join and the line width, above
+    /// which miter joins are converted into bevel joins.
+    pub async fn set_miter_limit(&mut self, limit: f64) -> Result<(), Error> {
+        self.content().await?.set_miter_limit(limit).await?;
+        Ok(())
+    }
+
+    /// Sets the lengths of alternating dashes and gaps used to stroke paths, starting `phase`
+    /// points into the pattern. Use an empty `dashes` slice for solid lines.
+    pub async fn set_dash_pattern(&mut self, dashes: &[f64], phase: f64) -> Result<(), Error> {
+        self.content()
+            .await?
+            .set_dash_pattern(dashes, phase)
+            .await?;
+        Ok(())
+    }
+
+    /// Sets the opacity (between `0.0` and `1.0`) used to fill paths and text. Values outside of
+    /// that range are clamped.
+    pub async fn set_fill_opacity(&mut self, opacity: f64) -> Result<(), Error> {
+        self.set_ext_g_state(ExtGState::fill_opacity(opacity)).await
+    }
+
+    /// Sets the opacity (between `0.0` and `1.0`) used to stroke paths. Values outside of that
+    /// range are clamped.
+    pub async fn set_stroke_opacity(&mut self, opacity: f64) -> Result<(), Error> {
+        self.set_ext_g_state(ExtGState::stroke_opacity(opacity))
+            .await
+    }
+
+    /// Applies the given graphics state parameter dictionary. Each distinct dictionary is only
+    /// written once per document.
+    async fn set_ext_g_state(&mut self, state: ExtGState) -> Result<(), Error> {
+        let id = match self.ext_g_states.get(&state) {
+            Some(id) => id.clone(),
+            None => {
+                let id = self.reserve_object_id()?;
+                self.ext_g_states.insert(state, id.clone());
+                id
+            }
+        };
+
+        let name = format!("GS{}", id.id());
+        self.content().await?.set_graphics_state(&name).await?;
+        self.resources()
+            .ext_g_states
+            .insert(name, Reference::new(id));
+
+        Ok(())
+    }
+
+    /// Sets the color used to fill paths.
+    pub async fn set_fill_color(&mut self, color: &Color) -> Result<(), Error> {
+        self.content().await?;
+        self.use_color_space(color)?;
+        self.content().await?.set_fill_color(color).await?;
+        Ok(())
+    }
+
+    /// Sets the color used to stroke paths.
+    pub async fn set_stroke_color(&mut self, color: &Color) -> Result<(), Error> {
+        self.content().await?;
+        self.use_color_space(color)?;
+        self.content().await?.set_stroke_color(color).await?;
+        Ok(())
+    }
+
+    /// Adds an ICC color profile to the document, which can then be used for colors of the
+    /// profile's color space (see [`Color::IccBased`]). The `alternate` color space is used by
+    /// readers that do not support ICC profiles and must have the same number of components as the
+    /// profile. The profile is only written once, no matter how often it is used.
+    pub fn add_icc_profile(
+        &mut self,
+        data: Vec<u8>,
+        alternate: DeviceColorSpace,
+    ) -> Result<IccProfile, Error> {
+        let id = self.reserve_object_id()?;
+        self.color_spaces
+            .push((id.clone(), ColorSpace::IccBased { data, alternate }));
+        Ok(IccProfile(id))
+    }
+
+    /// Adds a spot color with the given colorant `name` (e.g. `PANTONE 300 C`) to the document,
+    /// which can then be used with different tints (see [`Color::Spot`]). The `alternate` color
+    /// (which has to be a gray, RGB or CMYK color) is used to approximate the spot color on devices
+    /// that do not have a separate colorant for it.
+    pub fn add_spot_color(
+        &mut self,
+        name: impl Into<String>,
+        alternate: Color,
+    ) -> Result<SpotColor, Error> {
+        let space = alternate
+            .device_color_space()
+            .ok_or(Error::InvalidAlternateColor)?;
+        self.check_device_color_space(space)?;
+        let id = self.reserve_object_id()?;
+        self.color_spaces.push((
+            id.clone(),
+            ColorSpace::Separation {
+                name: name.into(),
+                alternate: space,
+                components: alternate.components(),
+            },
+        ));
+        Ok(SpotColor(id))
+    }
+
+    /// Adds the given JPEG image (baseline or progressive, in gray, RGB or CMYK) to the document,
+    /// so that it can be drawn with [`Document::draw_image`]. The image data is embedded as is,
+    /// without being decoded.
+    pub fn add_jpeg(&mut self, data: Vec<u8>) -> Result<Image, Error> {
+        let image = ImageData::jpeg(data)?;
+        if let ImageData::Jpeg { info, .. } = &image {
+            self.check_device_color_space(info.color_space)?;
+        }
+        let id = self.reserve_object_id()?;
+        let handle = image.to_image(id.clone());
+        self.images.push((id, image));
+        Ok(handle)
+    }
+
+    /// Adds the given PNG image (gray, RGB or palette based, optionally with transparency and
+    /// interlacing) to the document, so that it can be drawn with [`Document::draw_image`].
+    pub fn add_png(&mut self, data: &[u8]) -> Result<Image, Error> {
+        let image = ImageData::png(data)?;
+        let id = self.reserve_object_id()?;
+        let handle = image.to_image(id.clone());
+        self.images.push((id, image));
+        Ok(handle)
+    }
+
+    /// Draws the given `image` scaled to `width` and `height` with its top-left corner at `x`, `y`
+    /// (measured from the top-left corner of the page).
+    pub async fn draw_image(
+        &mut self,
+        image: &Image,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    ) -> Result<(), Error> {
+        let marked = self.begin_marked_content().await?;
+        let content_height = self.content_height();
+        let name = image.resource_name();
+        let content = self.content().await?;
+        content.save_state().await?;
+        // images are painted into the unit square, which is mapped to the target rectangle
+        content
+            .transform(Transform::new(
+                width,
+                0.0,
+                0.0,
+                height,
+                x,
+                content_height - y - height,
+            ))
+            .await?;
+        content.paint_x_object(&name).await?;
+        content.restore_state().await?;
+        self.end_marked_content(marked).await?;
+        self.resources()
+            .x_objects
+            .insert(name, Reference::new(image.id.clone()));
+
+        Ok(())
+    }
+
+    /// Adds the given `link` to the current page. The link covers the area of `width` by `height`
+    /// points with its top-left corner at `x`, `y` (measured from the top-left corner of the page).
+    /// Unlike content, link areas are not affected by transformations.
+    pub async fn add_link(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        link: &Link,
+    ) -> Result<(), Error> {
+        let (_, page_height) = self.page_layout.dimensions();
+        let rect = (x, page_height - y - height, x + width, page_height - y);
+        self.add_annotation(rect, link).await
+    }
+
+    /// Adds a link annotation covering the given `rect` (in PDF's default user space) to the
+    /// current page.
+    async fn add_annotation(&mut self, rect: Rect, link: &Link) -> Result<(), Error> {
+        // annotations belong to pages, and can thus not be recorded into forms
+        if self.form.is_some() {
+            return Err(Error::FormNotEnded);
+        }
+        self.content().await?;
+        let id = self.reserve_object_id()?;
+        if let LinkTarget::Named(name) = link.target() {
+            self.linked_names.insert(name.clone());
+        }
+        self.page_state.annotations.push((id, rect, link.clone()));
+        Ok(())
+    }
+
+    /// Adds a destination with the given `name`, which links can jump to (see [`Link::named`]).
+    /// Adding a destination with a name that already exists replaces the previous one.
+    pub fn add_named_destination(&mut self, name: impl Into<String>, destination: Destination) {
+        self.named_destinations.insert(name.into(), destination);
+    }
+
+    /// Adds the given interactive form `field` to the current page. The field covers the area of
+    /// `width` by `height` points with its top-left corner at `x`, `y` (measured from the top-left
+    /// corner of the page). Its text is written with the font `font_ref` (the default font if
+    /// `None`), which is also used by viewers when the field is edited.
+    pub async fn add_field(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        field: &Field,
+        font_ref: Option<F::FontRef>,
+    ) -> Result<(), Error> {
+        // widgets belong to pages, and can thus not be recorded into forms
+        if self.form.is_some() {
+            return Err(Error::FormNotEnded);
+        }
+        field.validate()?;
+        let existing_id = self.acro_form.existing_field_id(field)?;
+        self.content().await?;
+        let font_ref = font_ref.unwrap_or_default();
+        if field.has_text() {
+            self.check_font(font_ref)?;
+        }
+
+        // the appearance of each state is written as form XObject
+        let mut appearances = Vec::new();
+        let mut default_subset = None;
+        for state in field.appearance_states() {
+            self.begin_form(width, height)?;
+            let font = self.font_collection.font(font_ref);
+            let content = active_content(&mut self.form, &mut self.page_stream)?;
+            let mut subset_refs = field
+                .write_appearance(state, font, width, height, content)
+                .await?;
+            if field.has_text() {
+                // the subset that contains the space is used by viewers to (re)generate the
+                // field's appearance
+                let (subset_ref, _) = font.encode_into(" ", &mut Vec::new())?;
+                subset_refs.insert(subset_ref);
+                default_subset = Some(subset_ref);
+            }
+            self.add_subsets(font_ref, subset_refs)?;
+            appearances.push((state.map(String::from), self.end_form()?));
+        }
+        let da = default_subset.map(|subset_ref| {
+            (
+                format!("F{}", subset_ref.font_id()),
+                Reference::new(self.subsets[&font_ref][&subset_ref].clone()),
+            )
+        });
+
+        let (_, page_height) = self.page_layout.dimensions();
+        let widget = Widget {
+            id: self.reserve_object_id()?,
+            rect: (x, page_height - y - height, x + width, page_height - y),
+            field: field.clone(),
+            appearances,
+        };
+        self.page_state.widgets.push(widget.id.clone());
+        let id = match existing_id {
+            Some(id) => id,
+            None => self.reserve_object_id()?,
+        };
+        self.acro_form.add_widget(id, widget, da);
+
+        Ok(())
+    }
+
+    /// Adds the given `item` to the document outline (bookmarks), nested below `parent` or at the
+    /// top level if there is none. The returned entry can be used as parent of further items of
+    /// this document.
+    pub fn add_outline_item(
+        &mut self,
+        parent: Option<OutlineEntry>,
+        item: OutlineItem,
+    ) -> Result<OutlineEntry, Error> {
+        self.outline.push(parent, item)
+    }
+
+    /// Starts recording a form XObject with a bounding box of `width` by `height` points. All
+    /// subsequent content (except flowed paragraphs) is recorded into the form instead of being
+    /// added to the current page, until the form is ended with [`Document::end_form`]. Like on
+    /// pages, coordinates are measured from the top-left corner of the form.
+    pub fn begin_form(&mut self, width: f64, height: f64) -> Result<(), Error> {
+        if self.form.is_some() {
+            return Err(Error::FormNotEnded);
+        }
+        let id = self.reserve_object_id()?;
+        self.form = Some((FormRecorder::new(id, width, height), PageState::default()));
+        Ok(())
+    }
+
+    /// Ends the form XObject started with [`Document::begin_form`]. The returned form can be drawn
+    /// onto any page (or into other forms) with [`Document::draw_form`].
+    pub fn end_form(&mut self) -> Result<Form, Error> {
+        let (recorder, resources) = self.form.take().ok_or(Error::NoForm)?;
+        let (form, data) = recorder.end(resources.into_resources());
+        self.forms.push((form.id.clone(), data));
+        Ok(form)
+    }
+
+    /// Draws the given `form` onto the current page. The `transform` maps the coordinates of the
+    /// form (measured from its top-left corner) onto the page, e.g. [`Transform::translate`] places
+    /// the form's top-left corner at the given position.
+    pub async fn draw_form(&mut self, form: &Form, transform: Transform) -> Result<(), Error> {
+        let marked = self.begin_marked_content().await?;
+        let content_height = self.content_height();
+        let name = form.resource_name();
+        let content = self.content().await?;
+        content.save_state().await?;
+        content
+            .transform(transform.form_to_user_space(form.height(), content_height))
+            .await?;
+        content.paint_x_object(&name).await?;
+        content.restore_state().await?;
+        self.end_marked_content(marked).await?;
+        self.resources()
+            .x_objects
+            .insert(name, Reference::new(form.id.clone()));
+
+        Ok(())
+    }
+
+    /// Adds the color space of the given `color` (if it isn't a device color space) to the
+    /// resources of the current page or form, after ensuring that the color has as many
+    /// components as its color space.
+    fn use_color_space(&mut self, color: &Color) -> Result<(), Error> {
+        if let Some(space) = color.device_color_space() {
+            self.check_device_color_space(space)?;
+        }
+        if let Some(id) = color.color_space_id() {
+            let space = self
+                .color_spaces
+                .iter()
+                .find(|(space_id, _)| space_id == id);
+            if let Some((_, space)) = space {
+                let expected = space.components();
+                let actual = color.components().len();
+                if actual != expected {
+                    return Err(Error::InvalidColorComponents { expected, actual });
+                }
+            }
+
+            self.resources()
+                .color_spaces
+                .insert(color_space_resource_name(id), Reference::new(id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Ensures that the given device color space is allowed by the document's conformance level.
+    /// PDF/A documents come with an sRGB output intent, which does not allow to use DeviceCMYK.
+    fn check_device_color_space(&self, space: DeviceColorSpace) -> Result<(), Error> {
+        if self.conformance.is_some() && space == DeviceColorSpace::Cmyk {
+            return Err(Violation::DeviceCmyk.into());
+        }
+        Ok(())
+    }
+
+    /// Ensures that the given font is allowed by the document's conformance level, which requires
+    /// all fonts to be embedded.
+    fn check_font(&self, font_ref: F::FontRef) -> Result<(), Error> {
+        let font = self.font_collection.font(font_ref);
+        if self.conformance.is_some() && !font.is_embedded() {
+            return Err(Violation::FontNotEmbedded(font.base_name().to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// Starts the given structure element (tagged PDF). All subsequent content of the pages (but not
+    /// of forms) and all elements started before it is ended with [`Document::end_tag`] belong to
+    /// it. Elements are nested into the element that is open when they are started.
+    pub fn begin_tag(&mut self, tag: Tag) {
+        self.structure.begin(tag);
+    }
+
+    /// Ends the structure element most recently started with [`Document::begin_tag`].
+    pub fn end_tag(&mut self) -> Result<(), Error> {
+        if self.structure.end() {
+            Ok(())
+        } else {
+            Err(Error::NoTag)
+        }
+    }
+
+    /// Marks all subsequent content as `artifact` (e.g. page headers and footers), which excludes
+    /// it from the structure tree, until [`Document::end_artifact`] is called.
+    pub fn begin_artifact(&mut self, artifact: Artifact) {
+        self.artifact = Some(artifact);
+    }
+
+    /// Ends the artifact started with [`Document::begin_artifact`].
+    pub fn end_artifact(&mut self) -> Result<(), Error> {
+        self.artifact.take().map(|_| ()).ok_or(Error::NoArtifact)
+    }
+
+    /// Starts a marked-content sequence for the content that is about to be written to the
+    /// current page, either as artifact or as content of the innermost open structure element.
+    /// Returns whether a sequence has been started, which must be passed to
+    /// [`Document::end_marked_content`] once the content is written.
+    async fn begin_marked_content(&mut self) -> Result<bool, Error> {
+        // forms can be drawn multiple times, so their content cannot belong to a single element
+        if self.form.is_some() {
+            return Ok(false);
+        }
+        if let Some(artifact) = self.artifact {
+            self.content().await?.begin_artifact(artifact).await?;
+            return Ok(true);
+        }
+        if !self.structure.has_open_elements() {
+            return Ok(false);
+        }
+
+        // start the page first (if necessary), so that the content is added to the right page
+        self.content().await?;
+        let page_index = self.page_index();
+        if let Some((role, id)) = self.structure.add_content(page_index) {
+            self.content()
+                .await?
+                .begin_marked_content(role.name(), id)
+                .await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Ends the marked-content sequence started by [`Document::begin_marked_content`] (if any).
+    async fn end_marked_content(&mut self, marked: bool) -> Result<(), Error> {
+        if marked {
+            self.content().await?.end_marked_content().await?;
+        }
+        Ok(())
+    }
+
+    /// Embeds the given `file` into the document. PDF/A-2 documents cannot contain embedded files
+    /// (use [`Conformance::PdfA3b`] instead), and files embedded into PDF/A-3 documents require a
+    /// MIME type.
+    pub fn embed_file(&mut self, file: EmbeddedFile) -> Result<(), Error> {
+        if let Some(conformance) = self.conformance {
+            if !conformance.allows_embedded_files() {
+                return Err(Violation::EmbeddedFile.into());
+            }
+            if file.mime_type().is_none() {
+                return Err(Violation::EmbeddedFileMimeType(file.name().to_string()).into());
+            }
+        }
+        let id = self.reserve_object_id()?;
+        self.embedded_files.push((id, file));
+        Ok(())
+    }
+
+    /// Reserves an object id from whichever writer is currently active.
+    fn reserve_object_id(&mut self) -> Result<ObjectId, Error> {
+        match (&mut self.page_stream, &mut self.doc_writer) {
+            (Some(page_stream), _) => Ok(page_stream.reserve_object_id()),
+            (None, Some(doc)) => Ok(doc.reserve_object_id()),
+            (None, None) => Err(Error::StreamGone),
+        }
+    }
+
+    /// Writes the construction operators of the given `path` to the current page or form.
+    async fn path(&mut self, path: &Path) -> Result<&mut dyn ContentStream, Error> {
+        let height = self.content_height();
+        let content = self.content().await?;
+        path.write(content, height).await?;
+        Ok(content)
+    }
+
+    /// The content stream all content is written to: the form that is currently being recorded, or
+    /// otherwise the stream of the current page (starting a new page if there is none).
+    async fn content(&mut self) -> Result<&mut dyn ContentStream, Error> {
+        if self.form.is_none() {
+            self.ensure_page().await?;
+        }
+        active_content(&mut self.form, &mut self.page_stream)
+    }
+
+    /// The resources of the form that is currently being recorded, or otherwise of the current
+    /// page.
+    fn resources(&mut self) -> &mut PageState {
+        match &mut self.form {
+            Some((_, resources)) => resources,
+            None => &mut self.page_state,
+        }
+    }
+
+    /// The height of the form that is currently being recorded, or otherwise of the current page.
+    fn content_height(&self) -> f64 {
+        match &self.form {
+            Some((recorder, _)) => recorder.height(),
+            None => self.page_layout.dimensions().1,
+        }
+    }
+
+    /// Reserves object ids for font subsets that haven't been used yet and adds the subsets to the
+    /// resources of the current page or form.
+    fn add_subsets(
+        &mut self,
+        font_ref: F::FontRef,
+        subset_refs: HashSet<SubsetRef>,
+    ) -> Result<(), Error> {
+        let subsets = self.subsets.entry(font_ref).or_default();
+        let new_subsets = subset_refs
+            .iter()
+            .filter(|s| !subsets.contains_key(s))
+            .copied()
+            .collect::<Vec<_>>();
+        for subset_ref in new_subsets {
+            let id = self.reserve_object_id()?;
+            self.subsets
+                .entry(font_ref)
+                .or_default()
+                .insert(subset_ref, id);
+        }
+
+        let subsets = &self.subsets[&font_ref];
+        let fonts = subset_refs
+            .into_iter()
+            .filter_map(|s| subsets.get(&s).map(|o| (s, Reference::new(o.clone()))))
+            .collect::<Vec<_>>();
+        self.resources().fonts.extend(fonts);
 
         Ok(())
     }
@@ -117,19 +1010,84 @@ where
     pub async fn end(mut self) -> Result<(), Error> {
         #[derive(Serialize)]
         #[serde(rename_all = "PascalCase")]
-        struct Catalog {
+        struct Catalog<'a> {
             pages: Reference<Pages>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            outlines: Option<Reference<OutlineRef>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            page_mode: Option<Name<'static>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            names: Option<NameDictionary>,
+            metadata: Reference<MetadataRef>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            acro_form: Option<AcroFormDict>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            struct_tree_root: Option<Reference<StructTreeRef>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            mark_info: Option<MarkInfo>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            lang: Option<PdfText<'a>>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            output_intents: Vec<Reference<OutputIntentRef>>,
+            /// The files associated with the document (PDF/A-3 requires all embedded files to be
+            /// associated with a part of the document).
+            #[serde(rename = "AF", skip_serializing_if = "Vec::is_empty")]
+            af: Vec<Reference<FileSpecRef>>,
+        }
+
+        /// The mark information dictionary (PDF spec 1.7 page 561).
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        #[serde(rename = "")]
+        struct MarkInfo {
+            marked: bool,
+        }
+
+        /// The document's name dictionary (PDF spec 1.7 page 87).
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        #[serde(rename = "")]
+        struct NameDictionary {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            dests: Option<Reference<NameTreeRef>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            embedded_files: Option<Reference<NameTreeRef>>,
         }
 
-        let mut doc = self.end_page().await?;
+        if self.form.is_some() {
+            return Err(Error::FormNotEnded);
+        }
+        if self.structure.has_open_elements() {
+            return Err(Error::TagNotEnded);
+        }
+        // a document has at least one page
+        if self.pages.is_empty() {
+            self.ensure_page().await?;
+        }
+        self.end_page().await?;
+        let mut doc = self.doc_writer.take().ok_or(Error::StreamGone)?;
 
         let Document {
             id,
-            producer,
+            metadata,
+            conformance,
+            embedded_files,
+            acro_form,
+            lang,
+            structure,
             font_collection,
             subsets,
+            ext_g_states,
+            color_spaces,
+            images,
+            forms,
+            outline,
+            named_destinations,
+            pending_annotations,
+            linked_names,
             pages,
             compressed,
+            default_page_layout,
             ..
         } = self;
 
@@ -138,14 +1096,122 @@ where
             self.pages_obj_id.id(),
             self.pages_obj_id.rev(),
             Pages {
-                media_box: (0.0, 0.0, 595.296, 841.896),
+                media_box: default_page_layout.media_box(),
                 count: pages.len(),
-                kids: pages,
+                kids: pages.iter().map(|(page, _)| page.clone()).collect(),
             },
         );
         let pages_ref = pages_obj.to_reference();
         doc.write_object(pages_obj).await?;
-        let catalog_ref = doc.serialize_object(Catalog { pages: pages_ref }).await?;
+
+        // Write structure tree
+        let struct_tree_root = if structure.is_empty() {
+            None
+        } else {
+            Some(structure.write(&mut doc, &pages).await?)
+        };
+
+        // Write form fields
+        let acro_form = if acro_form.is_empty() {
+            None
+        } else {
+            Some(acro_form.write(&mut doc).await?)
+        };
+
+        // Write outline
+        let outlines = if outline.is_empty() {
+            None
+        } else {
+            Some(outline.write(&mut doc, &pages).await?)
+        };
+
+        // Write link annotations that point to pages that have been written after them
+        for (id, rect, link) in pending_annotations {
+            doc.write_object(Object::new(
+                id.id(),
+                id.rev(),
+                link.to_annotation(rect, &pages)?,
+            ))
+            .await?;
+        }
+
+        // Write named destinations
+        if let Some(name) = linked_names
+            .into_iter()
+            .find(|name| !named_destinations.contains_key(name))
+        {
+            return Err(Error::UnknownNamedDestination(name));
+        }
+        let dests = if named_destinations.is_empty() {
+            None
+        } else {
+            let tree = destination_name_tree(&named_destinations, &pages)?;
+            let id = doc.reserve_object_id();
+            doc.write_object(Object::new(id.id(), id.rev(), tree))
+                .await?;
+            Some(Reference::new(id))
+        };
+
+        // Write embedded files
+        let default_date = metadata.modification_date.unwrap_or(metadata.creation_date);
+        for (id, file) in &embedded_files {
+            doc = file
+                .write(id.clone(), doc, compressed, &default_date)
+                .await?;
+        }
+        let af = embedded_files
+            .iter()
+            .map(|(id, _)| Reference::new(id.clone()))
+            .collect::<Vec<_>>();
+        let embedded_files = if embedded_files.is_empty() {
+            None
+        } else {
+            let tree = NameTree::new(
+                embedded_files
+                    .iter()
+                    .map(|(id, file)| (file.name(), Reference::<FileSpecRef>::new(id.clone()))),
+            );
+            let id = doc.reserve_object_id();
+            doc.write_object(Object::new(id.id(), id.rev(), tree))
+                .await?;
+            Some(Reference::new(id))
+        };
+
+        let names = if dests.is_some() || embedded_files.is_some() {
+            Some(NameDictionary {
+                dests,
+                embedded_files,
+            })
+        } else {
+            None
+        };
+
+        // Write XMP metadata and the output intent (for PDF/A)
+        let metadata_id = doc.reserve_object_id();
+        doc = metadata.write_xmp(metadata_id.clone(), doc).await?;
+        let mut output_intents = Vec::new();
+        if let Some(conformance) = conformance {
+            let (output_intent, d) = conformance.write_output_intent(doc, compressed).await?;
+            output_intents.push(output_intent);
+            doc = d;
+        }
+
+        let catalog_ref = doc
+            .serialize_object(Catalog {
+                pages: pages_ref,
+                // show the outline when the document is opened
+                page_mode: outlines.as_ref().map(|_| Name("UseOutlines")),
+                outlines,
+                names,
+                metadata: Reference::new(metadata_id),
+                acro_form,
+                mark_info: struct_tree_root.as_ref().map(|_| MarkInfo { marked: true }),
+                struct_tree_root,
+                lang: lang.as_deref().map(PdfText),
+                output_intents,
+                af,
+            })
+            .await?;
 
         // Write fonts
         for (font_ref, subsets) in subsets {
@@ -156,46 +1222,121 @@ where
             }
         }
 
-        // xref
-        let startxref = doc.len();
-        doc.write_xref().await?;
+        // Write graphics states (ordered by their ids to keep the output deterministic)
+        let mut ext_g_states = ext_g_states.into_iter().collect::<Vec<_>>();
+        ext_g_states.sort_by_key(|(_, id)| id.id());
+        for (state, id) in ext_g_states {
+            doc.write_object(Object::new(id.id(), id.rev(), state))
+                .await?;
+        }
 
-        // trailer
-        #[derive(Serialize)]
-        #[serde(rename_all = "PascalCase")]
-        #[serde(rename = "")]
-        struct Info<'a> {
-            producer: PdfStr<'a>,
-            #[serde(with = "serde_pdf::datetime")]
-            creation_date: &'a DateTime<Utc>,
+        // Write color spaces
+        for (id, color_space) in color_spaces {
+            match color_space {
+                ColorSpace::IccBased { data, alternate } => {
+                    let stream_id = doc.reserve_object_id();
+                    let mut stream = Stream::start_with_dict(
+                        doc,
+                        stream_id.clone(),
+                        compressed,
+                        |length, filter| IccStream {
+                            n: alternate.components(),
+                            alternate,
+                            length,
+                            filter,
+                        },
+                    )
+                    .await?;
+                    stream.write_all(&data).await?;
+                    doc = stream.end().await?;
+                    let icc_based = (Name("ICCBased"), Reference::<StreamRef>::new(stream_id));
+                    doc.write_object(Object::new(id.id(), id.rev(), icc_based))
+                        .await?;
+                }
+                ColorSpace::Separation {
+                    name,
+                    alternate,
+                    components,
+                } => {
+                    let separation = (
+                        Name("Separation"),
+                        Name(&name),
+                        alternate,
+                        TintTransform::new(alternate, components),
+                    );
+                    doc.write_object(Object::new(id.id(), id.rev(), separation))
+                        .await?;
+                }
+            }
+        }
+
+        // Write images
+        for (id, image) in images {
+            doc = image.write(id, doc, compressed).await?;
+        }
+
+        // Write forms
+        for (id, form) in forms {
+            doc = form.write(id, doc, compressed).await?;
         }
 
+        // Encrypted documents refer to an indirect information dictionary, so that its strings
+        // can be encrypted as well
+        let info_ref = if doc.security_handler().is_some() {
+            Some(doc.serialize_object(metadata.to_info()).await?)
+        } else {
+            None
+        };
+
+        // trailer
         #[derive(Serialize)]
         #[serde(rename_all = "PascalCase")]
         #[serde(rename = "")]
         struct Trailer<'a> {
             size: usize,
-            root: Reference<Catalog>,
+            root: Reference<Catalog<'a>>,
             #[serde(rename = "ID")]
             id: (PdfStr<'a>, PdfStr<'a>),
-            info: Info<'a>,
-        }
-
-        writeln!(doc, "trailer").await?;
-        let size = doc.object_count() - 1;
-        to_async_writer(
-            &mut doc,
-            &Trailer {
-                size,
-                root: catalog_ref,
-                id: (PdfStr::Hex(&id), PdfStr::Hex(&id)),
-                info: Info {
-                    producer: PdfStr::Literal(&producer),
-                    creation_date: &self.creation_date,
-                },
+            #[serde(skip_serializing_if = "Option::is_none")]
+            info: Option<Info<'a>>,
+            #[serde(rename = "Info", skip_serializing_if = "Option::is_none")]
+            info_ref: Option<Reference<Info<'a>>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            encrypt: Option<EncryptDict<'a>>,
+        }
+
+        // Write the objects remaining in the current object stream (compact mode only)
+        doc.end_object_stream().await?;
+        let xref_stream_id = if doc.is_compact() {
+            Some(doc.reserve_object_id())
+        } else {
+            None
+        };
+
+        // Neither the XREF stream nor the trailer are encrypted
+        let security_handler = doc.take_security_handler();
+        let trailer = Trailer {
+            size: doc.object_count() + 1,
+            root: catalog_ref,
+            id: (PdfStr::Hex(&id), PdfStr::Hex(&id)),
+            info: match info_ref {
+                Some(_) => None,
+                None => Some(metadata.to_info()),
             },
-        )
-        .await?;
+            info_ref,
+            encrypt: security_handler.as_ref().map(SecurityHandler::to_dict),
+        };
+
+        // xref
+        let startxref = doc.len();
+        match xref_stream_id {
+            Some(id) => doc.write_xref_stream(id, &trailer).await?,
+            None => {
+                doc.write_xref().await?;
+                writeln!(doc, "trailer").await?;
+                to_async_writer(&mut doc, &trailer).await?;
+            }
+        }
         write!(doc, "\nstartxref\n{}\n%%EOF", startxref).await?;
 
         Ok(())
@@ -210,8 +1351,19 @@ where
         DocumentBuilder {
             id: None,
             creation_date: None,
+            modification_date: None,
             producer: None,
+            title: None,
+            author: None,
+            subject: None,
+            keywords: Vec::new(),
+            creator: None,
+            conformance: None,
+            encryption: None,
+            lang: None,
             compressed: true,
+            compact: false,
+            page_layout: PageLayout::default(),
             font_collection,
         }
     }
@@ -228,25 +1380,103 @@ where
         self
     }
 
+    /// Sets the date the PDF was last modified (not set by default).
+    pub fn with_modification_date(mut self, date: DateTime<Utc>) -> Self {
+        self.modification_date = Some(date);
+        self
+    }
+
     /// Overrides the default producer (pdfrs) by the provided `producer`.
     pub fn with_producer<S: Into<String>>(mut self, producer: S) -> Self {
         self.producer = Some(producer.into());
         self
     }
 
+    pub fn with_title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the name of the person who created the document's content.
+    pub fn with_author<S: Into<String>>(mut self, author: S) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_subject<S: Into<String>>(mut self, subject: S) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn with_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keywords = keywords.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the name of the application that created the original document's content (e.g. the
+    /// application the document has been converted from).
+    pub fn with_creator<S: Into<String>>(mut self, creator: S) -> Self {
+        self.creator = Some(creator.into());
+        self
+    }
+
+    /// Creates the document in conformance with the given archival standard (PDF/A). Operations
+    /// that would violate it fail with [`Error::NotConformant`].
+    pub fn with_conformance(mut self, conformance: Conformance) -> Self {
+        self.conformance = Some(conformance);
+        self
+    }
+
+    /// Encrypts the document's strings and streams (see [`Encryption`]). Encryption is not allowed
+    /// for documents created with a [`Conformance`] level.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Sets the natural language (e.g. `en-US`) of the document's text, which is used by screen
+    /// readers (see [`Tag::with_lang`] for content in other languages).
+    pub fn with_lang<S: Into<String>>(mut self, lang: S) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
     pub fn with_compressed(mut self, compressed: bool) -> Self {
         self.compressed = compressed;
         self
     }
 
+    /// Packs all non-stream objects into compressed object streams and writes the cross-reference
+    /// table as a compressed stream (which requires a PDF 1.5 reader). Disabled by default.
+    pub fn with_compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Overrides the default page layout (portrait A4) used for all pages that are not started
+    /// with an explicit layout.
+    pub fn with_page_layout(mut self, layout: impl Into<PageLayout>) -> Self {
+        self.page_layout = layout.into();
+        self
+    }
+
     /// Constructs a new `Document<'a, W>`.
     ///
     /// The document will immediately start generating a PDF. Each time the document is provided
     /// with further content, the resulting PDF output is generated right-away (most of the times).
     /// The resulting output is not buffered. It is directly written into the given `writer`. For
     /// most use-cases, it is thus recommended to provide a [`BufWriter`](std::io::BufWriter).
-    pub async fn start<'a, W: Write + Unpin>(self, writer: W) -> Result<Document<F, W>, io::Error> {
+    pub async fn start<'a, W: Write + Unpin>(self, writer: W) -> Result<Document<F, W>, Error> {
+        if self.conformance.is_some() && self.encryption.is_some() {
+            return Err(Violation::Encryption.into());
+        }
+
         let mut wr = DocWriter::new(writer, IdSeq::new(1));
+        wr.set_compact(self.compact);
 
         // The PDF format mandates that we add at least 4 commented binary characters
         // (ASCII value >= 128), so that generic tools have a chance to detect
@@ -254,22 +1484,56 @@ where
         write!(wr, "%PDF-1.6\n%").await?;
         wr.write_all(&[255, 255, 255, 255, b'\n', b'\n']).await?;
 
+        let id = self.id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        if let Some(encryption) = &self.encryption {
+            wr.set_security_handler(encryption.security_handler(&PdfStr::Hex(&id).to_bytes()));
+        }
+
         Ok(Document {
             pages_obj_id: wr.reserve_object_id(),
-            page_stream: Some(wr.start_stream(self.compressed).await?),
+            // the first page is started once content is added to it (or a page layout is chosen)
+            page_stream: None,
+            doc_writer: Some(wr),
             pages: Vec::new(),
-            id: self.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
-            creation_date: self.creation_date.unwrap_or_else(Utc::now),
-            producer: self.producer.unwrap_or_else(|| {
-                format!(
-                    "pdfrs v{} (github.com/rkusa/pdfrs)",
-                    env!("CARGO_PKG_VERSION")
-                )
-            }),
+            id,
+            metadata: Metadata {
+                title: self.title,
+                author: self.author,
+                subject: self.subject,
+                keywords: self.keywords,
+                creator: self.creator,
+                producer: self.producer.unwrap_or_else(|| {
+                    format!(
+                        "pdfrs v{} (github.com/rkusa/pdfrs)",
+                        env!("CARGO_PKG_VERSION")
+                    )
+                }),
+                creation_date: self.creation_date.unwrap_or_else(Utc::now),
+                modification_date: self.modification_date,
+                conformance: self.conformance,
+            },
+            conformance: self.conformance,
             compressed: self.compressed,
+            default_page_layout: self.page_layout.clone(),
+            cursor: self.page_layout.margins().top,
+            page_layout: self.page_layout,
             page_state: PageState::default(),
+            form: None,
             font_collection: self.font_collection,
             subsets: HashMap::new(),
+            ext_g_states: HashMap::new(),
+            color_spaces: Vec::new(),
+            images: Vec::new(),
+            forms: Vec::new(),
+            outline: Outline::default(),
+            named_destinations: BTreeMap::new(),
+            pending_annotations: Vec::new(),
+            linked_names: BTreeSet::new(),
+            embedded_files: Vec::new(),
+            acro_form: AcroForm::default(),
+            lang: self.lang,
+            structure: StructTree::default(),
+            artifact: None,
         })
     }
 }
@@ -282,4 +1546,47 @@ pub enum Error {
     Pdf(#[from] serde_pdf::Error),
     #[error("Page stream gone (this is a bug, please report)")]
     StreamGone,
+    #[error("Error reading image")]
+    Image(#[from] ImageError),
+    #[error("The alternate color of a spot color must be a gray, RGB or CMYK color")]
+    InvalidAlternateColor,
+    #[error("The color has {actual} components, but its color space expects {expected}")]
+    InvalidColorComponents { expected: usize, actual: usize },
+    #[error("The form that is being recorded must be ended first")]
+    FormNotEnded,
+    #[error("There is no form being recorded")]
+    NoForm,
+    #[error("The destination page {0} does not exist")]
+    InvalidDestination(usize),
+    #[error("There is no named destination called {0:?}")]
+    UnknownNamedDestination(String),
+    #[error("The parent outline item does not belong to the document's outline")]
+    InvalidOutlineParent,
+    #[error("There is already a form field called {0:?}")]
+    DuplicateField(String),
+    #[error("The form field name {0:?} is empty or contains a period")]
+    InvalidFieldName(String),
+    #[error("The radio button value {0:?} is reserved for unselected radio buttons")]
+    InvalidRadioValue(String),
+    #[error("The structure element that has been started must be ended first")]
+    TagNotEnded,
+    #[error("There is no structure element to end")]
+    NoTag,
+    #[error("There is no artifact to end")]
+    NoArtifact,
+    #[error("The document does not conform to its PDF/A level: {0}")]
+    NotConformant(#[from] Violation),
+}
+
+/// The content stream all content is written to: the form that is currently being recorded, or
+/// otherwise the stream of the current page.
+fn active_content<'a, W: Write + Unpin>(
+    form: &'a mut Option<(FormRecorder, PageState)>,
+    page_stream: &'a mut Option<Stream<W>>,
+) -> Result<&'a mut dyn ContentStream, Error> {
+    match (form, page_stream) {
+        (Some((recorder, _)), _) => Ok(recorder),
+        (None, Some(page_stream)) => Ok(page_stream),
+        (None, None) => Err(Error::StreamGone),
+    }
 }
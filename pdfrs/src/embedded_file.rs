@@ -0,0 +1,187 @@
+use crate::document::Error;
+use crate::stream::{Filter, Stream, StreamRef};
+use crate::writer::DocWriter;
+use async_std::io::prelude::{Write, WriteExt};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, Object, ObjectId, PdfText, Reference};
+
+/// A file embedded into the document (e.g. the XML data of an e-invoice), added with
+/// [`Document::embed_file`](crate::Document::embed_file).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedFile {
+    name: String,
+    data: Vec<u8>,
+    mime_type: Option<String>,
+    description: Option<String>,
+    relationship: FileRelationship,
+    modification_date: Option<DateTime<Utc>>,
+}
+
+/// How an [`EmbeddedFile`] relates to the content of the document (PDF/A-3 `AFRelationship`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileRelationship {
+    /// The original source material of the document's content.
+    Source,
+    /// Data used to derive the document's content (e.g. the data of a table or an invoice).
+    Data,
+    /// An alternative representation of the document's content.
+    Alternative,
+    /// A supplemental representation of the document's content (e.g. an accessible version).
+    Supplement,
+    Unspecified,
+}
+
+/// A type used to create PDF references to file specifications.
+pub(crate) type FileSpecRef = ();
+
+/// An embedded file stream dictionary (PDF spec 1.7 page 104).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "EmbeddedFile")]
+struct EmbeddedFileStream<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subtype: Option<Name<'a>>,
+    params: EmbeddedFileParams<'a>,
+    length: Reference<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    filter: Vec<Filter>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+struct EmbeddedFileParams<'a> {
+    size: usize,
+    #[serde(with = "serde_pdf::datetime")]
+    mod_date: &'a DateTime<Utc>,
+}
+
+/// A file specification dictionary (PDF spec 1.7 page 102).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "Filespec")]
+struct FileSpec<'a> {
+    #[serde(rename = "F")]
+    f: PdfText<'a>,
+    #[serde(rename = "UF")]
+    uf: PdfText<'a>,
+    #[serde(rename = "EF")]
+    ef: EmbeddedFiles,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    desc: Option<PdfText<'a>>,
+    #[serde(rename = "AFRelationship")]
+    af_relationship: FileRelationship,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "")]
+struct EmbeddedFiles {
+    #[serde(rename = "F")]
+    f: Reference<StreamRef>,
+    #[serde(rename = "UF")]
+    uf: Reference<StreamRef>,
+}
+
+impl EmbeddedFile {
+    /// Creates an embedded file with the given file `name` and content (`data`), which relates to
+    /// the document in an unspecified way.
+    pub fn new(name: impl Into<String>, data: Vec<u8>) -> Self {
+        EmbeddedFile {
+            name: name.into(),
+            data,
+            mime_type: None,
+            description: None,
+            relationship: FileRelationship::Unspecified,
+            modification_date: None,
+        }
+    }
+
+    /// Sets the MIME type (e.g. `text/xml`) of the file.
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_relationship(mut self, relationship: FileRelationship) -> Self {
+        self.relationship = relationship;
+        self
+    }
+
+    /// Overrides the date the file was last modified (the document's modification or creation date
+    /// by default).
+    pub fn with_modification_date(mut self, date: DateTime<Utc>) -> Self {
+        self.modification_date = Some(date);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        self.mime_type.as_deref()
+    }
+
+    /// Writes the file as embedded file stream and its file specification with the given object
+    /// `id`. Files without modification date are dated `default_date`.
+    pub(crate) async fn write<W: Write + Unpin>(
+        &self,
+        id: ObjectId,
+        mut doc: DocWriter<W>,
+        compressed: bool,
+        default_date: &DateTime<Utc>,
+    ) -> Result<DocWriter<W>, Error> {
+        let stream_id = doc.reserve_object_id();
+        let mut stream =
+            Stream::start_with_dict(doc, stream_id.clone(), compressed, |length, filter| {
+                EmbeddedFileStream {
+                    subtype: self.mime_type.as_deref().map(Name),
+                    params: EmbeddedFileParams {
+                        size: self.data.len(),
+                        mod_date: self.modification_date.as_ref().unwrap_or(default_date),
+                    },
+                    length,
+                    filter,
+                }
+            })
+            .await?;
+        stream.write_all(&self.data).await?;
+        let mut doc = stream.end().await?;
+
+        let file_spec = FileSpec {
+            f: PdfText(&self.name),
+            uf: PdfText(&self.name),
+            ef: EmbeddedFiles {
+                f: Reference::new(stream_id.clone()),
+                uf: Reference::new(stream_id),
+            },
+            desc: self.description.as_deref().map(PdfText),
+            af_relationship: self.relationship,
+        };
+        doc.write_object(Object::new(id.id(), id.rev(), file_spec))
+            .await?;
+        Ok(doc)
+    }
+}
+
+impl Serialize for FileRelationship {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Name(match self {
+            FileRelationship::Source => "Source",
+            FileRelationship::Data => "Data",
+            FileRelationship::Alternative => "Alternative",
+            FileRelationship::Supplement => "Supplement",
+            FileRelationship::Unspecified => "Unspecified",
+        })
+        .serialize(serializer)
+    }
+}
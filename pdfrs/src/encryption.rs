@@ -0,0 +1,779 @@
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes256};
+use md5::{Digest, Md5};
+use serde::Serialize;
+use serde_pdf::{Name, ObjectId, PdfBytes};
+use sha2::{Sha256, Sha384, Sha512};
+
+/// The padding string used to pad passwords to 32 bytes (PDF spec 1.7 page 69).
+const PADDING: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+/// The user access permissions bits (PDF spec 1.7 page 62). All reserved bits are set, which is
+/// required for revision 3 and later.
+const P_RESERVED: u32 = 0xFFFF_F0C0;
+const P_PRINT: u32 = 1 << 2 | 1 << 11;
+const P_MODIFY: u32 = 1 << 3 | 1 << 5 | 1 << 8 | 1 << 10;
+const P_COPY: u32 = 1 << 4;
+/// Extracting text and graphics for accessibility purposes is always permitted.
+const P_ACCESSIBILITY: u32 = 1 << 9;
+
+/// The encryption of a document through the standard security handler (see
+/// [`DocumentBuilder::with_encryption`](crate::DocumentBuilder::with_encryption)).
+///
+/// Opening the document requires the user password (none by default). The owner password grants
+/// full access regardless of the permissions. If no owner password is set, a random one is used,
+/// so that the permissions cannot be lifted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encryption {
+    algorithm: EncryptionAlgorithm,
+    user_password: String,
+    owner_password: Option<String>,
+    print: bool,
+    copy: bool,
+    modify: bool,
+    seed: Option<Vec<u8>>,
+}
+
+/// The algorithm used to encrypt the strings and streams of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncryptionAlgorithm {
+    /// RC4 with a 128 bit key (revision 3), for legacy readers.
+    Rc4,
+    /// AES with a 128 bit key (revision 4).
+    Aes128,
+    /// AES with a 256 bit key (revision 6).
+    Aes256,
+}
+
+/// The standard security handler of an encrypted document, holding the file encryption key.
+pub(crate) struct SecurityHandler {
+    algorithm: EncryptionAlgorithm,
+    key: Vec<u8>,
+    p: i32,
+    o: Vec<u8>,
+    u: Vec<u8>,
+    oe: Vec<u8>,
+    ue: Vec<u8>,
+    perms: Vec<u8>,
+    random: Arc<Random>,
+}
+
+/// The source of the random passwords, keys, salts and initialization vectors.
+enum Random {
+    /// The random number generator of the operating system.
+    Os,
+    /// A deterministic sequence derived from a seed (SHA-256 of the seed and a counter).
+    Seeded { seed: Vec<u8>, counter: AtomicU64 },
+}
+
+/// The encryption dictionary of the standard security handler (PDF spec 1.7 page 61 and ISO
+/// 32000-2 section 7.6.4).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct EncryptDict<'a> {
+    filter: Name<'static>,
+    #[serde(rename = "V")]
+    v: u8,
+    #[serde(rename = "R")]
+    r: u8,
+    length: u16,
+    #[serde(rename = "CF", skip_serializing_if = "Option::is_none")]
+    cf: Option<CryptFilters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stm_f: Option<Name<'static>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    str_f: Option<Name<'static>>,
+    #[serde(rename = "O")]
+    o: PdfBytes<'a>,
+    #[serde(rename = "U")]
+    u: PdfBytes<'a>,
+    #[serde(rename = "OE", skip_serializing_if = "Option::is_none")]
+    oe: Option<PdfBytes<'a>>,
+    #[serde(rename = "UE", skip_serializing_if = "Option::is_none")]
+    ue: Option<PdfBytes<'a>>,
+    #[serde(rename = "P")]
+    p: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    perms: Option<PdfBytes<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "")]
+struct CryptFilters {
+    #[serde(rename = "StdCF")]
+    std_cf: CryptFilter,
+}
+
+/// A crypt filter dictionary (PDF spec 1.7 page 74).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "CryptFilter")]
+struct CryptFilter {
+    #[serde(rename = "CFM")]
+    cfm: Name<'static>,
+    auth_event: Name<'static>,
+    length: u16,
+}
+
+/// The key used to encrypt the strings and streams of a single object.
+pub(crate) struct ObjectKey {
+    algorithm: EncryptionAlgorithm,
+    key: Vec<u8>,
+    random: Arc<Random>,
+}
+
+/// Incrementally encrypts the data of a stream. The cipher states are boxed to keep the
+/// [`DocWriter`](crate::writer::DocWriter) (which is moved a lot) small.
+pub(crate) enum StreamCipher {
+    Rc4(Box<Rc4>),
+    Aes(Box<AesCbc>),
+}
+
+pub(crate) struct Rc4 {
+    state: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+/// AES in CBC mode with PKCS#7 padding. The randomly chosen initialization vector is prepended
+/// to the encrypted data.
+pub(crate) struct AesCbc {
+    cipher: AesCipher,
+    iv: Option<[u8; 16]>,
+    prev: [u8; 16],
+    partial: Vec<u8>,
+}
+
+enum AesCipher {
+    Aes128(Box<Aes128>),
+    Aes256(Box<Aes256>),
+}
+
+impl Encryption {
+    /// Constructs a new encryption using the given `algorithm`, which grants all permissions and
+    /// does not require a password to open the document.
+    pub fn new(algorithm: EncryptionAlgorithm) -> Self {
+        Encryption {
+            algorithm,
+            user_password: String::new(),
+            owner_password: None,
+            print: true,
+            copy: true,
+            modify: true,
+            seed: None,
+        }
+    }
+
+    /// Sets the password required to open the document.
+    pub fn with_user_password<S: Into<String>>(mut self, password: S) -> Self {
+        self.user_password = password.into();
+        self
+    }
+
+    /// Sets the password that grants full access to the document.
+    pub fn with_owner_password<S: Into<String>>(mut self, password: S) -> Self {
+        self.owner_password = Some(password.into());
+        self
+    }
+
+    /// Sets whether the document may be printed (in high quality).
+    pub fn with_print(mut self, print: bool) -> Self {
+        self.print = print;
+        self
+    }
+
+    /// Sets whether text and graphics may be copied from the document.
+    pub fn with_copy(mut self, copy: bool) -> Self {
+        self.copy = copy;
+        self
+    }
+
+    /// Sets whether the document may be modified (including adding annotations, filling in form
+    /// fields and assembling pages).
+    pub fn with_modify(mut self, modify: bool) -> Self {
+        self.modify = modify;
+        self
+    }
+
+    /// Derives all random values (e.g. the initialization vectors of AES) from the given `seed`
+    /// instead of the random number generator of the operating system. This makes the output
+    /// reproducible, but also predictable, and is thus only meant for tests.
+    pub fn with_seed(mut self, seed: impl Into<Vec<u8>>) -> Self {
+        self.seed = Some(seed.into());
+        self
+    }
+
+    pub fn algorithm(&self) -> EncryptionAlgorithm {
+        self.algorithm
+    }
+
+    /// The value of the `/P` entry of the encryption dictionary.
+    fn permissions(&self) -> i32 {
+        let mut p = P_RESERVED | P_ACCESSIBILITY;
+        if self.print {
+            p |= P_PRINT;
+        }
+        if self.modify {
+            p |= P_MODIFY;
+        }
+        if self.copy {
+            p |= P_COPY;
+        }
+        p as i32
+    }
+
+    /// Creates the security handler for the document with the given file identifier (the first
+    /// element of the trailer's `/ID`).
+    pub(crate) fn security_handler(&self, file_id: &[u8]) -> SecurityHandler {
+        let p = self.permissions();
+        let random = match &self.seed {
+            Some(seed) => Random::Seeded {
+                seed: seed.clone(),
+                counter: AtomicU64::new(0),
+            },
+            None => Random::Os,
+        };
+        let random = Arc::new(random);
+        match self.algorithm {
+            EncryptionAlgorithm::Rc4 | EncryptionAlgorithm::Aes128 => {
+                let user = pdf_doc_password(&self.user_password);
+                let owner = match &self.owner_password {
+                    Some(owner) => pdf_doc_password(owner),
+                    None => random.bytes(16),
+                };
+                let o = owner_hash_r3(&owner, &user);
+                let key = file_key_r3(&user, &o, p, file_id);
+                let u = user_hash_r3(&key, file_id);
+                SecurityHandler {
+                    algorithm: self.algorithm,
+                    key,
+                    p,
+                    o,
+                    u,
+                    oe: Vec::new(),
+                    ue: Vec::new(),
+                    perms: Vec::new(),
+                    random,
+                }
+            }
+            EncryptionAlgorithm::Aes256 => {
+                let user = utf8_password(&self.user_password);
+                let owner = match &self.owner_password {
+                    Some(owner) => utf8_password(owner),
+                    None => random.bytes(16),
+                };
+                let key = random.bytes(32);
+
+                let salts = random.bytes(16);
+                let mut u = hash_r6(&user, &salts[..8], &[]).to_vec();
+                u.extend_from_slice(&salts);
+                let ue = encrypt_key_r6(&hash_r6(&user, &salts[8..], &[]), &key);
+
+                let salts = random.bytes(16);
+                let mut o = hash_r6(&owner, &salts[..8], &u).to_vec();
+                o.extend_from_slice(&salts);
+                let oe = encrypt_key_r6(&hash_r6(&owner, &salts[8..], &u), &key);
+
+                let mut perms = [0; 16];
+                perms[..4].copy_from_slice(&p.to_le_bytes());
+                perms[4..8].copy_from_slice(&[0xFF; 4]);
+                // metadata is encrypted as well
+                perms[8..12].copy_from_slice(b"Tadb");
+                perms[12..].copy_from_slice(&random.bytes(4));
+                let mut block = GenericArray::from(perms);
+                Aes256::new(GenericArray::from_slice(&key)).encrypt_block(&mut block);
+
+                SecurityHandler {
+                    algorithm: self.algorithm,
+                    key,
+                    p,
+                    o,
+                    u,
+                    oe,
+                    ue,
+                    perms: block.to_vec(),
+                    random,
+                }
+            }
+        }
+    }
+}
+
+impl SecurityHandler {
+    /// Derives the key used to encrypt the strings and streams of the object with the given `id`
+    /// (algorithm 1, PDF spec 1.7 page 59). AES-256 uses the file encryption key for all objects.
+    pub(crate) fn object_key(&self, id: &ObjectId) -> ObjectKey {
+        if self.algorithm == EncryptionAlgorithm::Aes256 {
+            return ObjectKey {
+                algorithm: self.algorithm,
+                key: self.key.clone(),
+                random: self.random.clone(),
+            };
+        }
+
+        let mut md5 = Md5::new();
+        md5.update(&self.key);
+        md5.update(&(id.id() as u32).to_le_bytes()[..3]);
+        md5.update((id.rev() as u16).to_le_bytes());
+        if self.algorithm == EncryptionAlgorithm::Aes128 {
+            md5.update(b"sAlT");
+        }
+        let len = (self.key.len() + 5).min(16);
+        ObjectKey {
+            algorithm: self.algorithm,
+            key: md5.finalize()[..len].to_vec(),
+            random: self.random.clone(),
+        }
+    }
+
+    pub(crate) fn to_dict(&self) -> EncryptDict<'_> {
+        let crypt_filter = |cfm, length| {
+            Some(CryptFilters {
+                std_cf: CryptFilter {
+                    cfm: Name(cfm),
+                    auth_event: Name("DocOpen"),
+                    length,
+                },
+            })
+        };
+        let (v, r, length, cf) = match self.algorithm {
+            EncryptionAlgorithm::Rc4 => (2, 3, 128, None),
+            EncryptionAlgorithm::Aes128 => (4, 4, 128, crypt_filter("AESV2", 16)),
+            EncryptionAlgorithm::Aes256 => (5, 6, 256, crypt_filter("AESV3", 32)),
+        };
+        let std_cf = cf.as_ref().map(|_| Name("StdCF"));
+        EncryptDict {
+            filter: Name("Standard"),
+            v,
+            r,
+            length,
+            cf,
+            stm_f: std_cf,
+            str_f: std_cf,
+            o: PdfBytes(&self.o),
+            u: PdfBytes(&self.u),
+            oe: non_empty(&self.oe),
+            ue: non_empty(&self.ue),
+            p: self.p,
+            perms: non_empty(&self.perms),
+        }
+    }
+}
+
+impl ObjectKey {
+    pub(crate) fn encrypt(&self, data: &[u8]) -> Vec<u8> {
+        let mut cipher = self.stream_cipher();
+        let mut encrypted = cipher.update(data);
+        encrypted.extend(cipher.finish());
+        encrypted
+    }
+
+    pub(crate) fn stream_cipher(&self) -> StreamCipher {
+        match self.algorithm {
+            EncryptionAlgorithm::Rc4 => StreamCipher::Rc4(Box::new(Rc4::new(&self.key))),
+            EncryptionAlgorithm::Aes128 | EncryptionAlgorithm::Aes256 => {
+                let mut iv = [0; 16];
+                iv.copy_from_slice(&self.random.bytes(16));
+                StreamCipher::Aes(Box::new(AesCbc::new(&self.key, iv)))
+            }
+        }
+    }
+}
+
+impl StreamCipher {
+    /// Encrypts the next chunk of `data`. Block ciphers keep incomplete blocks until more data is
+    /// provided, or the cipher is finished.
+    pub(crate) fn update(&mut self, data: &[u8]) -> Vec<u8> {
+        match self {
+            StreamCipher::Rc4(rc4) => rc4.apply(data),
+            StreamCipher::Aes(aes) => aes.update(data),
+        }
+    }
+
+    /// Returns the remaining encrypted data.
+    pub(crate) fn finish(self) -> Vec<u8> {
+        match self {
+            StreamCipher::Rc4(_) => Vec::new(),
+            StreamCipher::Aes(aes) => aes.finish(),
+        }
+    }
+}
+
+impl Rc4 {
+    fn new(key: &[u8]) -> Self {
+        let mut state = [0; 256];
+        for (i, s) in state.iter_mut().enumerate() {
+            *s = i as u8;
+        }
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+            state.swap(i, j as usize);
+        }
+        Rc4 { state, i: 0, j: 0 }
+    }
+
+    fn apply(&mut self, data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|b| {
+                self.i = self.i.wrapping_add(1);
+                self.j = self.j.wrapping_add(self.state[self.i as usize]);
+                self.state.swap(self.i as usize, self.j as usize);
+                let k = self.state[self.i as usize].wrapping_add(self.state[self.j as usize]);
+                b ^ self.state[k as usize]
+            })
+            .collect()
+    }
+}
+
+impl AesCbc {
+    fn new(key: &[u8], iv: [u8; 16]) -> Self {
+        let cipher = if key.len() == 32 {
+            AesCipher::Aes256(Box::new(Aes256::new(GenericArray::from_slice(key))))
+        } else {
+            AesCipher::Aes128(Box::new(Aes128::new(GenericArray::from_slice(key))))
+        };
+        AesCbc {
+            cipher,
+            iv: Some(iv),
+            prev: iv,
+            partial: Vec::with_capacity(16),
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) -> Vec<u8> {
+        let mut encrypted = self.iv.take().map(Vec::from).unwrap_or_default();
+        while !data.is_empty() {
+            let take = (16 - self.partial.len()).min(data.len());
+            self.partial.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.partial.len() == 16 {
+                encrypted.extend_from_slice(&self.encrypt_block());
+            }
+        }
+        encrypted
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let mut encrypted = self.iv.take().map(Vec::from).unwrap_or_default();
+        let pad = 16 - self.partial.len();
+        self.partial.resize(16, pad as u8);
+        encrypted.extend_from_slice(&self.encrypt_block());
+        encrypted
+    }
+
+    /// Encrypts (and clears) the complete block in `partial`.
+    fn encrypt_block(&mut self) -> [u8; 16] {
+        let mut block = GenericArray::clone_from_slice(&self.partial);
+        for (b, p) in block.iter_mut().zip(&self.prev) {
+            *b ^= p;
+        }
+        self.cipher.encrypt_block(&mut block);
+        self.prev.copy_from_slice(&block);
+        self.partial.clear();
+        self.prev
+    }
+}
+
+impl AesCipher {
+    fn encrypt_block(&self, block: &mut GenericArray<u8, aes::cipher::consts::U16>) {
+        match self {
+            AesCipher::Aes128(aes) => aes.encrypt_block(block),
+            AesCipher::Aes256(aes) => aes.encrypt_block(block),
+        }
+    }
+}
+
+/// Encodes a password for revisions 3 and 4, which expect it to be encoded with PDFDocEncoding
+/// (approximated by Latin-1, characters outside of it are skipped).
+fn pdf_doc_password(password: &str) -> Vec<u8> {
+    password
+        .chars()
+        .filter_map(|ch| u8::try_from(u32::from(ch)).ok())
+        .collect()
+}
+
+/// Encodes a password for revision 6, which expects it as UTF-8 (truncated to 127 bytes).
+fn utf8_password(password: &str) -> Vec<u8> {
+    let mut bytes = password.as_bytes().to_vec();
+    bytes.truncate(127);
+    bytes
+}
+
+/// Pads or truncates the password to exactly 32 bytes.
+fn pad_password(password: &[u8]) -> [u8; 32] {
+    let mut padded = PADDING;
+    let len = password.len().min(32);
+    padded[..len].copy_from_slice(&password[..len]);
+    padded[len..].copy_from_slice(&PADDING[..32 - len]);
+    padded
+}
+
+/// Computes the `/O` entry (algorithm 3, PDF spec 1.7 page 63).
+fn owner_hash_r3(owner: &[u8], user: &[u8]) -> Vec<u8> {
+    let mut hash = Md5::digest(pad_password(owner));
+    for _ in 0..50 {
+        hash = Md5::digest(hash);
+    }
+    rc4_rounds(&hash, &pad_password(user))
+}
+
+/// Computes the file encryption key (algorithm 2, PDF spec 1.7 page 61).
+fn file_key_r3(user: &[u8], o: &[u8], p: i32, file_id: &[u8]) -> Vec<u8> {
+    let mut md5 = Md5::new();
+    md5.update(pad_password(user));
+    md5.update(o);
+    md5.update(p.to_le_bytes());
+    md5.update(file_id);
+    let mut hash = md5.finalize();
+    for _ in 0..50 {
+        hash = Md5::digest(hash);
+    }
+    hash.to_vec()
+}
+
+/// Computes the `/U` entry (algorithm 5, PDF spec 1.7 page 64).
+fn user_hash_r3(key: &[u8], file_id: &[u8]) -> Vec<u8> {
+    let mut md5 = Md5::new();
+    md5.update(PADDING);
+    md5.update(file_id);
+    let mut u = rc4_rounds(key, &md5.finalize());
+    // the remaining 16 bytes are arbitrary
+    u.resize(32, 0);
+    u
+}
+
+/// Encrypts `data` with RC4 twenty times, each time with the `key`'s bytes XORed with the
+/// iteration number.
+fn rc4_rounds(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut data = data.to_vec();
+    for i in 0..20u8 {
+        let key = key.iter().map(|b| b ^ i).collect::<Vec<_>>();
+        data = Rc4::new(&key).apply(&data);
+    }
+    data
+}
+
+/// Computes the hash used for the password validation and key encryption of revision 6
+/// (algorithm 2.B, ISO 32000-2 section 7.6.4.3.4).
+fn hash_r6(password: &[u8], salt: &[u8], user_data: &[u8]) -> [u8; 32] {
+    let mut sha = Sha256::new();
+    sha.update(password);
+    sha.update(salt);
+    sha.update(user_data);
+    let mut k = sha.finalize().to_vec();
+
+    let mut round = 0;
+    loop {
+        let mut k1 = Vec::with_capacity(64 * (password.len() + k.len() + user_data.len()));
+        for _ in 0..64 {
+            k1.extend_from_slice(password);
+            k1.extend_from_slice(&k);
+            k1.extend_from_slice(user_data);
+        }
+
+        let mut aes = AesCbc::new(&k[..16], {
+            let mut iv = [0; 16];
+            iv.copy_from_slice(&k[16..32]);
+            iv
+        });
+        aes.iv = None;
+        let e = aes.update(&k1);
+
+        // the first 16 bytes as a big-endian number modulo 3 (as 256 mod 3 is 1, this is the same
+        // as the sum of the bytes modulo 3)
+        k = match e[..16].iter().map(|b| u32::from(*b)).sum::<u32>() % 3 {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+
+        round += 1;
+        if round >= 64 && u32::from(e[e.len() - 1]) <= round - 32 {
+            break;
+        }
+    }
+
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&k[..32]);
+    hash
+}
+
+/// Encrypts the file encryption key for the `/OE` and `/UE` entries (AES-256 in CBC mode with no
+/// padding and a zero initialization vector).
+fn encrypt_key_r6(hash: &[u8; 32], key: &[u8]) -> Vec<u8> {
+    let mut aes = AesCbc::new(hash, [0; 16]);
+    aes.iv = None;
+    aes.update(key)
+}
+
+/// Used for the entries of the encryption dictionary that only exist for some revisions.
+fn non_empty(bytes: &[u8]) -> Option<PdfBytes<'_>> {
+    if bytes.is_empty() {
+        None
+    } else {
+        Some(PdfBytes(bytes))
+    }
+}
+
+impl Random {
+    fn bytes(&self, len: usize) -> Vec<u8> {
+        match self {
+            Random::Os => {
+                let mut bytes = vec![0; len];
+                getrandom::getrandom(&mut bytes).expect("failed to generate random bytes");
+                bytes
+            }
+            Random::Seeded { seed, counter } => {
+                let mut bytes = Vec::with_capacity(len);
+                while bytes.len() < len {
+                    let mut sha = Sha256::new();
+                    sha.update(seed);
+                    sha.update(counter.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+                    bytes.extend_from_slice(&sha.finalize());
+                }
+                bytes.truncate(len);
+                bytes
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aes::cipher::BlockDecrypt;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn decrypt_aes_cbc(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut prev = data[..16].to_vec();
+        let mut decrypted = Vec::new();
+        for chunk in data[16..].chunks(16) {
+            let mut block = GenericArray::clone_from_slice(chunk);
+            match key.len() {
+                32 => Aes256::new(GenericArray::from_slice(key)).decrypt_block(&mut block),
+                _ => Aes128::new(GenericArray::from_slice(key)).decrypt_block(&mut block),
+            }
+            decrypted.extend(block.iter().zip(&prev).map(|(b, p)| b ^ p));
+            prev = chunk.to_vec();
+        }
+        let pad = decrypted[decrypted.len() - 1] as usize;
+        decrypted.truncate(decrypted.len() - pad);
+        decrypted
+    }
+
+    #[test]
+    fn test_rc4() {
+        // test vector of RFC 6229
+        let mut rc4 = Rc4::new(&[1, 2, 3, 4, 5]);
+        assert_eq!(hex(&rc4.apply(&[0; 8])), "b2396305f03dc027");
+    }
+
+    #[test]
+    fn test_permissions() {
+        assert_eq!(Encryption::new(EncryptionAlgorithm::Rc4).permissions(), -4);
+        let encryption = Encryption::new(EncryptionAlgorithm::Rc4)
+            .with_print(false)
+            .with_copy(false)
+            .with_modify(false);
+        assert_eq!(encryption.permissions(), -3392);
+    }
+
+    #[test]
+    fn test_rc4_security_handler() {
+        let handler = Encryption::new(EncryptionAlgorithm::Rc4)
+            .with_user_password("user")
+            .with_owner_password("owner")
+            .with_print(false)
+            .security_handler(b"0123456789abcdef");
+        assert_eq!(
+            hex(&handler.o),
+            "0ba3835f88f90388e74e54584125ce142be0de24c6b0d37746e075b891756671"
+        );
+        assert_eq!(hex(&handler.key), "6bb602a759b854db482755e73489e04a");
+        assert_eq!(
+            hex(&handler.u),
+            "92dbf6af6a899368cdaaf77013ddf26d00000000000000000000000000000000"
+        );
+
+        let key = handler.object_key(&ObjectId::new(7, 0));
+        assert_eq!(hex(&key.key), "c3a1cafbbd5c2f7b5b0bfbd8c2d569f0");
+    }
+
+    #[test]
+    fn test_hash_r6() {
+        assert_eq!(
+            hex(&hash_r6(b"user", b"12345678", &[])),
+            "33a74805a1940282ca67d2b4938a4f77db6f69c75e92e9f281f0743ef0111571"
+        );
+        let user_data = (0..48).collect::<Vec<u8>>();
+        assert_eq!(
+            hex(&hash_r6(b"owner", b"12345678", &user_data)),
+            "16b37367f6acf5352ae1abc136f1be170a4309d616cbde5d2e6968d54cfc8dfd"
+        );
+    }
+
+    #[test]
+    fn test_aes_128_object_key() {
+        let handler = Encryption::new(EncryptionAlgorithm::Aes128)
+            .with_owner_password("owner")
+            .security_handler(b"0123456789abcdef");
+        let key = handler.object_key(&ObjectId::new(7, 0));
+        assert_eq!(key.key.len(), 16);
+
+        for len in [0, 5, 16, 40] {
+            let data = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+            let encrypted = key.encrypt(&data);
+            assert_eq!(encrypted.len(), 16 + (len / 16 + 1) * 16);
+            assert_eq!(decrypt_aes_cbc(&key.key, &encrypted), data);
+        }
+    }
+
+    #[test]
+    fn test_aes_256_security_handler() {
+        let handler = Encryption::new(EncryptionAlgorithm::Aes256)
+            .with_user_password("usér")
+            .with_owner_password("owner")
+            .security_handler(b"0123456789abcdef");
+
+        // validate the user and owner passwords
+        let user = "usér".as_bytes();
+        assert_eq!(hash_r6(user, &handler.u[32..40], &[]), handler.u[..32]);
+        assert_eq!(
+            hash_r6(b"owner", &handler.o[32..40], &handler.u),
+            handler.o[..32]
+        );
+
+        // the file encryption key can be recovered from /UE
+        let hash = hash_r6(user, &handler.u[40..48], &[]);
+        let mut prev = [0; 16];
+        let mut key = Vec::new();
+        for chunk in handler.ue.chunks(16) {
+            let mut block = GenericArray::clone_from_slice(chunk);
+            Aes256::new(GenericArray::from_slice(&hash)).decrypt_block(&mut block);
+            key.extend(block.iter().zip(&prev).map(|(b, p)| b ^ p));
+            prev.copy_from_slice(chunk);
+        }
+        assert_eq!(key, handler.key);
+
+        let mut perms = GenericArray::clone_from_slice(&handler.perms);
+        Aes256::new(GenericArray::from_slice(&handler.key)).decrypt_block(&mut perms);
+        assert_eq!(&perms[..12], b"\xfc\xff\xff\xff\xff\xff\xff\xffTadb");
+
+        let key = handler.object_key(&ObjectId::new(7, 0));
+        let encrypted = key.encrypt(b"Hello World");
+        assert_eq!(decrypt_aes_cbc(&handler.key, &encrypted), b"Hello World");
+    }
+}
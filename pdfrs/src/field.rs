@@ -0,0 +1,873 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+
+use crate::color::Color;
+use crate::document::Error;
+use crate::fonts::{Font, SubsetRef};
+use crate::form::Form;
+use crate::image::XObjectRef;
+use crate::page::{FontRef, Rect};
+use crate::path::{FillRule, Path};
+use crate::stream::ContentStream;
+use crate::text::{Origin, TextOptions};
+use crate::writer::DocWriter;
+use async_std::io::prelude::Write;
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, Object, ObjectId, PdfStr, PdfText, Reference};
+
+/// An interactive form field (AcroForm), added to a page with
+/// [`Document::add_field`](crate::Document::add_field). Every field comes with generated
+/// appearance streams, so that it is displayed correctly even by viewers that do not generate
+/// appearances themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    name: String,
+    kind: FieldKind,
+    tooltip: Option<String>,
+    read_only: bool,
+    required: bool,
+    font_size: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+    Text {
+        value: String,
+        multiline: bool,
+        max_length: Option<usize>,
+    },
+    Checkbox {
+        checked: bool,
+    },
+    Radio {
+        value: String,
+        checked: bool,
+    },
+    Choice {
+        options: Vec<String>,
+        value: Option<String>,
+        combo: bool,
+    },
+    PushButton {
+        caption: String,
+        action: Option<ButtonAction>,
+    },
+}
+
+/// What happens when a push button is clicked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ButtonAction {
+    /// Opens the given URI.
+    Uri(String),
+    /// Submits the values of all fields to the given URL (as HTML form).
+    SubmitForm(String),
+    /// Resets all fields to their default values.
+    ResetForm,
+}
+
+/// The fields added to the document and their widget annotations, which are written once the
+/// document ends.
+#[derive(Default)]
+pub(crate) struct AcroForm {
+    fields: Vec<FieldEntry>,
+    /// The fonts used by the default appearances of the fields.
+    fonts: BTreeMap<String, Reference<FontRef>>,
+}
+
+struct FieldEntry {
+    id: ObjectId,
+    field: Field,
+    /// The default appearance (font and color) viewers use to (re)generate the appearance of the
+    /// field's text.
+    da: Option<String>,
+    widgets: Vec<Widget>,
+}
+
+/// A widget annotation, i.e. the visual representation of a field on a page.
+pub(crate) struct Widget {
+    pub(crate) id: ObjectId,
+    pub(crate) rect: Rect,
+    /// The field the widget has been added for (which, for radio buttons, differs per widget).
+    pub(crate) field: Field,
+    /// The appearance stream of each appearance state (`None` for fields with a single
+    /// appearance).
+    pub(crate) appearances: Vec<(Option<String>, Form)>,
+}
+
+/// A type used to create PDF references to fields.
+type FieldRef = ();
+
+/// A field dictionary (PDF spec 1.7 page 676).
+#[derive(Serialize)]
+#[serde(rename = "")]
+struct FieldDict<'a> {
+    #[serde(rename = "FT")]
+    ft: Name<'static>,
+    #[serde(rename = "T")]
+    t: PdfText<'a>,
+    #[serde(rename = "TU", skip_serializing_if = "Option::is_none")]
+    tu: Option<PdfText<'a>>,
+    #[serde(rename = "Ff", skip_serializing_if = "is_zero")]
+    ff: u32,
+    #[serde(rename = "V", skip_serializing_if = "Option::is_none")]
+    v: Option<FieldValue<'a>>,
+    #[serde(rename = "DA", skip_serializing_if = "Option::is_none")]
+    da: Option<PdfStr<'a>>,
+    #[serde(rename = "MaxLen", skip_serializing_if = "Option::is_none")]
+    max_len: Option<usize>,
+    #[serde(rename = "Opt", skip_serializing_if = "Vec::is_empty")]
+    opt: Vec<PdfText<'a>>,
+    #[serde(rename = "Kids")]
+    kids: Vec<Reference<FieldRef>>,
+}
+
+enum FieldValue<'a> {
+    Text(PdfText<'a>),
+    State(Name<'a>),
+}
+
+/// A widget annotation dictionary (PDF spec 1.7 page 640).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "Annot")]
+struct WidgetAnnotation<'a> {
+    subtype: Name<'static>,
+    rect: Rect,
+    /// Annotation flags (bit 3 to print the annotation, which PDF/A requires).
+    #[serde(rename = "F")]
+    f: u32,
+    parent: Reference<FieldRef>,
+    #[serde(rename = "AP")]
+    ap: AppearanceDict<'a>,
+    #[serde(rename = "AS", skip_serializing_if = "Option::is_none")]
+    as_: Option<Name<'a>>,
+    #[serde(rename = "MK", skip_serializing_if = "Option::is_none")]
+    mk: Option<AppearanceCharacteristics<'a>>,
+    #[serde(rename = "A", skip_serializing_if = "Option::is_none")]
+    a: Option<ActionDict<'a>>,
+}
+
+/// An appearance dictionary (PDF spec 1.7 page 614) with normal appearances only.
+#[derive(Serialize)]
+#[serde(rename = "")]
+struct AppearanceDict<'a> {
+    #[serde(rename = "N")]
+    n: Appearance<'a>,
+}
+
+enum Appearance<'a> {
+    Single(Reference<XObjectRef>),
+    States(BTreeMap<&'a str, Reference<XObjectRef>>),
+}
+
+/// An appearance characteristics dictionary (PDF spec 1.7 page 641).
+#[derive(Serialize)]
+#[serde(rename = "")]
+struct AppearanceCharacteristics<'a> {
+    #[serde(rename = "CA")]
+    ca: PdfText<'a>,
+}
+
+/// A URI, submit-form or reset-form action (PDF spec 1.7 page 662ff).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "Action")]
+struct ActionDict<'a> {
+    #[serde(rename = "S")]
+    s: Name<'static>,
+    #[serde(rename = "URI", skip_serializing_if = "Option::is_none")]
+    uri: Option<PdfStr<'a>>,
+    #[serde(rename = "F", skip_serializing_if = "Option::is_none")]
+    f: Option<UrlFileSpec<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flags: Option<u32>,
+}
+
+/// A URL file specification (PDF spec 1.7 page 101).
+#[derive(Serialize)]
+#[serde(rename = "Filespec")]
+struct UrlFileSpec<'a> {
+    #[serde(rename = "FS")]
+    fs: Name<'static>,
+    #[serde(rename = "F")]
+    f: PdfStr<'a>,
+}
+
+/// The interactive form dictionary (PDF spec 1.7 page 672).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct AcroFormDict {
+    fields: Vec<Reference<FieldRef>>,
+    #[serde(rename = "DR", skip_serializing_if = "Option::is_none")]
+    dr: Option<DefaultResources>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+struct DefaultResources {
+    font: BTreeMap<String, Reference<FontRef>>,
+}
+
+// field flags (PDF spec 1.7 page 677ff)
+const READ_ONLY: u32 = 1 << 0;
+const REQUIRED: u32 = 1 << 1;
+const MULTILINE: u32 = 1 << 12;
+const NO_TOGGLE_TO_OFF: u32 = 1 << 14;
+const RADIO: u32 = 1 << 15;
+const PUSH_BUTTON: u32 = 1 << 16;
+const COMBO: u32 = 1 << 17;
+
+/// The name of the appearance state of checkboxes and radio buttons that are not selected.
+const OFF: &str = "Off";
+/// The name of the appearance state of checked checkboxes.
+const YES: &str = "Yes";
+/// The distance between the border of a field and its text.
+const PADDING: f64 = 2.0;
+/// The background color of the selected option of list boxes.
+const SELECTION: Color = Color::Rgb(0.6, 0.757, 0.855);
+
+impl Field {
+    fn new(name: impl Into<String>, kind: FieldKind) -> Self {
+        Field {
+            name: name.into(),
+            kind,
+            tooltip: None,
+            read_only: false,
+            required: false,
+            font_size: 11.0,
+        }
+    }
+
+    /// Creates an empty single-line text field with the given `name`. Names must not contain
+    /// periods, which separate the names of nested fields in fully qualified names.
+    pub fn text(name: impl Into<String>) -> Self {
+        Field::new(
+            name,
+            FieldKind::Text {
+                value: String::new(),
+                multiline: false,
+                max_length: None,
+            },
+        )
+    }
+
+    /// Creates an unchecked checkbox.
+    pub fn checkbox(name: impl Into<String>) -> Self {
+        Field::new(name, FieldKind::Checkbox { checked: false })
+    }
+
+    /// Creates an unchecked radio button that sets the field to `value` when selected. All radio
+    /// buttons with the same `name` form a group of which only one can be selected. The `value`
+    /// must not be `Off`, which is the appearance state of unselected radio buttons.
+    pub fn radio(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Field::new(
+            name,
+            FieldKind::Radio {
+                value: value.into(),
+                checked: false,
+            },
+        )
+    }
+
+    /// Creates a drop-down list of the given `options` with nothing selected.
+    pub fn combo_box<I, S>(name: impl Into<String>, options: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Field::choice(name, options, true)
+    }
+
+    /// Creates a scrollable list of the given `options` with nothing selected.
+    pub fn list_box<I, S>(name: impl Into<String>, options: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Field::choice(name, options, false)
+    }
+
+    fn choice<I, S>(name: impl Into<String>, options: I, combo: bool) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Field::new(
+            name,
+            FieldKind::Choice {
+                options: options.into_iter().map(Into::into).collect(),
+                value: None,
+                combo,
+            },
+        )
+    }
+
+    /// Creates a push button labeled with the given `caption`.
+    pub fn push_button(name: impl Into<String>, caption: impl Into<String>) -> Self {
+        Field::new(
+            name,
+            FieldKind::PushButton {
+                caption: caption.into(),
+                action: None,
+            },
+        )
+    }
+
+    /// Sets the value of a text field or the selected option of a combo or list box (ignored for
+    /// all other fields).
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        match &mut self.kind {
+            FieldKind::Text { value: v, .. } => *v = value.into(),
+            FieldKind::Choice { value: v, .. } => *v = Some(value.into()),
+            _ => {}
+        }
+        self
+    }
+
+    /// Allows a text field to contain multiple lines (ignored for all other fields).
+    pub fn with_multiline(mut self, multiline: bool) -> Self {
+        if let FieldKind::Text { multiline: m, .. } = &mut self.kind {
+            *m = multiline;
+        }
+        self
+    }
+
+    /// Limits the number of characters of a text field (ignored for all other fields).
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        if let FieldKind::Text { max_length: m, .. } = &mut self.kind {
+            *m = Some(max_length);
+        }
+        self
+    }
+
+    /// Checks a checkbox or selects a radio button (ignored for all other fields).
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        match &mut self.kind {
+            FieldKind::Checkbox { checked: c } | FieldKind::Radio { checked: c, .. } => {
+                *c = checked
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Sets what happens when a push button is clicked (ignored for all other fields).
+    pub fn with_action(mut self, action: ButtonAction) -> Self {
+        if let FieldKind::PushButton { action: a, .. } = &mut self.kind {
+            *a = Some(action);
+        }
+        self
+    }
+
+    /// Sets the text displayed when hovering the field, which is also used by screen readers.
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Marks the field as required to have a value when the form is submitted.
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets the size of the field's text (11pt by default).
+    pub fn with_font_size(mut self, font_size: f64) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Ensures that the field's name is not a fully qualified name and that a radio button's value
+    /// does not collide with the appearance state of unselected radio buttons.
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.name.is_empty() || self.name.contains('.') {
+            return Err(Error::InvalidFieldName(self.name.clone()));
+        }
+        match &self.kind {
+            FieldKind::Radio { value, .. } if value == OFF => {
+                Err(Error::InvalidRadioValue(value.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the field displays text, and thus needs a font.
+    pub(crate) fn has_text(&self) -> bool {
+        !matches!(
+            self.kind,
+            FieldKind::Checkbox { .. } | FieldKind::Radio { .. }
+        )
+    }
+
+    /// The names of the field's appearance states (`None` for fields with a single appearance).
+    pub(crate) fn appearance_states(&self) -> Vec<Option<&str>> {
+        match &self.kind {
+            FieldKind::Checkbox { .. } => vec![Some(YES), Some(OFF)],
+            FieldKind::Radio { value, .. } => vec![Some(value), Some(OFF)],
+            _ => vec![None],
+        }
+    }
+
+    /// Writes the appearance of the field with the given `state` into `wr`, which is a form of
+    /// `width` by `height` points. Returns the font subsets used by it.
+    pub(crate) async fn write_appearance(
+        &self,
+        state: Option<&str>,
+        font: &dyn Font,
+        width: f64,
+        height: f64,
+        wr: &mut dyn ContentStream,
+    ) -> Result<HashSet<SubsetRef>, io::Error> {
+        let size = self.font_size;
+        let mut subset_refs = HashSet::new();
+
+        if let FieldKind::PushButton { .. } = self.kind {
+            wr.set_fill_color(&Color::Gray(0.85)).await?;
+            wr.rect(0.0, 0.0, width, height).await?;
+            wr.fill(FillRule::NonZero).await?;
+        }
+
+        // border
+        wr.set_line_width(1.0).await?;
+        wr.set_stroke_color(&Color::Gray(0.5)).await?;
+        match &self.kind {
+            FieldKind::Radio { .. } => {
+                let radius = width.min(height) / 2.0 - 0.5;
+                Path::circle(width / 2.0, height / 2.0, radius)
+                    .write(wr, height)
+                    .await?;
+            }
+            _ => wr.rect(0.5, 0.5, width - 1.0, height - 1.0).await?,
+        }
+        wr.stroke().await?;
+
+        let on = state.map(|state| state != OFF).unwrap_or(false);
+        match &self.kind {
+            FieldKind::Text {
+                value, multiline, ..
+            } => {
+                let text = if *multiline {
+                    value.clone()
+                } else {
+                    value.replace('\n', " ")
+                };
+                subset_refs.extend(
+                    self.write_variable_text(&text, *multiline, font, width, height, wr)
+                        .await?,
+                );
+            }
+            FieldKind::Checkbox { .. } if on => {
+                let line_width = width.min(height) * 0.1;
+                wr.set_line_width(line_width).await?;
+                wr.set_stroke_color(&Color::BLACK).await?;
+                wr.move_to(width * 0.2, height * 0.5).await?;
+                wr.line_to(width * 0.4, height * 0.25).await?;
+                wr.line_to(width * 0.8, height * 0.8).await?;
+                wr.stroke().await?;
+            }
+            FieldKind::Radio { .. } if on => {
+                wr.set_fill_color(&Color::BLACK).await?;
+                Path::circle(width / 2.0, height / 2.0, width.min(height) / 4.0)
+                    .write(wr, height)
+                    .await?;
+                wr.fill(FillRule::NonZero).await?;
+            }
+            FieldKind::Checkbox { .. } | FieldKind::Radio { .. } => {}
+            FieldKind::Choice {
+                value, combo: true, ..
+            } => {
+                let text = value.as_deref().unwrap_or_default();
+                subset_refs.extend(
+                    self.write_variable_text(text, false, font, width, height, wr)
+                        .await?,
+                );
+            }
+            FieldKind::Choice {
+                options,
+                value,
+                combo: false,
+            } => {
+                let line_height = line_height(font, size);
+                if let Some(i) = options.iter().position(|o| Some(o) == value.as_ref()) {
+                    wr.set_fill_color(&SELECTION).await?;
+                    wr.rect(
+                        1.0,
+                        height - PADDING - (i + 1) as f64 * line_height,
+                        width - 2.0,
+                        line_height,
+                    )
+                    .await?;
+                    wr.fill(FillRule::NonZero).await?;
+                }
+                subset_refs.extend(
+                    self.write_variable_text(&options.join("\n"), true, font, width, height, wr)
+                        .await?,
+                );
+            }
+            FieldKind::PushButton { caption, .. } => {
+                let x = (width - font.text_width(caption, size)) / 2.0;
+                let options = TextOptions::new()
+                    .with_origin(Origin::BottomLeft)
+                    .with_position(x, single_line_baseline(font, size, height))
+                    .with_font_size(size);
+                subset_refs
+                    .extend(crate::text::write_text(caption, font, &options, height, wr).await?);
+            }
+        }
+
+        Ok(subset_refs)
+    }
+
+    /// Writes the given `text` as variable text (PDF spec 1.7 page 677), i.e. as marked content
+    /// that viewers replace when the field's value is edited. Single lines are vertically centered,
+    /// multiple lines start at the top of the field.
+    async fn write_variable_text(
+        &self,
+        text: &str,
+        multiline: bool,
+        font: &dyn Font,
+        width: f64,
+        height: f64,
+        wr: &mut dyn ContentStream,
+    ) -> Result<HashSet<SubsetRef>, io::Error> {
+        let size = self.font_size;
+        let line_height = line_height(font, size);
+        let y = if multiline {
+            height - PADDING - f64::from(font.ascent()) * size / 1000.0
+        } else {
+            single_line_baseline(font, size, height)
+        };
+
+        wr.begin_marked_content_sequence("Tx").await?;
+        wr.save_state().await?;
+        wr.rect(1.0, 1.0, width - 2.0, height - 2.0).await?;
+        wr.clip(FillRule::NonZero).await?;
+        wr.end_path().await?;
+        let options = TextOptions::new()
+            .with_origin(Origin::BottomLeft)
+            .with_position(PADDING, y)
+            .with_font_size(size)
+            .with_line_height(line_height);
+        let subset_refs = if text.is_empty() {
+            HashSet::new()
+        } else {
+            crate::text::write_text(text, font, &options, height, wr).await?
+        };
+        wr.restore_state().await?;
+        wr.end_marked_content().await?;
+
+        Ok(subset_refs)
+    }
+
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.read_only {
+            flags |= READ_ONLY;
+        }
+        if self.required {
+            flags |= REQUIRED;
+        }
+        flags
+            | match &self.kind {
+                FieldKind::Text {
+                    multiline: true, ..
+                } => MULTILINE,
+                FieldKind::Radio { .. } => RADIO | NO_TOGGLE_TO_OFF,
+                FieldKind::PushButton { .. } => PUSH_BUTTON,
+                FieldKind::Choice { combo: true, .. } => COMBO,
+                _ => 0,
+            }
+    }
+
+    fn field_type(&self) -> Name<'static> {
+        Name(match self.kind {
+            FieldKind::Text { .. } => "Tx",
+            FieldKind::Checkbox { .. } | FieldKind::Radio { .. } | FieldKind::PushButton { .. } => {
+                "Btn"
+            }
+            FieldKind::Choice { .. } => "Ch",
+        })
+    }
+}
+
+impl AcroForm {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The object id of the existing field the given `field`'s widget has to be added to, which is
+    /// only the case for radio buttons of the same group. Fails if there is any other field with
+    /// the same name.
+    pub(crate) fn existing_field_id(&self, field: &Field) -> Result<Option<ObjectId>, Error> {
+        let entry = match self.fields.iter().find(|e| e.field.name() == field.name()) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        match (&entry.field.kind, &field.kind) {
+            (FieldKind::Radio { .. }, FieldKind::Radio { .. }) => Ok(Some(entry.id.clone())),
+            _ => Err(Error::DuplicateField(field.name().to_string())),
+        }
+    }
+
+    /// Adds the given `widget` to the field with the given object `id`, which is created if it does
+    /// not exist yet. `da` is the resource name and reference of the font of the field's default
+    /// appearance.
+    pub(crate) fn add_widget(
+        &mut self,
+        id: ObjectId,
+        widget: Widget,
+        da: Option<(String, Reference<FontRef>)>,
+    ) {
+        if let Some(entry) = self.fields.iter_mut().find(|e| e.id == id) {
+            entry.widgets.push(widget);
+            return;
+        }
+
+        let da = da.map(|(font_name, font)| {
+            let da = format!("/{} {} Tf 0 g", font_name, widget.field.font_size);
+            self.fonts.insert(font_name, font);
+            da
+        });
+        self.fields.push(FieldEntry {
+            id,
+            field: widget.field.clone(),
+            da,
+            widgets: vec![widget],
+        });
+    }
+
+    /// Writes all fields and their widgets. Returns the interactive form dictionary of the
+    /// document's catalog.
+    pub(crate) async fn write<W: Write + Unpin>(
+        self,
+        doc: &mut DocWriter<W>,
+    ) -> Result<AcroFormDict, Error> {
+        for entry in &self.fields {
+            let field = &entry.field;
+            let v = match &field.kind {
+                FieldKind::Text { value, .. } if !value.is_empty() => {
+                    Some(FieldValue::Text(PdfText(value)))
+                }
+                FieldKind::Choice {
+                    value: Some(value), ..
+                } => Some(FieldValue::Text(PdfText(value))),
+                FieldKind::Checkbox { checked } => {
+                    Some(FieldValue::State(Name(if *checked { YES } else { OFF })))
+                }
+                FieldKind::Radio { .. } => Some(FieldValue::State(Name(
+                    entry
+                        .widgets
+                        .iter()
+                        .find_map(|widget| match &widget.field.kind {
+                            FieldKind::Radio {
+                                value,
+                                checked: true,
+                            } => Some(value.as_str()),
+                            _ => None,
+                        })
+                        .unwrap_or(OFF),
+                ))),
+                _ => None,
+            };
+            let dict = FieldDict {
+                ft: field.field_type(),
+                t: PdfText(&field.name),
+                tu: field.tooltip.as_deref().map(PdfText),
+                ff: field.flags(),
+                v,
+                da: entry.da.as_deref().map(PdfStr::Literal),
+                max_len: match field.kind {
+                    FieldKind::Text { max_length, .. } => max_length,
+                    _ => None,
+                },
+                opt: match &field.kind {
+                    FieldKind::Choice { options, .. } => {
+                        options.iter().map(|o| PdfText(o)).collect()
+                    }
+                    _ => Vec::new(),
+                },
+                kids: entry
+                    .widgets
+                    .iter()
+                    .map(|widget| Reference::new(widget.id.clone()))
+                    .collect(),
+            };
+            doc.write_object(Object::new(entry.id.id(), entry.id.rev(), dict))
+                .await?;
+
+            for widget in &entry.widgets {
+                let annotation = widget.to_annotation(Reference::new(entry.id.clone()));
+                doc.write_object(Object::new(widget.id.id(), widget.id.rev(), annotation))
+                    .await?;
+            }
+        }
+
+        Ok(AcroFormDict {
+            fields: self
+                .fields
+                .iter()
+                .map(|entry| Reference::new(entry.id.clone()))
+                .collect(),
+            dr: if self.fonts.is_empty() {
+                None
+            } else {
+                Some(DefaultResources { font: self.fonts })
+            },
+        })
+    }
+}
+
+impl Widget {
+    fn to_annotation(&self, parent: Reference<FieldRef>) -> WidgetAnnotation<'_> {
+        let reference = |form: &Form| Reference::new(form.id.clone());
+        let n = match self.appearances.as_slice() {
+            [(None, form)] => Appearance::Single(reference(form)),
+            appearances => Appearance::States(
+                appearances
+                    .iter()
+                    .filter_map(|(state, form)| state.as_deref().map(|s| (s, reference(form))))
+                    .collect(),
+            ),
+        };
+        let as_ = match &self.field.kind {
+            FieldKind::Checkbox { checked } => Some(Name(if *checked { YES } else { OFF })),
+            FieldKind::Radio { value, checked } => {
+                Some(Name(if *checked { value.as_str() } else { OFF }))
+            }
+            _ => None,
+        };
+        let (mk, a) = match &self.field.kind {
+            FieldKind::PushButton { caption, action } => (
+                Some(AppearanceCharacteristics {
+                    ca: PdfText(caption),
+                }),
+                action.as_ref().map(ButtonAction::to_action),
+            ),
+            _ => (None, None),
+        };
+        WidgetAnnotation {
+            subtype: Name("Widget"),
+            rect: self.rect,
+            f: 4,
+            parent,
+            ap: AppearanceDict { n },
+            as_,
+            mk,
+            a,
+        }
+    }
+}
+
+impl ButtonAction {
+    fn to_action(&self) -> ActionDict<'_> {
+        match self {
+            ButtonAction::Uri(uri) => ActionDict {
+                s: Name("URI"),
+                uri: Some(PdfStr::Literal(uri)),
+                f: None,
+                flags: None,
+            },
+            ButtonAction::SubmitForm(url) => ActionDict {
+                s: Name("SubmitForm"),
+                uri: None,
+                f: Some(UrlFileSpec {
+                    fs: Name("URL"),
+                    f: PdfStr::Literal(url),
+                }),
+                // submit the fields as HTML form (bit 3: ExportFormat)
+                flags: Some(1 << 2),
+            },
+            ButtonAction::ResetForm => ActionDict {
+                s: Name("ResetForm"),
+                uri: None,
+                f: None,
+                flags: None,
+            },
+        }
+    }
+}
+
+/// The distance between two lines of text of the given font `size`.
+fn line_height(font: &dyn Font, size: f64) -> f64 {
+    f64::from(font.ascent() - font.descent()) * size / 1000.0
+}
+
+/// The baseline of a single line of text that is vertically centered in a field of the given
+/// `height`.
+fn single_line_baseline(font: &dyn Font, size: f64, height: f64) -> f64 {
+    (height - line_height(font, size)) / 2.0 - f64::from(font.descent()) * size / 1000.0
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl<'a> Serialize for FieldValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            FieldValue::Text(text) => text.serialize(serializer),
+            FieldValue::State(name) => name.serialize(serializer),
+        }
+    }
+}
+
+impl<'a> Serialize for Appearance<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Appearance::Single(reference) => reference.serialize(serializer),
+            Appearance::States(states) => states.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(Field::text("a").flags(), 0);
+        assert_eq!(
+            Field::text("a")
+                .with_multiline(true)
+                .with_required(true)
+                .flags(),
+            MULTILINE | REQUIRED
+        );
+        assert_eq!(
+            Field::radio("a", "b").with_read_only(true).flags(),
+            READ_ONLY | RADIO | NO_TOGGLE_TO_OFF
+        );
+        assert_eq!(Field::combo_box("a", ["b"]).flags(), COMBO);
+        assert_eq!(Field::list_box("a", ["b"]).flags(), 0);
+        assert_eq!(Field::push_button("a", "b").flags(), PUSH_BUTTON);
+    }
+
+    #[test]
+    fn test_builders_ignore_other_kinds() {
+        let field = Field::checkbox("a").with_value("b").with_multiline(true);
+        assert_eq!(field, Field::checkbox("a"));
+        assert_eq!(
+            Field::radio("a", "b").appearance_states(),
+            vec![Some("b"), Some(OFF)]
+        );
+        assert_eq!(Field::text("a").appearance_states(), vec![None]);
+    }
+}
@@ -4,7 +4,7 @@ use crate::fonts::font::{Font, FontCollection, SingleFont, SubsetRef};
 use crate::writer::DocWriter;
 use async_std::io::prelude::Write;
 use serde::Serialize;
-use serde_pdf::{Object, ObjectId, PdfStr};
+use serde_pdf::{Object, ObjectId};
 
 impl<'a> Font for &'a pdfrs_afm::AfmFont {
     fn base_name(&self) -> &str {
@@ -17,9 +17,76 @@ impl<'a> Font for &'a pdfrs_afm::AfmFont {
 
     fn encode_into(&self, text: &str, buf: &mut Vec<u8>) -> Result<(SubsetRef, usize), io::Error> {
         buf.clear();
-        buf.extend_from_slice(PdfStr::Literal(text).to_string().as_bytes());
+        buf.push(b'(');
+        // characters that are not part of the WinAnsiEncoding have no width and are omitted
+        for code in text.chars().filter_map(win_ansi_code) {
+            match code {
+                b'\\' | b'(' | b')' => buf.extend_from_slice(&[b'\\', code]),
+                0x20..=0x7e => buf.push(code),
+                _ => buf.extend_from_slice(format!("\\{:03o}", code).as_bytes()),
+            }
+        }
+        buf.push(b')');
         Ok((SubsetRef(0), text.len()))
     }
+
+    fn supports_word_spacing(&self) -> bool {
+        true
+    }
+
+    fn char_width(&self, ch: char) -> u32 {
+        win_ansi_code(ch)
+            .and_then(|code| self.glyph_widths.get(&code))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    fn ascent(&self) -> i32 {
+        self.ascender
+    }
+
+    fn descent(&self) -> i32 {
+        self.descender
+    }
+
+    fn is_embedded(&self) -> bool {
+        false
+    }
+}
+
+/// Maps a unicode character to its code in the WinAnsiEncoding (Windows code page 1252).
+fn win_ansi_code(ch: char) -> Option<u8> {
+    match ch {
+        '\u{0}'..='\u{7f}' | '\u{a0}'..='\u{ff}' => Some(ch as u8),
+        '\u{20ac}' => Some(128),
+        '\u{201a}' => Some(130),
+        '\u{0192}' => Some(131),
+        '\u{201e}' => Some(132),
+        '\u{2026}' => Some(133),
+        '\u{2020}' => Some(134),
+        '\u{2021}' => Some(135),
+        '\u{02c6}' => Some(136),
+        '\u{2030}' => Some(137),
+        '\u{0160}' => Some(138),
+        '\u{2039}' => Some(139),
+        '\u{0152}' => Some(140),
+        '\u{017d}' => Some(142),
+        '\u{2018}' => Some(145),
+        '\u{2019}' => Some(146),
+        '\u{201c}' => Some(147),
+        '\u{201d}' => Some(148),
+        '\u{2022}' => Some(149),
+        '\u{2013}' => Some(150),
+        '\u{2014}' => Some(151),
+        '\u{02dc}' => Some(152),
+        '\u{2122}' => Some(153),
+        '\u{0161}' => Some(154),
+        '\u{203a}' => Some(155),
+        '\u{0153}' => Some(156),
+        '\u{017e}' => Some(158),
+        '\u{0178}' => Some(159),
+        _ => None,
+    }
 }
 
 #[cfg(any(feature = "afm", test))]
@@ -85,6 +152,15 @@ mod test {
         assert_eq!(&String::from_utf8_lossy(&buf), "(Hello)");
     }
 
+    #[test]
+    fn test_text_width() {
+        // H (722) + e (556) + l (222) + l (222) + o (556)
+        assert_eq!(HELVETICA.deref().text_width("Hello", 10.0), 22.78);
+        // kerning: W (944) - 30 + o (556)
+        assert_eq!(HELVETICA.deref().text_width("Wo", 10.0), 14.7);
+        assert_eq!(HELVETICA.deref().text_width("€", 10.0), 5.56);
+    }
+
     #[test]
     fn test_encode_reserved_characters() {
         let mut buf = Vec::new();
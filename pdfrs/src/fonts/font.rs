@@ -23,12 +23,45 @@ pub trait Font {
     fn base_name(&self) -> &str;
     fn kerning(&self, lhs: char, rhs: char) -> Option<i32>;
     fn encode_into(&self, text: &str, buf: &mut Vec<u8>) -> Result<(SubsetRef, usize), io::Error>;
+
+    /// Whether the font encodes the space character as the single-byte code 32, which is the only
+    /// code word spacing (`Tw`) is applied to. Subset fonts assign their codes on their own.
+    fn supports_word_spacing(&self) -> bool;
+
+    /// The horizontal advance of the given character in glyph space units (1/1000 em).
+    fn char_width(&self, ch: char) -> u32;
+
+    /// The maximum height above the baseline of the font's glyphs in glyph space units.
+    fn ascent(&self) -> i32;
+
+    /// The maximum depth below the baseline of the font's glyphs in glyph space units (a negative
+    /// number).
+    fn descent(&self) -> i32;
+
+    /// Whether the font program is embedded into the document (as opposed to fonts that are
+    /// expected to be provided by the viewer).
+    fn is_embedded(&self) -> bool;
+
+    /// The width of the given `text` in points when written with the given font `size` (kerning
+    /// included).
+    fn text_width(&self, text: &str, size: f64) -> f64 {
+        let mut width = 0i64;
+        let mut prev = None;
+        for ch in text.chars() {
+            if let Some(kerning) = prev.and_then(|p| self.kerning(p, ch)) {
+                width += i64::from(kerning);
+            }
+            width += i64::from(self.char_width(ch));
+            prev = Some(ch);
+        }
+        width as f64 * size / 1000.0
+    }
 }
 
-#[derive(Hash, Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Hash, Default, PartialEq, Eq, Clone, Copy)]
 pub struct SingleFont(pub(super) usize);
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub struct SubsetRef(pub(super) usize);
 
 impl SubsetRef {
@@ -14,6 +14,6 @@ mod font;
 mod otf;
 
 pub use self::otf::OpenTypeFont;
-pub use font::{Font, FontCollection, SubsetRef};
+pub use font::{Font, FontCollection, SingleFont, SubsetRef};
 #[cfg(any(feature = "afm", test))]
 pub use pdfrs_afm::*;
@@ -88,6 +88,31 @@ impl Font for OpenTypeFont {
 
         Ok((SubsetRef(ix), len))
     }
+
+    fn supports_word_spacing(&self) -> bool {
+        false
+    }
+
+    fn char_width(&self, ch: char) -> u32 {
+        // control characters are not encoded and thus don't have a width
+        if ch < ' ' {
+            0
+        } else {
+            self.font.char_width(ch)
+        }
+    }
+
+    fn ascent(&self) -> i32 {
+        self.font.ascent()
+    }
+
+    fn descent(&self) -> i32 {
+        self.font.descent()
+    }
+
+    fn is_embedded(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -314,7 +339,7 @@ impl UnicodeSubset {
 
     #[allow(clippy::needless_lifetimes)]
     fn chars<'a>(&'a self) -> impl Iterator<Item = Option<(u8, char)>> + 'a {
-        (self.first_char()..self.last_char())
+        (self.first_char()..=self.last_char())
             .map(move |b| self.mapping_inverted.get(&b).map(|ch| (b, *ch)))
     }
 
@@ -323,7 +348,7 @@ impl UnicodeSubset {
     }
 
     fn last_char(&self) -> u8 {
-        self.current_codepoint + 1
+        self.current_codepoint
     }
 }
 
@@ -0,0 +1,195 @@
+use std::io;
+use std::pin::Pin;
+
+use crate::fonts::SubsetRef;
+use crate::image::XObjectType;
+use crate::page::{Rect, Resources};
+use crate::stream::{ContentStream, Filter, Stream};
+use crate::writer::DocWriter;
+use async_std::io::prelude::{Write, WriteExt};
+use async_std::task::{Context, Poll};
+use futures_io::AsyncWrite;
+use serde::Serialize;
+use serde_pdf::{ObjectId, Reference};
+
+/// A handle to a form XObject recorded with [`Document::begin_form`](crate::Document::begin_form)
+/// and [`Document::end_form`](crate::Document::end_form). The form is only written once, no matter
+/// on how many pages it is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Form {
+    pub(crate) id: ObjectId,
+    width: f64,
+    height: f64,
+}
+
+/// Records the content of a form XObject in memory. It supports the same operators as the content
+/// stream of a page, but its content is only written once the document ends (as the content stream
+/// of the current page is still being written while the form is recorded).
+pub(crate) struct FormRecorder {
+    id: ObjectId,
+    width: f64,
+    height: f64,
+    content: Vec<u8>,
+    prev_subset: Option<SubsetRef>,
+}
+
+/// The recorded content of a form XObject that is written to the document once it ends.
+pub(crate) struct FormData {
+    width: f64,
+    height: f64,
+    content: Vec<u8>,
+    resources: Resources,
+}
+
+/// A form dictionary (PDF spec 1.7 page 358).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "XObject")]
+struct FormXObject {
+    subtype: XObjectType,
+    #[serde(rename = "BBox")]
+    b_box: Rect,
+    resources: Resources,
+    length: Reference<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    filter: Vec<Filter>,
+}
+
+impl Form {
+    /// The width of the form's bounding box in points.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// The height of the form's bounding box in points.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// The name used to refer to the form from within content streams.
+    pub(crate) fn resource_name(&self) -> String {
+        format!("Fm{}", self.id.id())
+    }
+}
+
+impl FormRecorder {
+    /// Starts recording a form XObject with the given object `id`, whose bounding box spans from
+    /// the origin to `width`, `height`.
+    pub(crate) fn new(id: ObjectId, width: f64, height: f64) -> Self {
+        FormRecorder {
+            id,
+            width,
+            height,
+            content: Vec::new(),
+            prev_subset: None,
+        }
+    }
+
+    /// The height of the form's bounding box, used to convert coordinates into the form's space.
+    pub(crate) fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// Ends the recording. Returns the handle used to draw the form and the data to write once the
+    /// document ends.
+    pub(crate) fn end(self, resources: Resources) -> (Form, FormData) {
+        let form = Form {
+            id: self.id,
+            width: self.width,
+            height: self.height,
+        };
+        let data = FormData {
+            width: self.width,
+            height: self.height,
+            content: self.content,
+            resources,
+        };
+        (form, data)
+    }
+}
+
+impl FormData {
+    /// Writes the form as a form XObject with the given object `id`.
+    pub(crate) async fn write<W: Write + Unpin>(
+        self,
+        id: ObjectId,
+        doc: DocWriter<W>,
+        compressed: bool,
+    ) -> Result<DocWriter<W>, io::Error> {
+        let FormData {
+            width,
+            height,
+            content,
+            resources,
+        } = self;
+        let mut stream =
+            Stream::start_with_dict(doc, id, compressed, |length, filter| FormXObject {
+                subtype: XObjectType::Form,
+                b_box: (0.0, 0.0, width, height),
+                resources,
+                length,
+                filter,
+            })
+            .await?;
+        stream.write_all(&content).await?;
+        stream.end().await
+    }
+}
+
+impl ContentStream for FormRecorder {
+    fn prev_subset(&mut self) -> &mut Option<SubsetRef> {
+        &mut self.prev_subset
+    }
+}
+
+impl AsyncWrite for FormRecorder {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.content).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.content).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.content).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::idseq::IdSeq;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[async_std::test]
+    async fn test_record_form() {
+        let mut recorder = FormRecorder::new(ObjectId::new(1, 0), 100.0, 50.0);
+        recorder.rect(0.0, 0.0, 100.0, 50.0).await.unwrap();
+        recorder.fill(crate::FillRule::NonZero).await.unwrap();
+        let (form, data) = recorder.end(Resources {
+            font: HashMap::new(),
+            ext_g_state: BTreeMap::new(),
+            color_space: BTreeMap::new(),
+            x_object: BTreeMap::new(),
+        });
+        assert_eq!(form.resource_name(), "Fm1");
+
+        let mut buf = Vec::new();
+        let mut doc = DocWriter::new(&mut buf, IdSeq::new(1));
+        let id = doc.reserve_object_id();
+        data.write(id, doc, false).await.unwrap();
+
+        let result = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            &result[..result.find("endstream").unwrap()],
+            "1 0 obj\n<<\n\t/Type /XObject\n\t/Subtype /Form\n\t/BBox [0 0 100 50]\n\
+             \t/Resources <<\n\t\t/Type /Resources\n\t>>\n\t/Length 2 0 R\n>>\nstream\n\
+             0.000 0.000 100.000 50.000 re\nf\n\n"
+        );
+    }
+}
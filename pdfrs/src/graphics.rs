@@ -0,0 +1,234 @@
+use std::hash::{Hash, Hasher};
+
+use serde::Serialize;
+
+/// A transformation matrix `[a b c d e f]` mapping `x`, `y` to `a*x + c*y + e`, `b*x + d*y + f`.
+///
+/// Like all other coordinates of a [`Document`](crate::Document), transformations are expressed
+/// in a coordinate system with its origin in the top-left corner of the page and `y` growing
+/// downwards. Positive rotation angles thus rotate clockwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+
+    pub fn translate(tx: f64, ty: f64) -> Self {
+        Transform::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    /// Rotates clockwise around the origin by the given angle in degrees.
+    pub fn rotate(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Transform::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    pub fn scale(sx: f64, sy: f64) -> Self {
+        Transform::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// Skews the `x` axis by `x_degrees` and the `y` axis by `y_degrees`.
+    pub fn skew(x_degrees: f64, y_degrees: f64) -> Self {
+        Transform::new(
+            1.0,
+            y_degrees.to_radians().tan(),
+            x_degrees.to_radians().tan(),
+            1.0,
+            0.0,
+            0.0,
+        )
+    }
+
+    /// Returns a transformation that first applies `self` and then `next`.
+    pub fn then(self, next: Transform) -> Self {
+        Transform {
+            a: self.a * next.a + self.b * next.c,
+            b: self.a * next.b + self.b * next.d,
+            c: self.c * next.a + self.d * next.c,
+            d: self.c * next.b + self.d * next.d,
+            e: self.e * next.a + self.f * next.c + next.e,
+            f: self.e * next.b + self.f * next.d + next.f,
+        }
+    }
+
+    /// Converts the transformation into PDF's default user space (origin bottom-left) of a page
+    /// with the given `page_height`.
+    pub(crate) fn to_user_space(self, page_height: f64) -> Self {
+        let flip = Transform::new(1.0, 0.0, 0.0, -1.0, 0.0, page_height);
+        flip.then(self).then(flip)
+    }
+
+    /// Converts the transformation into a mapping from PDF's default user space of a form with the
+    /// given `form_height` into the default user space of a page with the given `page_height`.
+    pub(crate) fn form_to_user_space(self, form_height: f64, page_height: f64) -> Self {
+        let flip = |height| Transform::new(1.0, 0.0, 0.0, -1.0, 0.0, height);
+        flip(form_height).then(self).then(flip(page_height))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+/// The shape of the endpoints of stroked open paths (PDF spec 1.7 page 216).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    Butt = 0,
+    Round = 1,
+    ProjectingSquare = 2,
+}
+
+/// The shape of the corners of stroked paths (PDF spec 1.7 page 216).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter = 0,
+    Round = 1,
+    Bevel = 2,
+}
+
+/// A graphics state parameter dictionary (PDF spec 1.7 page 220), used for the parameters that
+/// cannot be set directly inside of content streams.
+#[derive(Debug, Default, Clone, Serialize)]
+pub(crate) struct ExtGState {
+    #[serde(rename = "ca", skip_serializing_if = "Option::is_none")]
+    fill_opacity: Option<f64>,
+    #[serde(rename = "CA", skip_serializing_if = "Option::is_none")]
+    stroke_opacity: Option<f64>,
+}
+
+impl ExtGState {
+    pub fn fill_opacity(opacity: f64) -> Self {
+        ExtGState {
+            fill_opacity: Some(normalize_opacity(opacity)),
+            ..Default::default()
+        }
+    }
+
+    pub fn stroke_opacity(opacity: f64) -> Self {
+        ExtGState {
+            stroke_opacity: Some(normalize_opacity(opacity)),
+            ..Default::default()
+        }
+    }
+
+    /// Graphic states are deduplicated by the exact bits of their (normalized) parameter values.
+    fn key(&self) -> (Option<u64>, Option<u64>) {
+        (
+            self.fill_opacity.map(f64::to_bits),
+            self.stroke_opacity.map(f64::to_bits),
+        )
+    }
+}
+
+impl PartialEq for ExtGState {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ExtGState {}
+
+impl Hash for ExtGState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Clamps an opacity to `0.0..=1.0`, without negative zero. NaN is treated as fully opaque.
+fn normalize_opacity(opacity: f64) -> f64 {
+    if opacity.is_nan() {
+        1.0
+    } else {
+        // adding positive zero turns negative zero into positive zero
+        opacity.clamp(0.0, 1.0) + 0.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_transform_eq(lhs: Transform, rhs: Transform) {
+        for (l, r) in [
+            (lhs.a, rhs.a),
+            (lhs.b, rhs.b),
+            (lhs.c, rhs.c),
+            (lhs.d, rhs.d),
+            (lhs.e, rhs.e),
+            (lhs.f, rhs.f),
+        ] {
+            assert!((l - r).abs() < 1e-9, "{:?} != {:?}", lhs, rhs);
+        }
+    }
+
+    #[test]
+    fn test_ext_g_state_normalization() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |state: &ExtGState| {
+            let mut hasher = DefaultHasher::new();
+            state.hash(&mut hasher);
+            hasher.finish()
+        };
+        for (lhs, rhs) in [
+            (0.0, -0.0),
+            (f64::NAN, f64::NAN),
+            (f64::NAN, 1.0),
+            (2.0, 1.0),
+        ] {
+            let (lhs, rhs) = (ExtGState::fill_opacity(lhs), ExtGState::fill_opacity(rhs));
+            assert_eq!(lhs, rhs);
+            assert_eq!(hash(&lhs), hash(&rhs));
+        }
+        assert_ne!(ExtGState::fill_opacity(0.5), ExtGState::stroke_opacity(0.5));
+    }
+
+    #[test]
+    fn test_transform_then() {
+        // rotate around the point 100, 100
+        let t = Transform::translate(-100.0, -100.0)
+            .then(Transform::rotate(90.0))
+            .then(Transform::translate(100.0, 100.0));
+        assert_transform_eq(t, Transform::new(0.0, 1.0, -1.0, 0.0, 200.0, 0.0));
+    }
+
+    #[test]
+    fn test_transform_to_user_space() {
+        assert_transform_eq(
+            Transform::translate(10.0, 20.0).to_user_space(800.0),
+            Transform::translate(10.0, -20.0),
+        );
+        // clockwise on the page is clockwise in user space as well (i.e. a negative angle)
+        assert_transform_eq(
+            Transform::rotate(90.0).to_user_space(800.0),
+            Transform::new(0.0, -1.0, 1.0, 0.0, -800.0, 800.0),
+        );
+    }
+
+    #[test]
+    fn test_serialize_ext_g_state() {
+        let state = ExtGState {
+            fill_opacity: Some(0.5),
+            stroke_opacity: None,
+        };
+        assert_eq!(
+            serde_pdf::to_string(&state).unwrap(),
+            "<<\n\t/Type /ExtGState\n\t/ca 0.5\n>>"
+        );
+    }
+}
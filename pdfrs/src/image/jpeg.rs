@@ -0,0 +1,143 @@
+use super::ImageError;
+use crate::color::DeviceColorSpace;
+
+/// The properties of a JPEG image required to embed it into a PDF without decoding it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JpegInfo {
+    pub width: u32,
+    pub height: u32,
+    pub color_space: DeviceColorSpace,
+    pub bits_per_component: u8,
+    /// Whether the image contains inverted CMYK values, as written by Adobe applications.
+    pub inverted: bool,
+}
+
+/// Reads the image properties from the frame header (SOF) and the Adobe application segment
+/// (APP14) of the given JPEG `data`.
+pub(crate) fn parse(data: &[u8]) -> Result<JpegInfo, ImageError> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(ImageError::InvalidJpeg("missing start of image marker"));
+    }
+
+    let mut frame = None;
+    let mut adobe = false;
+    let mut pos = 2;
+    while pos + 1 < data.len() {
+        if data[pos] != 0xFF {
+            return Err(ImageError::InvalidJpeg("expected marker"));
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // fill bytes
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // markers without a segment
+            0x01 | 0xD0..=0xD7 => {
+                pos += 2;
+                continue;
+            }
+            // start of scan or end of image, the image properties must have been read already
+            0xDA | 0xD9 => break,
+            _ => {}
+        }
+
+        let len = data
+            .get(pos + 2..pos + 4)
+            .map(|len| usize::from(u16::from_be_bytes([len[0], len[1]])))
+            .ok_or(ImageError::InvalidJpeg("unexpected end of data"))?;
+        let segment = data
+            .get(pos + 4..pos + 2 + len)
+            .ok_or(ImageError::InvalidJpeg("unexpected end of data"))?;
+        match marker {
+            // baseline, extended sequential and progressive (Huffman coding)
+            0xC0..=0xC2 => {
+                if segment.len() < 6 {
+                    return Err(ImageError::InvalidJpeg("frame header too short"));
+                }
+                let height = u16::from_be_bytes([segment[1], segment[2]]);
+                let width = u16::from_be_bytes([segment[3], segment[4]]);
+                let color_space = match segment[5] {
+                    1 => DeviceColorSpace::Gray,
+                    3 => DeviceColorSpace::Rgb,
+                    4 => DeviceColorSpace::Cmyk,
+                    _ => return Err(ImageError::UnsupportedJpeg("number of components")),
+                };
+                frame = Some((segment[0], u32::from(width), u32::from(height), color_space));
+            }
+            // lossless, hierarchical and arithmetic coding
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                return Err(ImageError::UnsupportedJpeg("coding process"));
+            }
+            0xEE => adobe = segment.starts_with(b"Adobe"),
+            _ => {}
+        }
+
+        pos += 2 + len;
+    }
+
+    let (bits_per_component, width, height, color_space) =
+        frame.ok_or(ImageError::InvalidJpeg("missing frame header"))?;
+    if width == 0 || height == 0 {
+        return Err(ImageError::UnsupportedJpeg("image dimensions"));
+    }
+
+    Ok(JpegInfo {
+        width,
+        height,
+        color_space,
+        bits_per_component,
+        inverted: adobe && color_space == DeviceColorSpace::Cmyk,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_rgb() {
+        let info = parse(include_bytes!("../../../images/rgb.jpg")).unwrap();
+        assert_eq!(
+            info,
+            JpegInfo {
+                width: 48,
+                height: 32,
+                color_space: DeviceColorSpace::Rgb,
+                bits_per_component: 8,
+                inverted: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_progressive() {
+        let info = parse(include_bytes!("../../../images/rgb_progressive.jpg")).unwrap();
+        assert_eq!((info.width, info.height), (48, 32));
+        assert_eq!(info.color_space, DeviceColorSpace::Rgb);
+    }
+
+    #[test]
+    fn test_parse_gray() {
+        let info = parse(include_bytes!("../../../images/gray.jpg")).unwrap();
+        assert_eq!(info.color_space, DeviceColorSpace::Gray);
+        assert!(!info.inverted);
+    }
+
+    #[test]
+    fn test_parse_adobe_cmyk() {
+        let info = parse(include_bytes!("../../../images/cmyk_adobe.jpg")).unwrap();
+        assert_eq!(info.color_space, DeviceColorSpace::Cmyk);
+        assert!(info.inverted);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(parse(b"\x89PNG"), Err(ImageError::InvalidJpeg(_))));
+        assert!(matches!(
+            parse(&[0xFF, 0xD8, 0xFF, 0xC0, 0x00]),
+            Err(ImageError::InvalidJpeg(_))
+        ));
+    }
+}
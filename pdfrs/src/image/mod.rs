@@ -0,0 +1,340 @@
+mod jpeg;
+mod png;
+
+use std::io;
+
+use crate::color::DeviceColorSpace;
+use crate::stream::{DecodeParms, Filter, Stream};
+use crate::writer::DocWriter;
+use async_std::io::prelude::{Write, WriteExt};
+use jpeg::JpegInfo;
+use png::{PngColorSpace, PngData, PngImage};
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, ObjectId, PdfBytes, Reference};
+
+/// A handle to an image added to a document with [`Document::add_jpeg`](crate::Document::add_jpeg)
+/// or [`Document::add_png`](crate::Document::add_png). The image is only written once, no matter on
+/// how many pages it is drawn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Image {
+    pub(crate) id: ObjectId,
+    width: u32,
+    height: u32,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImageError {
+    #[error("Invalid JPEG image: {0}")]
+    InvalidJpeg(&'static str),
+    #[error("Unsupported JPEG image: {0}")]
+    UnsupportedJpeg(&'static str),
+    #[error("Invalid PNG image: {0}")]
+    InvalidPng(&'static str),
+    #[error("Unsupported PNG image: {0}")]
+    UnsupportedPng(&'static str),
+}
+
+/// The encoded data of an image that is written to the document once it ends.
+pub(crate) enum ImageData {
+    Jpeg { data: Vec<u8>, info: JpegInfo },
+    Png(PngImage),
+}
+
+/// A type used to create PDF references to external objects (e.g. images).
+pub(crate) type XObjectRef = ();
+
+#[derive(Serialize)]
+pub(crate) enum XObjectType {
+    Image,
+    Form,
+}
+
+/// An image dictionary (PDF spec 1.7 page 340).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "XObject")]
+struct ImageXObject<'a> {
+    subtype: XObjectType,
+    width: u32,
+    height: u32,
+    color_space: ImageColorSpace<'a>,
+    bits_per_component: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decode: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mask: Option<&'a [u16]>,
+    #[serde(rename = "SMask", skip_serializing_if = "Option::is_none")]
+    s_mask: Option<Reference<XObjectRef>>,
+    length: Reference<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    filter: Vec<Filter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decode_parms: Option<Vec<Option<DecodeParms>>>,
+}
+
+enum ImageColorSpace<'a> {
+    Device(DeviceColorSpace),
+    /// An RGB palette (PDF spec 1.7 page 262).
+    Indexed(&'a [u8]),
+}
+
+impl<'a> Serialize for ImageColorSpace<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            ImageColorSpace::Device(color_space) => color_space.serialize(serializer),
+            ImageColorSpace::Indexed(palette) => (
+                Name("Indexed"),
+                DeviceColorSpace::Rgb,
+                palette.len() / 3 - 1,
+                PdfBytes(palette),
+            )
+                .serialize(serializer),
+        }
+    }
+}
+
+impl<'a> ImageXObject<'a> {
+    fn new(
+        width: u32,
+        height: u32,
+        color_space: ImageColorSpace<'a>,
+        bits_per_component: u8,
+        length: Reference<usize>,
+        filter: Vec<Filter>,
+    ) -> Self {
+        ImageXObject {
+            subtype: XObjectType::Image,
+            width,
+            height,
+            color_space,
+            bits_per_component,
+            decode: None,
+            mask: None,
+            s_mask: None,
+            length,
+            decode_parms: Filter::decode_parms(&filter),
+            filter,
+        }
+    }
+}
+
+impl Image {
+    /// The width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The name used to refer to the image from within content streams.
+    pub(crate) fn resource_name(&self) -> String {
+        format!("Im{}", self.id.id())
+    }
+}
+
+impl ImageData {
+    /// Reads the properties of the given JPEG `data`, which is embedded as is (`DCTDecode`).
+    pub(crate) fn jpeg(data: Vec<u8>) -> Result<Self, ImageError> {
+        let info = jpeg::parse(&data)?;
+        Ok(ImageData::Jpeg { data, info })
+    }
+
+    /// Reads the given PNG `data`. Images without transparency are embedded without being
+    /// decoded, all others are split into the color and alpha channel (as soft mask).
+    pub(crate) fn png(data: &[u8]) -> Result<Self, ImageError> {
+        Ok(ImageData::Png(png::parse(data)?))
+    }
+
+    /// Returns a handle for the image, which is written to the object with the given `id`.
+    pub(crate) fn to_image(&self, id: ObjectId) -> Image {
+        let (width, height) = match self {
+            ImageData::Jpeg { info, .. } => (info.width, info.height),
+            ImageData::Png(image) => (image.width, image.height),
+        };
+        Image { id, width, height }
+    }
+
+    /// Writes the image as an image XObject with the given object `id`.
+    pub(crate) async fn write<W: Write + Unpin>(
+        self,
+        id: ObjectId,
+        doc: DocWriter<W>,
+        compressed: bool,
+    ) -> Result<DocWriter<W>, io::Error> {
+        match self {
+            ImageData::Jpeg { data, info } => {
+                // JPEG data is already compressed, so it is passed through without any further
+                // compression
+                let mut stream = Stream::start_with_dict(doc, id, false, |length, _| {
+                    let mut dict = ImageXObject::new(
+                        info.width,
+                        info.height,
+                        ImageColorSpace::Device(info.color_space),
+                        info.bits_per_component,
+                        length,
+                        vec![Filter::DCTDecode],
+                    );
+                    if info.inverted {
+                        dict.decode = Some([1.0, 0.0].repeat(info.color_space.components()));
+                    }
+                    dict
+                })
+                .await?;
+                stream.write_all(&data).await?;
+                stream.end().await
+            }
+            ImageData::Png(image) => write_png(image, id, doc, compressed).await,
+        }
+    }
+}
+
+/// Writes the given PNG `image` as an image XObject with the given object `id`, and its alpha
+/// channel (if there is any) as a separate soft mask image.
+async fn write_png<W: Write + Unpin>(
+    image: PngImage,
+    id: ObjectId,
+    mut doc: DocWriter<W>,
+    compressed: bool,
+) -> Result<DocWriter<W>, io::Error> {
+    let PngImage {
+        width,
+        height,
+        color_space,
+        bits_per_component,
+        color_key,
+        data,
+    } = image;
+    // the alpha values of transparent palette entries always have 8 bits
+    let alpha_bits = match color_space {
+        PngColorSpace::Device(_) => bits_per_component,
+        PngColorSpace::Indexed(_) => 8,
+    };
+    let color_space = match &color_space {
+        PngColorSpace::Device(color_space) => ImageColorSpace::Device(*color_space),
+        PngColorSpace::Indexed(palette) => ImageColorSpace::Indexed(palette),
+    };
+
+    match data {
+        PngData::Predicted { data, colors } => {
+            // the compressed PNG data is passed through and decoded using PNG predictors
+            let filter = Filter::FlateDecode(Some(DecodeParms {
+                predictor: 15,
+                colors,
+                bits_per_component,
+                columns: width,
+            }));
+            let mut stream = Stream::start_with_dict(doc, id, false, |length, _| {
+                let mut dict = ImageXObject::new(
+                    width,
+                    height,
+                    color_space,
+                    bits_per_component,
+                    length,
+                    vec![filter],
+                );
+                dict.mask = color_key.as_deref();
+                dict
+            })
+            .await?;
+            stream.write_all(&data).await?;
+            stream.end().await
+        }
+        PngData::Decoded { color, alpha } => {
+            let s_mask_id = alpha.as_ref().map(|_| doc.reserve_object_id());
+            let mut stream = Stream::start_with_dict(doc, id, compressed, |length, filter| {
+                let mut dict = ImageXObject::new(
+                    width,
+                    height,
+                    color_space,
+                    bits_per_component,
+                    length,
+                    filter,
+                );
+                dict.mask = color_key.as_deref();
+                dict.s_mask = s_mask_id.clone().map(Reference::new);
+                dict
+            })
+            .await?;
+            stream.write_all(&color).await?;
+            doc = stream.end().await?;
+
+            if let (Some(s_mask_id), Some(alpha)) = (s_mask_id, alpha) {
+                let mut stream =
+                    Stream::start_with_dict(doc, s_mask_id, compressed, |length, filter| {
+                        ImageXObject::new(
+                            width,
+                            height,
+                            ImageColorSpace::Device(DeviceColorSpace::Gray),
+                            alpha_bits,
+                            length,
+                            filter,
+                        )
+                    })
+                    .await?;
+                stream.write_all(&alpha).await?;
+                doc = stream.end().await?;
+            }
+
+            Ok(doc)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::idseq::IdSeq;
+
+    #[async_std::test]
+    async fn test_write_inverted_cmyk_jpeg() {
+        let image =
+            ImageData::jpeg(include_bytes!("../../../images/cmyk_adobe.jpg").to_vec()).unwrap();
+        let mut buf = Vec::new();
+        let mut doc = DocWriter::new(&mut buf, IdSeq::new(1));
+        let id = doc.reserve_object_id();
+        image.write(id, doc, false).await.unwrap();
+
+        let result = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            &result[..result.find("stream").unwrap()],
+            "1 0 obj\n<<\n\t/Type /XObject\n\t/Subtype /Image\n\t/Width 48\n\t/Height 32\n\
+             \t/ColorSpace /DeviceCMYK\n\t/BitsPerComponent 8\n\t/Decode [1 0 1 0 1 0 1 0]\n\
+             \t/Length 2 0 R\n\t/Filter [/DCTDecode]\n>>\n"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_write_predicted_palette_png() {
+        let png = PngImage {
+            width: 40,
+            height: 30,
+            color_space: PngColorSpace::Indexed(vec![0, 0, 0, 255, 0, 0]),
+            bits_per_component: 2,
+            color_key: None,
+            data: PngData::Predicted {
+                data: Vec::new(),
+                colors: 1,
+            },
+        };
+
+        let mut buf = Vec::new();
+        let mut doc = DocWriter::new(&mut buf, IdSeq::new(1));
+        let id = doc.reserve_object_id();
+        ImageData::Png(png).write(id, doc, true).await.unwrap();
+
+        let result = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            &result[..result.find("stream").unwrap()],
+            "1 0 obj\n<<\n\t/Type /XObject\n\t/Subtype /Image\n\t/Width 40\n\t/Height 30\n\
+             \t/ColorSpace [/Indexed /DeviceRGB 1 <000000FF0000>]\n\t/BitsPerComponent 2\n\
+             \t/Length 2 0 R\n\t/Filter [/FlateDecode]\n\t/DecodeParms [<<\n\t\t/Predictor 15\n\
+             \t\t/Colors 1\n\t\t/BitsPerComponent 2\n\t\t/Columns 40\n\t>>]\n>>\n"
+        );
+    }
+}
@@ -0,0 +1,465 @@
+use std::io::Read;
+
+use super::ImageError;
+use crate::color::DeviceColorSpace;
+use flate2::read::ZlibDecoder;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The origin and size of the passes of the Adam7 interlacing method: `(x, y, dx, dy)`.
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// A PNG image prepared to be embedded into a PDF.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PngImage {
+    pub width: u32,
+    pub height: u32,
+    pub color_space: PngColorSpace,
+    pub bits_per_component: u8,
+    /// The range of colors (`[min max]` for each component) that is masked out (i.e. transparent).
+    pub color_key: Option<Vec<u16>>,
+    pub data: PngData,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PngColorSpace {
+    Device(DeviceColorSpace),
+    /// An RGB palette (3 bytes per color) indexed by the samples of the image.
+    Indexed(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PngData {
+    /// The unmodified zlib compressed data of the PNG, which can be decoded by PDF readers using
+    /// PNG predictors.
+    Predicted { data: Vec<u8>, colors: u8 },
+    /// The decoded samples of the color channels and the separate samples of the alpha channel
+    /// (if there is any).
+    Decoded {
+        color: Vec<u8>,
+        alpha: Option<Vec<u8>>,
+    },
+}
+
+struct Header {
+    width: u32,
+    height: u32,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+/// Reads the given PNG `data`. If possible, the compressed image data is kept as is, otherwise
+/// (alpha channels, interlacing and transparent palette entries) the image is decoded.
+pub(crate) fn parse(data: &[u8]) -> Result<PngImage, ImageError> {
+    if !data.starts_with(SIGNATURE) {
+        return Err(ImageError::InvalidPng("missing signature"));
+    }
+
+    let mut header = None;
+    let mut palette = None;
+    let mut transparency = None;
+    let mut compressed = Vec::new();
+    let mut pos = SIGNATURE.len();
+    while pos + 8 <= data.len() {
+        let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]);
+        let kind = &data[pos + 4..pos + 8];
+        let chunk = data
+            .get(pos + 8..pos + 8 + len as usize)
+            .ok_or(ImageError::InvalidPng("unexpected end of data"))?;
+        match kind {
+            b"IHDR" => header = Some(parse_header(chunk)?),
+            b"PLTE" => palette = Some(chunk.to_vec()),
+            b"tRNS" => transparency = Some(chunk.to_vec()),
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+        // skip chunk data and CRC
+        pos += 12 + len as usize;
+    }
+
+    let header = header.ok_or(ImageError::InvalidPng("missing header"))?;
+    let color_space = match header.color_type {
+        0 | 4 => PngColorSpace::Device(DeviceColorSpace::Gray),
+        2 | 6 => PngColorSpace::Device(DeviceColorSpace::Rgb),
+        _ => match palette {
+            Some(palette) if palette.len() % 3 == 0 && !palette.is_empty() => {
+                PngColorSpace::Indexed(palette)
+            }
+            _ => return Err(ImageError::InvalidPng("missing or invalid palette")),
+        },
+    };
+    let color_channels = if header.color_type == 2 || header.color_type == 6 {
+        3
+    } else {
+        1
+    };
+
+    // transparent palette entries require a separate alpha channel, while transparent gray and RGB
+    // colors are masked using a color key
+    let palette_alpha = transparency.as_deref().filter(|_| header.color_type == 3);
+    let key = transparency.as_deref().filter(|_| header.color_type != 3);
+    let has_alpha = header.color_type == 4 || header.color_type == 6 || palette_alpha.is_some();
+
+    if !has_alpha && !header.interlaced {
+        return Ok(PngImage {
+            width: header.width,
+            height: header.height,
+            color_space,
+            bits_per_component: header.bit_depth,
+            color_key: key.and_then(|t| color_key(t, color_channels, 1)),
+            data: PngData::Predicted {
+                data: compressed,
+                colors: color_channels as u8,
+            },
+        });
+    }
+
+    let mut decoded = Vec::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut decoded)
+        .map_err(|_| ImageError::InvalidPng("corrupt image data"))?;
+    let samples = decode_samples(&header, &decoded)?;
+
+    // The decoded image is written with 8 or 16 bits per component. Gray samples with less bits
+    // are scaled accordingly, palette indices are kept as is.
+    let wide = header.bit_depth == 16;
+    let scale = if header.color_type == 0 && header.bit_depth < 8 {
+        255 / ((1 << header.bit_depth) - 1)
+    } else {
+        1
+    };
+    let channels = channel_count(header.color_type);
+    let mut color = Vec::with_capacity(samples.len() / channels * color_channels);
+    let mut alpha = Vec::new();
+    let push = |buf: &mut Vec<u8>, sample: u16| {
+        if wide {
+            buf.extend_from_slice(&sample.to_be_bytes())
+        } else {
+            buf.push(sample as u8)
+        }
+    };
+    for pixel in samples.chunks(channels) {
+        for sample in &pixel[..color_channels] {
+            push(&mut color, sample * scale);
+        }
+        if let Some(palette_alpha) = palette_alpha {
+            alpha.push(palette_alpha.get(pixel[0] as usize).copied().unwrap_or(255));
+        } else if channels > color_channels {
+            push(&mut alpha, pixel[color_channels]);
+        }
+    }
+
+    Ok(PngImage {
+        width: header.width,
+        height: header.height,
+        color_space,
+        bits_per_component: if wide { 16 } else { 8 },
+        color_key: key.and_then(|t| color_key(t, color_channels, scale)),
+        data: PngData::Decoded {
+            color,
+            alpha: if has_alpha { Some(alpha) } else { None },
+        },
+    })
+}
+
+fn parse_header(chunk: &[u8]) -> Result<Header, ImageError> {
+    if chunk.len() < 13 {
+        return Err(ImageError::InvalidPng("header too short"));
+    }
+    let header = Header {
+        width: u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+        height: u32::from_be_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]),
+        bit_depth: chunk[8],
+        color_type: chunk[9],
+        interlaced: match chunk[12] {
+            0 => false,
+            1 => true,
+            _ => return Err(ImageError::UnsupportedPng("interlace method")),
+        },
+    };
+    if chunk[10] != 0 || chunk[11] != 0 {
+        return Err(ImageError::UnsupportedPng("compression or filter method"));
+    }
+    if header.width == 0 || header.height == 0 {
+        return Err(ImageError::UnsupportedPng("image dimensions"));
+    }
+    let valid_bit_depth = match header.color_type {
+        0 => matches!(header.bit_depth, 1 | 2 | 4 | 8 | 16),
+        3 => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+        2 | 4 | 6 => matches!(header.bit_depth, 8 | 16),
+        _ => return Err(ImageError::UnsupportedPng("color type")),
+    };
+    if !valid_bit_depth {
+        return Err(ImageError::UnsupportedPng("bit depth"));
+    }
+    Ok(header)
+}
+
+/// The number of samples per pixel for the given PNG `color_type`.
+fn channel_count(color_type: u8) -> usize {
+    match color_type {
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        _ => 1,
+    }
+}
+
+/// Converts the transparent color of a gray or RGB image (`tRNS` chunk) into a color key mask.
+fn color_key(transparency: &[u8], channels: usize, scale: u16) -> Option<Vec<u16>> {
+    if transparency.len() < channels * 2 {
+        return None;
+    }
+    Some(
+        transparency
+            .chunks(2)
+            .take(channels)
+            .flat_map(|c| {
+                let sample = u16::from_be_bytes([c[0], c[1]]) * scale;
+                [sample, sample]
+            })
+            .collect(),
+    )
+}
+
+/// Reverts the scanline filters of the decompressed image `data` and returns all samples of the
+/// image (de-interlaced, in row-major order).
+fn decode_samples(header: &Header, data: &[u8]) -> Result<Vec<u16>, ImageError> {
+    let width = header.width as usize;
+    let height = header.height as usize;
+    let depth = usize::from(header.bit_depth);
+    let channels = channel_count(header.color_type);
+    // the distance in bytes to the corresponding byte of the previous pixel
+    let bpp = (channels * depth / 8).max(1);
+
+    let passes: &[(usize, usize, usize, usize)] = if header.interlaced {
+        &ADAM7
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+
+    // The dimensions are not trusted before making sure that there is enough data for them (each
+    // row of each pass consists of a filter type byte followed by the row's samples).
+    let mut expected_len = 0usize;
+    for &(x0, y0, dx, dy) in passes {
+        if x0 >= width || y0 >= height {
+            continue;
+        }
+        let pass_width = (width - x0).div_ceil(dx);
+        let pass_height = (height - y0).div_ceil(dy);
+        expected_len = pass_width
+            .checked_mul(channels * depth)
+            .map(|bits| bits.div_ceil(8) + 1)
+            .and_then(|row_len| row_len.checked_mul(pass_height))
+            .and_then(|len| len.checked_add(expected_len))
+            .ok_or(ImageError::InvalidPng("not enough image data"))?;
+    }
+    if expected_len > data.len() {
+        return Err(ImageError::InvalidPng("not enough image data"));
+    }
+
+    let mut samples = vec![0u16; width * height * channels];
+    let mut pos = 0;
+    for &(x0, y0, dx, dy) in passes {
+        if x0 >= width || y0 >= height {
+            continue;
+        }
+        let pass_width = (width - x0).div_ceil(dx);
+        let pass_height = (height - y0).div_ceil(dy);
+        let row_len = (pass_width * channels * depth).div_ceil(8);
+
+        let mut prev = vec![0u8; row_len];
+        for y in 0..pass_height {
+            let filter = *data
+                .get(pos)
+                .ok_or(ImageError::InvalidPng("not enough image data"))?;
+            let mut row = data
+                .get(pos + 1..pos + 1 + row_len)
+                .ok_or(ImageError::InvalidPng("not enough image data"))?
+                .to_vec();
+            pos += 1 + row_len;
+            unfilter(filter, &mut row, &prev, bpp)?;
+
+            for x in 0..pass_width {
+                let offset = ((y0 + y * dy) * width + x0 + x * dx) * channels;
+                for c in 0..channels {
+                    let i = x * channels + c;
+                    samples[offset + c] = match depth {
+                        16 => u16::from_be_bytes([row[i * 2], row[i * 2 + 1]]),
+                        8 => u16::from(row[i]),
+                        _ => {
+                            let bit = i * depth;
+                            let mask = (1 << depth) - 1;
+                            u16::from((row[bit / 8] >> (8 - depth - bit % 8)) & mask)
+                        }
+                    };
+                }
+            }
+            prev = row;
+        }
+    }
+
+    Ok(samples)
+}
+
+/// Reverts the PNG `filter` applied to the given scanline `row` (PNG spec section 9).
+fn unfilter(filter: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), ImageError> {
+    for i in 0..row.len() {
+        let left = if i >= bpp { row[i - bpp] } else { 0 };
+        let up = prev[i];
+        let up_left = if i >= bpp { prev[i - bpp] } else { 0 };
+        let prediction = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return Err(ImageError::InvalidPng("unknown filter type")),
+        };
+        row[i] = row[i].wrapping_add(prediction);
+    }
+    Ok(())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let pa = (p - i16::from(a)).abs();
+    let pb = (p - i16::from(b)).abs();
+    let pc = (p - i16::from(c)).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_rgb_predicted() {
+        let image = parse(include_bytes!("../../../images/rgb.png")).unwrap();
+        assert_eq!((image.width, image.height), (40, 30));
+        assert_eq!(
+            image.color_space,
+            PngColorSpace::Device(DeviceColorSpace::Rgb)
+        );
+        assert_eq!(image.bits_per_component, 8);
+        assert!(matches!(image.data, PngData::Predicted { colors: 3, .. }));
+    }
+
+    #[test]
+    fn test_parse_gray_predicted() {
+        let image = parse(include_bytes!("../../../images/gray4.png")).unwrap();
+        assert_eq!(image.bits_per_component, 4);
+        assert!(matches!(image.data, PngData::Predicted { colors: 1, .. }));
+    }
+
+    #[test]
+    fn test_decode_rgba() {
+        let image = parse(include_bytes!("../../../images/rgba.png")).unwrap();
+        let (color, alpha) = match image.data {
+            PngData::Decoded { color, alpha } => (color, alpha.unwrap()),
+            _ => panic!("expected decoded image"),
+        };
+        assert_eq!(color.len(), 40 * 30 * 3);
+        assert_eq!(alpha.len(), 40 * 30);
+        // top-right pixel
+        assert_eq!(&color[39 * 3..40 * 3], &[255, 80, 0]);
+        assert_eq!(alpha[39], 255);
+        // center pixel (inside the circle)
+        let center = 15 * 40 + 20;
+        assert_eq!(&color[center * 3..center * 3 + 3], &[240, 200, 40]);
+        assert_eq!(alpha[center], 255);
+        // bottom-left pixel
+        assert_eq!(alpha[29 * 40], 0);
+    }
+
+    #[test]
+    fn test_decode_interlaced_16bit() {
+        let image = parse(include_bytes!("../../../images/rgb16_interlaced.png")).unwrap();
+        assert_eq!(image.bits_per_component, 16);
+        let color = match image.data {
+            PngData::Decoded { color, alpha: None } => color,
+            _ => panic!("expected decoded image without alpha"),
+        };
+        assert_eq!(color.len(), 40 * 30 * 3 * 2);
+        // pixel 13, 29 (part of the last Adam7 pass)
+        let offset = (29 * 40 + 13) * 6;
+        assert_eq!(
+            &color[offset..offset + 6],
+            &[0x55, 0x55, 0x50, 0x50, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_decode_palette_alpha() {
+        let image = parse(include_bytes!("../../../images/palette_alpha.png")).unwrap();
+        assert_eq!(
+            image.color_space,
+            PngColorSpace::Indexed(vec![0, 0, 0, 200, 30, 30, 30, 160, 60, 30, 60, 200])
+        );
+        assert_eq!(image.bits_per_component, 8);
+        assert_eq!(image.color_key, None);
+        let (color, alpha) = match image.data {
+            PngData::Decoded { color, alpha } => (color, alpha.unwrap()),
+            _ => panic!("expected decoded image"),
+        };
+        let center = 15 * 40 + 20;
+        assert_eq!((color[0], alpha[0]), (0, 0));
+        assert_eq!((color[center], alpha[center]), (2, 255));
+    }
+
+    #[test]
+    fn test_decode_too_large() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut idat = ZlibEncoder::new(Vec::new(), Compression::default());
+        idat.write_all(&[0, 1, 2, 3, 4]).unwrap();
+        let idat = idat.finish().unwrap();
+
+        // a 100000 by 100000 pixels RGBA image that only consists of a few bytes
+        let mut png = SIGNATURE.to_vec();
+        let mut push_chunk = |kind: &[u8], data: &[u8]| {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            png.extend_from_slice(&[0; 4]); // CRC
+        };
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&100_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&100_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+        push_chunk(b"IHDR", &ihdr);
+        push_chunk(b"IDAT", &idat);
+        push_chunk(b"IEND", &[]);
+
+        assert!(matches!(
+            parse(&png),
+            Err(ImageError::InvalidPng("not enough image data"))
+        ));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            parse(b"\xFF\xD8\xFF"),
+            Err(ImageError::InvalidPng(_))
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+use super::paragraph::TextChunk;
+
+/// The languages with bundled hyphenation patterns (Liang's algorithm using the TeX patterns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// American English (`en-US`).
+    EnglishUS,
+    /// German with the reformed orthography of 1996 (`de`).
+    German,
+}
+
+impl Language {
+    fn patterns(self) -> hypher::Lang {
+        match self {
+            Language::EnglishUS => hypher::Lang::English,
+            Language::German => hypher::Lang::German,
+        }
+    }
+}
+
+/// Splits the given `chunk` at all hyphenation points of the words it contains. All but the last
+/// of the resulting chunks are breakable with a visible hyphen.
+pub(super) fn hyphenate<'a, R>(chunk: TextChunk<'a, R>, lang: Language) -> Vec<TextChunk<'a, R>> {
+    let mut positions = Vec::new();
+    let mut start = None;
+    for (i, ch) in chunk
+        .text
+        .char_indices()
+        .chain(std::iter::once((chunk.text.len(), ' ')))
+    {
+        match (start, ch.is_alphabetic()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let word = &chunk.text[s..i];
+                let mut offset = s;
+                for syllable in hypher::hyphenate(word, lang.patterns()) {
+                    offset += syllable.len();
+                    if offset < i {
+                        positions.push(offset);
+                    }
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if positions.is_empty() {
+        return vec![chunk];
+    }
+
+    let mut chunks = Vec::with_capacity(positions.len() + 1);
+    let mut prev = 0;
+    for pos in positions {
+        chunks.push(TextChunk {
+            text: &chunk.text[prev..pos],
+            style: chunk.style,
+            break_after: Some(unicode_linebreak::BreakOpportunity::Allowed),
+            hyphen: true,
+        });
+        prev = pos;
+    }
+    chunks.push(TextChunk {
+        text: &chunk.text[prev..],
+        ..chunk
+    });
+    chunks
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fonts::SingleFont;
+    use crate::layout::Style;
+    use unicode_linebreak::BreakOpportunity;
+
+    #[test]
+    fn test_hyphenate_chunk() {
+        let style = Style::new(SingleFont::default());
+        let chunk = TextChunk {
+            text: "Donaudampfschiff, ",
+            style: &style,
+            break_after: Some(BreakOpportunity::Allowed),
+            hyphen: false,
+        };
+
+        let chunks = hyphenate(chunk, Language::German);
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|c| (c.text, c.hyphen))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Do", true),
+                ("nau", true),
+                ("dampf", true),
+                ("schiff, ", false)
+            ]
+        );
+        assert!(chunks[..3]
+            .iter()
+            .all(|c| c.break_after == Some(BreakOpportunity::Allowed)));
+    }
+}
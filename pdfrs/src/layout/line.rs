@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::io;
+
+use super::paragraph::{Align, TextChunk};
+use super::style::Style;
+use crate::fonts::{FontCollection, SubsetRef};
+use crate::stream::ContentStream;
+
+/// A single line of a laid out paragraph.
+#[derive(Debug, PartialEq)]
+pub struct Line<'a, R> {
+    chunks: Vec<TextChunk<'a, R>>,
+    /// The width of the line's content (without trailing whitespace).
+    width: f64,
+    /// The width of the line including trailing whitespace.
+    advance: f64,
+    height: f64,
+    /// The horizontal offset of the line caused by its alignment.
+    offset: f64,
+    /// The space added to each space character to justify the line.
+    word_spacing: f64,
+}
+
+impl<'a, R> Line<'a, R> {
+    pub(super) fn new() -> Self {
+        Line {
+            chunks: Vec::new(),
+            width: 0.0,
+            advance: 0.0,
+            height: 0.0,
+            offset: 0.0,
+            word_spacing: 0.0,
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub(super) fn advance(&self) -> f64 {
+        self.advance
+    }
+
+    pub(super) fn into_chunks(self) -> Vec<TextChunk<'a, R>> {
+        self.chunks
+    }
+
+    /// Appends the chunks of a word to the line. The `width` is the width of the whole word,
+    /// `trailing_width` the width of its trailing whitespace, and `hyphen_width` the width of the
+    /// hyphen shown if the line ends after the word.
+    pub(super) fn push_word(
+        &mut self,
+        chunks: impl Iterator<Item = TextChunk<'a, R>>,
+        width: f64,
+        trailing_width: f64,
+        hyphen_width: f64,
+    ) {
+        for chunk in chunks {
+            self.height = self.height.max(chunk.style.line_height);
+            self.chunks.push(chunk);
+        }
+        self.width = self.advance + width - trailing_width + hyphen_width;
+        self.advance += width;
+    }
+
+    /// Aligns the line within the given `max_width`. Justified lines are left aligned if they are
+    /// the `last` line of a paragraph (or end with a mandatory line-break).
+    pub(super) fn align(&mut self, align: Align, max_width: f64, last: bool) {
+        let remaining = (max_width - self.width).max(0.0);
+        match align {
+            Align::Left => {}
+            Align::Right => self.offset = remaining,
+            Align::Center => self.offset = remaining / 2.0,
+            Align::Justify => {
+                let spaces = self.space_count();
+                if !last && spaces > 0 {
+                    self.word_spacing = remaining / spaces as f64;
+                }
+            }
+        }
+    }
+
+    /// The number of spaces between the words of the line (trailing whitespace excluded).
+    fn space_count(&self) -> usize {
+        let text = self.chunks.iter().map(|c| c.text).collect::<String>();
+        text.trim_end().matches(' ').count()
+    }
+
+    /// The width of the line's content in points.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// The height of the line in points (the largest line height of all its chunks).
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// The distance between the start of the line and the left edge of the layout area.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// The space added to each space character in points (only set for justified lines).
+    pub fn word_spacing(&self) -> f64 {
+        self.word_spacing
+    }
+
+    /// Returns the text of the line grouped into runs of the same style. Trailing whitespace
+    /// (including mandatory line-breaks) at the end of the line and soft hyphens are omitted. A
+    /// hyphen is added if the line ends at a hyphenation point.
+    pub fn runs(&self) -> Vec<(String, &'a Style<R>)>
+    where
+        R: PartialEq,
+    {
+        let mut runs: Vec<(String, &'a Style<R>)> = Vec::new();
+        for chunk in &self.chunks {
+            let chunk_text = chunk.visible_text();
+            match runs.last_mut() {
+                Some((text, style)) if *style == chunk.style => text.push_str(&chunk_text),
+                _ => runs.push((chunk_text.into_owned(), chunk.style)),
+            }
+        }
+        if let Some((text, _)) = runs.last_mut() {
+            text.truncate(text.trim_end().len());
+            if self.chunks.last().map(|c| c.hyphen).unwrap_or(false) {
+                text.push('-');
+            }
+        }
+        runs
+    }
+}
+
+/// Writes the given `lines` as a single text object. The top-left corner of the first line is
+/// positioned at `x`, `y` (in PDF's default user space). Returns the font subsets used by each font
+/// of the `fonts` collection.
+pub async fn render_lines<R, F>(
+    lines: &[Line<'_, R>],
+    fonts: &F,
+    x: f64,
+    mut y: f64,
+    wr: &mut dyn ContentStream,
+) -> Result<HashMap<R, HashSet<SubsetRef>>, io::Error>
+where
+    F: FontCollection<FontRef = R>,
+    R: Copy + Hash + Eq,
+{
+    let mut subset_refs: HashMap<R, HashSet<SubsetRef>> = HashMap::new();
+    let mut color = None;
+    let mut word_spacing = 0.0;
+
+    wr.begin_text().await?;
+    for line in lines {
+        y -= line.height;
+        wr.set_text_matrix(1.0, 0.0, 0.0, 1.0, x + line.offset, y)
+            .await?;
+
+        for (text, style) in line.runs() {
+            if text.is_empty() {
+                continue;
+            }
+            if color != Some(&style.color) {
+                wr.set_fill_color(&style.color).await?;
+                color = Some(&style.color);
+            }
+
+            let font = fonts.font(style.font);
+            // other fonts might use code 32 for a different character than space
+            let run_word_spacing = if font.supports_word_spacing() {
+                line.word_spacing
+            } else {
+                0.0
+            };
+            if run_word_spacing != word_spacing {
+                wr.set_word_spacing(run_word_spacing).await?;
+                word_spacing = run_word_spacing;
+            }
+
+            subset_refs.entry(style.font).or_default().extend(
+                wr.show_spaced_text_string(&text, font, style.font_size, line.word_spacing)
+                    .await?,
+            );
+        }
+    }
+    // the word spacing is part of the text state, which outlives the text object
+    if word_spacing != 0.0 {
+        wr.set_word_spacing(0.0).await?;
+    }
+    wr.end_text().await?;
+
+    Ok(subset_refs)
+}
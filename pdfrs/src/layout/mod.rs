@@ -1,10 +1,50 @@
+mod hyphenation;
+mod line;
 mod paragraph;
 mod style;
 
-// TODO: remove allow(unused)
-#[allow(unused)]
-pub enum Render<'a> {
-    Line { words: Vec<&'a str> },
-    PageBreak,
-    BlockEnd { y: f32 },
+pub use hyphenation::Language;
+pub use line::{render_lines, Line};
+pub use paragraph::{Align, Paragraph, TextNode};
+pub use style::Style;
+
+/// Returns how many of the given `lines` fit onto the current page if the first one is placed at
+/// `y` (measured from the top of the page), without exceeding `bottom`. A line that does not fit
+/// is still placed if nothing has been placed on the page yet (`y` is still at the page's `top`),
+/// as it would not fit onto the next page either.
+pub fn fit<R>(lines: &[Line<'_, R>], mut y: f64, top: f64, bottom: f64) -> usize {
+    for (i, line) in lines.iter().enumerate() {
+        if y + line.height() > bottom && y > top {
+            return i;
+        }
+        y += line.height();
+    }
+    lines.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fonts::{SingleFont, HELVETICA};
+    use std::ops::Deref;
+
+    #[test]
+    fn test_fit() {
+        let style = Style {
+            line_height: 10.0,
+            ..Style::new(SingleFont::default())
+        };
+
+        let mut p = Paragraph::new();
+        p.push("one\ntwo\nthree\nfour\nfive", &style);
+        let lines = p.layout(&HELVETICA.deref(), 100.0);
+
+        // two lines fit onto the current page
+        assert_eq!(fit(&lines, 75.0, 20.0, 100.0), 2);
+        assert_eq!(fit(&lines[2..], 20.0, 20.0, 50.0), 3);
+        // a line is placed on an empty page even if it does not fit
+        assert_eq!(fit(&lines, 20.0, 20.0, 25.0), 1);
+        // but moved to the next page otherwise
+        assert_eq!(fit(&lines, 21.0, 20.0, 25.0), 0);
+    }
 }
@@ -1,80 +1,242 @@
+use std::borrow::Cow;
 use std::iter;
+use std::mem;
 
+use super::hyphenation::{hyphenate, Language};
+use super::line::Line;
 use super::style::Style;
+use crate::fonts::FontCollection;
 use unicode_linebreak::{linebreaks_iter, BreakOpportunity};
 
 /// A type that contains a text paragraph, which consists of chunks of styled text.
-// TODO: remove allow(unused)
-#[allow(unused)]
-pub struct Paragraph<'a> {
-    pub children: Vec<TextNode<'a>>,
+pub struct Paragraph<'a, R> {
+    pub children: Vec<TextNode<'a, R>>,
+    pub align: Align,
+    /// The language used to hyphenate words (no hyphenation besides soft hyphens if `None`).
+    pub hyphenation: Option<Language>,
+}
+
+/// The horizontal alignment of the lines of a paragraph.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+    /// Stretches the spaces of each line so that it fills the whole width. The last line of the
+    /// paragraph (and each line ending with a mandatory line-break) stays left aligned.
+    Justify,
 }
 
 /// A styled text node used as a building-block for paragraphs.
-#[derive(Clone)]
-pub struct TextNode<'a> {
+pub struct TextNode<'a, R> {
     pub text: &'a str,
-    pub style: &'a Style<'a>,
+    pub style: &'a Style<R>,
 }
 
 /// A chunk of text that is styled and optionally has a possible trailing line-break.
 #[derive(Debug, PartialEq)]
-struct TextChunk<'a> {
-    text: &'a str,
-    style: &'a Style<'a>,
-    break_after: Option<BreakOpportunity>,
+pub(super) struct TextChunk<'a, R> {
+    pub(super) text: &'a str,
+    pub(super) style: &'a Style<R>,
+    pub(super) break_after: Option<BreakOpportunity>,
+    /// Whether a hyphen has to be shown if the line is broken after the chunk.
+    pub(super) hyphen: bool,
+}
+
+impl<'a, R> TextChunk<'a, R> {
+    /// The text of the chunk without soft hyphens.
+    pub(super) fn visible_text(&self) -> Cow<'a, str> {
+        if self.text.contains('\u{AD}') {
+            Cow::Owned(self.text.replace('\u{AD}', ""))
+        } else {
+            Cow::Borrowed(self.text)
+        }
+    }
 }
 
-impl<'a> Paragraph<'a> {
+impl<'a, R> Paragraph<'a, R> {
+    pub fn new() -> Self {
+        Paragraph {
+            children: Vec::new(),
+            align: Align::default(),
+            hyphenation: None,
+        }
+    }
+
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Enables hyphenation of words using the patterns of the given `lang`.
+    pub fn with_hyphenation(mut self, lang: Language) -> Self {
+        self.hyphenation = Some(lang);
+        self
+    }
+
+    /// Appends the given `text` with the given `style` to the paragraph.
+    pub fn push(&mut self, text: &'a str, style: &'a Style<R>) {
+        self.children.push(TextNode { text, style });
+    }
+
+    /// Breaks the paragraph into lines that are at most `max_width` points wide and aligns them
+    /// within `max_width`. Lines are only broken at line-break opportunities, soft hyphens and (if
+    /// enabled) hyphenation points, so a single word wider than `max_width` can still overflow its
+    /// line.
+    pub fn layout<F>(&'a self, fonts: &F, max_width: f64) -> Vec<Line<'a, R>>
+    where
+        F: FontCollection<FontRef = R>,
+        R: Copy,
+    {
+        // words containing soft hyphens are only broken at their soft hyphens
+        let mut soft_hyphenated = false;
+        let chunks = self.chunks().flat_map(|chunk| {
+            let manual = soft_hyphenated || chunk.hyphen;
+            soft_hyphenated = chunk.hyphen;
+            match self.hyphenation {
+                Some(lang) if !manual => hyphenate(chunk, lang),
+                _ => vec![chunk],
+            }
+        });
+
+        self.break_lines(chunks, fonts, max_width)
+    }
+
+    /// Breaks the text of the given `lines` (previously laid out from this paragraph) anew into
+    /// lines that are at most `max_width` points wide. Used to continue a paragraph on a page
+    /// with a different content width.
+    pub(crate) fn relayout<F>(
+        &'a self,
+        lines: Vec<Line<'a, R>>,
+        fonts: &F,
+        max_width: f64,
+    ) -> Vec<Line<'a, R>>
+    where
+        F: FontCollection<FontRef = R>,
+        R: Copy,
+    {
+        let chunks = lines.into_iter().flat_map(Line::into_chunks);
+        self.break_lines(chunks, fonts, max_width)
+    }
+
+    fn break_lines<F>(
+        &'a self,
+        chunks: impl Iterator<Item = TextChunk<'a, R>>,
+        fonts: &F,
+        max_width: f64,
+    ) -> Vec<Line<'a, R>>
+    where
+        F: FontCollection<FontRef = R>,
+        R: Copy,
+    {
+        let mut lines = Vec::new();
+        let mut line = Line::new();
+        let mut push_line = |line: Line<'a, R>, last: bool| {
+            let mut line = line;
+            line.align(self.align, max_width, last);
+            lines.push(line);
+        };
+
+        // chunks are collected into words (text between two line-break opportunities), since
+        // there are no line-break opportunities between the chunks of a word
+        let mut word = Vec::new();
+        let mut word_width = 0.0;
+
+        for chunk in chunks {
+            let font = fonts.font(chunk.style.font);
+            let text = chunk.visible_text();
+            word_width += font.text_width(&text, chunk.style.font_size);
+
+            let br = chunk.break_after;
+            // trailing whitespace does not need to fit onto the line
+            let trimmed = text.trim_end();
+            let trailing_width = font.text_width(&text[trimmed.len()..], chunk.style.font_size);
+            // the hyphen shown when breaking after the chunk however does
+            let hyphen_width = if chunk.hyphen {
+                font.text_width("-", chunk.style.font_size)
+            } else {
+                0.0
+            };
+            word.push(chunk);
+
+            let br = match br {
+                Some(br) => br,
+                None => continue,
+            };
+
+            if !line.is_empty()
+                && line.advance() + word_width - trailing_width + hyphen_width > max_width
+            {
+                push_line(mem::replace(&mut line, Line::new()), false);
+            }
+            line.push_word(word.drain(..), word_width, trailing_width, hyphen_width);
+            word_width = 0.0;
+
+            if br == BreakOpportunity::Mandatory {
+                push_line(mem::replace(&mut line, Line::new()), true);
+            }
+        }
+
+        if !word.is_empty() {
+            line.push_word(word.drain(..), word_width, 0.0, 0.0);
+        }
+        if !line.is_empty() {
+            push_line(line, true);
+        }
+
+        lines
+    }
+
     /// Splits the paragraph into text nodes by possible line-breaks.
-    // TODO: remove allow(unused)
-    #[allow(unused)]
-    fn chunks(&'a self) -> impl Iterator<Item = TextChunk<'a>> {
+    fn chunks(&'a self) -> impl Iterator<Item = TextChunk<'a, R>> {
         let mut linebreaks = linebreaks_iter(self.children.iter().map(|node| node.text));
         let mut next_break = linebreaks.next();
 
-        let mut nodes = self.children.iter();
-        let mut next_node = nodes.next().cloned();
+        let mut nodes = self.children.iter().map(|node| (node.text, node.style));
+        let mut next_node = nodes.next();
 
         // the offset is used to derive the current text position across all text nodes
         let mut offset = 0;
 
         iter::from_fn(move || {
-            if let (Some(mut node), Some((i, br))) = (
-                next_node.take().or_else(|| nodes.next().cloned()),
+            if let (Some((mut text, style)), Some((i, br))) = (
+                next_node.take().or_else(|| nodes.next()),
                 next_break.take().or_else(|| linebreaks.next()),
             ) {
                 // calculate the break position relative to the current text node
                 let pos = i - offset;
 
-                if pos > node.text.len() {
+                if pos > text.len() {
                     // keep the possible line-break for the next node (next iteration)
                     next_break = Some((i, br));
-                    offset += node.text.len();
+                    offset += text.len();
 
                     // return the remaining text of the current node if the next possible line-break
                     // is not within the current node
                     Some(TextChunk {
-                        text: node.text,
-                        style: &node.style,
+                        text,
+                        style,
                         break_after: None,
+                        hyphen: false,
                     })
                 } else {
                     // split the current node at the possible line-break and return the
                     // corresponding chunk
 
-                    let (word, remaining) = node.text.split_at(pos);
+                    let (word, remaining) = text.split_at(pos);
                     let chunk = TextChunk {
                         text: word,
-                        style: &node.style,
+                        style,
                         break_after: Some(br),
+                        hyphen: word.ends_with('\u{AD}'),
                     };
 
                     // if there is still text left for the current node, keep it for the next
                     // iteration
                     if !remaining.is_empty() {
-                        node.text = remaining;
-                        next_node = Some(node);
+                        text = remaining;
+                        next_node = Some((text, style));
                     }
                     offset = i;
 
@@ -87,16 +249,25 @@ impl<'a> Paragraph<'a> {
     }
 }
 
+impl<'a, R> Default for Paragraph<'a, R> {
+    fn default() -> Self {
+        Paragraph::new()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::fonts::HELVETICA;
+    use crate::fonts::{Font, SingleFont, HELVETICA};
+    use std::ops::Deref;
 
     #[test]
     fn test_paragraph_chunks_optional_break() {
-        let style = Style { font: &&*HELVETICA };
+        let style = Style::new(SingleFont::default());
 
         let p = Paragraph {
+            align: Align::Left,
+            hyphenation: None,
             children: vec![TextNode {
                 text: "foo-bar",
                 style: &style,
@@ -109,12 +280,14 @@ mod test {
                 TextChunk {
                     text: "foo-",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Allowed)
+                    break_after: Some(BreakOpportunity::Allowed),
+                    hyphen: false
                 },
                 TextChunk {
                     text: "bar",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Mandatory)
+                    break_after: Some(BreakOpportunity::Mandatory),
+                    hyphen: false
                 },
             ]
         );
@@ -122,9 +295,11 @@ mod test {
 
     #[test]
     fn test_paragraph_chunks_mandatory_break() {
-        let style = Style { font: &&*HELVETICA };
+        let style = Style::new(SingleFont::default());
 
         let p = Paragraph {
+            align: Align::Left,
+            hyphenation: None,
             children: vec![TextNode {
                 text: "foo\nbar",
                 style: &style,
@@ -137,12 +312,14 @@ mod test {
                 TextChunk {
                     text: "foo\n",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Mandatory)
+                    break_after: Some(BreakOpportunity::Mandatory),
+                    hyphen: false
                 },
                 TextChunk {
                     text: "bar",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Mandatory)
+                    break_after: Some(BreakOpportunity::Mandatory),
+                    hyphen: false
                 },
             ]
         );
@@ -150,9 +327,11 @@ mod test {
 
     #[test]
     fn test_paragraph_chunks_multiple_text_nodes() {
-        let style = Style { font: &&*HELVETICA };
+        let style = Style::new(SingleFont::default());
 
         let p = Paragraph {
+            align: Align::Left,
+            hyphenation: None,
             children: vec![
                 TextNode {
                     text: "This ",
@@ -171,12 +350,14 @@ mod test {
                 TextChunk {
                     text: "This ",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Allowed)
+                    break_after: Some(BreakOpportunity::Allowed),
+                    hyphen: false
                 },
                 TextChunk {
                     text: "works.",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Mandatory)
+                    break_after: Some(BreakOpportunity::Mandatory),
+                    hyphen: false
                 }
             ]
         );
@@ -184,9 +365,11 @@ mod test {
 
     #[test]
     fn test_paragraph_chunks_two_nodes_without_breaks() {
-        let style = Style { font: &&*HELVETICA };
+        let style = Style::new(SingleFont::default());
 
         let p = Paragraph {
+            align: Align::Left,
+            hyphenation: None,
             children: vec![
                 TextNode {
                     text: "foo",
@@ -205,12 +388,14 @@ mod test {
                 TextChunk {
                     text: "foo",
                     style: &style,
-                    break_after: None
+                    break_after: None,
+                    hyphen: false
                 },
                 TextChunk {
                     text: "bar",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Mandatory)
+                    break_after: Some(BreakOpportunity::Mandatory),
+                    hyphen: false
                 }
             ]
         );
@@ -218,9 +403,11 @@ mod test {
 
     #[test]
     fn test_paragraph_chunks_three_nodes_without_breaks() {
-        let style = Style { font: &&*HELVETICA };
+        let style = Style::new(SingleFont::default());
 
         let p = Paragraph {
+            align: Align::Left,
+            hyphenation: None,
             children: vec![
                 TextNode {
                     text: "fo",
@@ -243,24 +430,180 @@ mod test {
                 TextChunk {
                     text: "fo",
                     style: &style,
-                    break_after: None
+                    break_after: None,
+                    hyphen: false
                 },
                 TextChunk {
                     text: "ob",
                     style: &style,
-                    break_after: None
+                    break_after: None,
+                    hyphen: false
                 },
                 TextChunk {
                     text: "ar ",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Allowed)
+                    break_after: Some(BreakOpportunity::Allowed),
+                    hyphen: false
                 },
                 TextChunk {
                     text: "is!",
                     style: &style,
-                    break_after: Some(BreakOpportunity::Mandatory)
+                    break_after: Some(BreakOpportunity::Mandatory),
+                    hyphen: false
                 }
             ]
         );
     }
+
+    fn line_texts(lines: &[Line<'_, SingleFont>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.runs()
+                    .into_iter()
+                    .map(|(text, _)| text)
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paragraph_layout_greedy() {
+        let style = Style {
+            font_size: 10.0,
+            ..Style::new(SingleFont::default())
+        };
+
+        let mut p = Paragraph::new();
+        p.push("The quick brown fox jumps over the lazy dog", &style);
+
+        // "The quick brown" is 72.79pt wide
+        let lines = p.layout(&HELVETICA.deref(), 75.0);
+        assert_eq!(
+            line_texts(&lines),
+            vec!["The quick brown", "fox jumps over", "the lazy dog"]
+        );
+        assert!(lines.iter().all(|line| line.width() <= 75.0));
+        assert_eq!(lines[0].height(), 10.175);
+    }
+
+    #[test]
+    fn test_paragraph_layout_mandatory_breaks() {
+        let style = Style::new(SingleFont::default());
+
+        let mut p = Paragraph::new();
+        p.push("foo\n\nbar baz", &style);
+
+        let lines = p.layout(&HELVETICA.deref(), 500.0);
+        assert_eq!(line_texts(&lines), vec!["foo", "", "bar baz"]);
+    }
+
+    #[test]
+    fn test_paragraph_layout_overflowing_word() {
+        let style = Style::new(SingleFont::default());
+
+        let mut p = Paragraph::new();
+        p.push("a Donaudampfschifffahrt b", &style);
+
+        let lines = p.layout(&HELVETICA.deref(), 20.0);
+        assert_eq!(line_texts(&lines), vec!["a", "Donaudampfschifffahrt", "b"]);
+    }
+
+    #[test]
+    fn test_paragraph_layout_mixed_styles() {
+        let regular = Style::new(SingleFont::default());
+        let large = Style {
+            font_size: 20.0,
+            line_height: 24.0,
+            ..Style::new(SingleFont::default())
+        };
+
+        let mut p = Paragraph::new();
+        p.push("small ", &regular);
+        p.push("LARGE", &large);
+        p.push(" small", &regular);
+
+        let lines = p.layout(&HELVETICA.deref(), 500.0);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].height(), 24.0);
+        let expected = HELVETICA.deref().text_width("small small ", 11.0)
+            + HELVETICA.deref().text_width("LARGE", 20.0);
+        assert!((lines[0].width() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_paragraph_layout_align() {
+        let style = Style {
+            font_size: 10.0,
+            ..Style::new(SingleFont::default())
+        };
+
+        for (align, offset) in [
+            (Align::Left, 0.0),
+            (Align::Right, 1.0),
+            (Align::Center, 0.5),
+        ] {
+            let mut p = Paragraph::new().with_align(align);
+            p.push("The quick brown fox jumps over the lazy dog", &style);
+
+            let lines = p.layout(&HELVETICA.deref(), 75.0);
+            for line in &lines {
+                assert!((line.offset() - offset * (75.0 - line.width())).abs() < 1e-9);
+                assert_eq!(line.word_spacing(), 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_paragraph_layout_justify() {
+        let style = Style {
+            font_size: 10.0,
+            ..Style::new(SingleFont::default())
+        };
+
+        let mut p = Paragraph::new().with_align(Align::Justify);
+        p.push("The quick brown fox jumps over\nthe lazy dog", &style);
+
+        let lines = p.layout(&HELVETICA.deref(), 75.0);
+        assert_eq!(
+            line_texts(&lines),
+            vec!["The quick brown", "fox jumps over", "the lazy dog"]
+        );
+        assert!((lines[0].word_spacing() - (75.0 - lines[0].width()) / 2.0).abs() < 1e-9);
+        // the line before the mandatory break and the last line are not justified
+        assert_eq!(lines[1].word_spacing(), 0.0);
+        assert_eq!(lines[2].word_spacing(), 0.0);
+        assert!(lines.iter().all(|line| line.offset() == 0.0));
+    }
+
+    #[test]
+    fn test_paragraph_layout_hyphenation() {
+        let style = Style::new(SingleFont::default());
+
+        let mut p = Paragraph::new().with_hyphenation(Language::German);
+        p.push("Die Donaudampfschifffahrt", &style);
+
+        let lines = p.layout(&HELVETICA.deref(), 70.0);
+        assert_eq!(
+            line_texts(&lines),
+            vec!["Die Donau-", "dampfschiff-", "fahrt"]
+        );
+        assert!(lines.iter().all(|line| line.width() <= 70.0));
+    }
+
+    #[test]
+    fn test_paragraph_layout_soft_hyphens() {
+        let style = Style::new(SingleFont::default());
+
+        let mut p = Paragraph::new().with_hyphenation(Language::German);
+        p.push("Eine Zeit\u{AD}schrift", &style);
+
+        // soft hyphens are invisible unless the line is broken there
+        let lines = p.layout(&HELVETICA.deref(), 500.0);
+        assert_eq!(line_texts(&lines), vec!["Eine Zeitschrift"]);
+
+        // a visible hyphen is added when breaking at a soft hyphen
+        let lines = p.layout(&HELVETICA.deref(), 40.0);
+        assert_eq!(line_texts(&lines), vec!["Eine", "Zeit-", "schrift"]);
+    }
 }
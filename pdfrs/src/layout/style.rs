@@ -1,19 +1,24 @@
-use std::fmt;
+use crate::color::Color;
 
-use crate::fonts::Font;
-
-pub struct Style<'a> {
-    pub font: &'a dyn Font,
-}
-
-impl<'a> PartialEq for Style<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.font.base_name() == other.font.base_name()
-    }
+/// The text style of a [`TextNode`](super::TextNode). The `font` is a reference to a font of the
+/// document's font collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style<R> {
+    pub font: R,
+    pub font_size: f64,
+    /// The distance between the baselines of two consecutive lines.
+    pub line_height: f64,
+    pub color: Color,
 }
 
-impl<'a> fmt::Debug for Style<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Style {{ font: {} }}", self.font.base_name())
+impl<R> Style<R> {
+    /// Constructs a new style for 11pt black text set in the given `font`.
+    pub fn new(font: R) -> Self {
+        Style {
+            font,
+            font_size: 11.0,
+            line_height: 10.175,
+            color: Color::BLACK,
+        }
     }
 }
@@ -1,18 +1,49 @@
+mod annotation;
+mod color;
+mod conformance;
+mod destination;
 mod document;
+mod embedded_file;
+mod encryption;
+mod field;
 pub mod fonts;
+mod form;
+mod graphics;
 mod idseq;
+mod image;
 mod layout;
+mod metadata;
+mod name_tree;
+mod outline;
 mod page;
+mod path;
 mod stream;
+mod structure;
 mod text;
 mod writer;
 
 use std::ops::Deref;
 
-pub use document::{Document, DocumentBuilder};
+pub use annotation::{Border, BorderStyle, Link, LinkTarget};
+pub use color::{Color, DeviceColorSpace, IccProfile, SpotColor};
+pub use conformance::{Conformance, Violation};
+pub use destination::Destination;
+pub use document::{Document, DocumentBuilder, Error};
+pub use embedded_file::{EmbeddedFile, FileRelationship};
+pub use encryption::{Encryption, EncryptionAlgorithm};
+pub use field::{ButtonAction, Field};
 use fonts::FontCollection;
+pub use form::Form;
+pub use graphics::{LineCap, LineJoin, Transform};
+pub use image::{Image, ImageError};
 use js_sys::Uint8Array;
+pub use layout::{Align, Language, Paragraph, Style, TextNode};
+pub use outline::{OutlineEntry, OutlineItem};
+pub use page::{Margins, Orientation, PageLayout, PageSize, Rect, Rotation};
+pub use path::{FillRule, Paint, Path};
 use pdfrs_afm::HELVETICA;
+pub use structure::{Artifact, Role, Tag};
+pub use text::{Origin, TextOptions};
 use wasm_bindgen::prelude::*;
 
 // #[wasm_bindgen]
@@ -0,0 +1,271 @@
+use std::io;
+
+use crate::conformance::Conformance;
+use crate::stream::{Filter, Stream};
+use crate::writer::DocWriter;
+use async_std::io::prelude::{Write, WriteExt};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, ObjectId, PdfText, Reference};
+
+/// The metadata of a document, which is written both as document information dictionary and as
+/// XMP metadata stream.
+#[derive(Debug, Clone)]
+pub(crate) struct Metadata {
+    pub(crate) title: Option<String>,
+    pub(crate) author: Option<String>,
+    pub(crate) subject: Option<String>,
+    pub(crate) keywords: Vec<String>,
+    pub(crate) creator: Option<String>,
+    pub(crate) producer: String,
+    pub(crate) creation_date: DateTime<Utc>,
+    pub(crate) modification_date: Option<DateTime<Utc>>,
+    pub(crate) conformance: Option<Conformance>,
+}
+
+/// A type used to create PDF references to the XMP metadata stream.
+pub(crate) type MetadataRef = ();
+
+/// The document information dictionary (PDF spec 1.7 page 550).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct Info<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<PdfText<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<PdfText<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subject: Option<PdfText<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keywords: Option<Keywords<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<PdfText<'a>>,
+    producer: PdfText<'a>,
+    creation_date: PdfDate<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mod_date: Option<PdfDate<'a>>,
+}
+
+struct PdfDate<'a>(&'a DateTime<Utc>);
+
+/// Keywords, serialized as a single comma separated text string.
+struct Keywords<'a>(&'a [String]);
+
+/// A metadata stream dictionary (PDF spec 1.7 page 556).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "Metadata")]
+struct MetadataStream {
+    subtype: Name<'static>,
+    length: Reference<usize>,
+}
+
+impl Metadata {
+    /// The document information dictionary.
+    pub(crate) fn to_info(&self) -> Info<'_> {
+        Info {
+            title: self.title.as_deref().map(PdfText),
+            author: self.author.as_deref().map(PdfText),
+            subject: self.subject.as_deref().map(PdfText),
+            keywords: if self.keywords.is_empty() {
+                None
+            } else {
+                Some(Keywords(&self.keywords))
+            },
+            creator: self.creator.as_deref().map(PdfText),
+            producer: PdfText(&self.producer),
+            creation_date: PdfDate(&self.creation_date),
+            mod_date: self.modification_date.as_ref().map(PdfDate),
+        }
+    }
+
+    /// Writes the XMP metadata stream with the given object `id`. Metadata streams are never
+    /// compressed, so that tools that do not understand PDF can still find the metadata.
+    pub(crate) async fn write_xmp<W: Write + Unpin>(
+        &self,
+        id: ObjectId,
+        doc: DocWriter<W>,
+    ) -> Result<DocWriter<W>, io::Error> {
+        let mut stream =
+            Stream::start_with_dict(doc, id, false, |length, _: Vec<Filter>| MetadataStream {
+                subtype: Name("XML"),
+                length,
+            })
+            .await?;
+        stream.write_all(self.to_xmp().as_bytes()).await?;
+        stream.end().await
+    }
+
+    /// The metadata as XMP packet (see XMP specification part 1).
+    pub(crate) fn to_xmp(&self) -> String {
+        let mut dc = String::from("<dc:format>application/pdf</dc:format>\n");
+        if let Some(title) = &self.title {
+            dc += &format!(
+                "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>\n",
+                escape_xml(title)
+            );
+        }
+        if let Some(author) = &self.author {
+            dc += &format!(
+                "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>\n",
+                escape_xml(author)
+            );
+        }
+        if let Some(subject) = &self.subject {
+            dc += &format!(
+                "<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>\n",
+                escape_xml(subject)
+            );
+        }
+        if !self.keywords.is_empty() {
+            dc += "<dc:subject><rdf:Bag>";
+            for keyword in &self.keywords {
+                dc += &format!("<rdf:li>{}</rdf:li>", escape_xml(keyword));
+            }
+            dc += "</rdf:Bag></dc:subject>\n";
+        }
+
+        let mut pdf = format!(
+            "<pdf:Producer>{}</pdf:Producer>\n",
+            escape_xml(&self.producer)
+        );
+        if !self.keywords.is_empty() {
+            pdf += &format!(
+                "<pdf:Keywords>{}</pdf:Keywords>\n",
+                escape_xml(&self.keywords.join(", "))
+            );
+        }
+
+        let mut xmp = String::new();
+        if let Some(creator) = &self.creator {
+            xmp += &format!(
+                "<xmp:CreatorTool>{}</xmp:CreatorTool>\n",
+                escape_xml(creator)
+            );
+        }
+        let modification_date = self.modification_date.unwrap_or(self.creation_date);
+        xmp += &format!(
+            "<xmp:CreateDate>{}</xmp:CreateDate>\n<xmp:ModifyDate>{}</xmp:ModifyDate>\n\
+             <xmp:MetadataDate>{}</xmp:MetadataDate>\n",
+            xmp_date(&self.creation_date),
+            xmp_date(&modification_date),
+            xmp_date(&modification_date),
+        );
+
+        format!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+             <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+             <rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+             {}</rdf:Description>\n\
+             <rdf:Description rdf:about=\"\" xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">\n\
+             {}</rdf:Description>\n\
+             <rdf:Description rdf:about=\"\" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\">\n\
+             {}</rdf:Description>\n\
+             {}</rdf:RDF>\n\
+             </x:xmpmeta>\n\
+             <?xpacket end=\"r\"?>",
+            dc,
+            pdf,
+            xmp,
+            self.conformance
+                .map(Conformance::to_xmp)
+                .unwrap_or_default()
+        )
+    }
+}
+
+impl<'a> Serialize for Keywords<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        PdfText(&self.0.join(", ")).serialize(serializer)
+    }
+}
+
+impl<'a> Serialize for PdfDate<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serde_pdf::datetime::serialize(self.0, serializer)
+    }
+}
+
+fn xmp_date(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            title: Some("Größenvergleich".to_string()),
+            author: Some("Jane Doe".to_string()),
+            subject: None,
+            keywords: vec!["pdf".to_string(), "R&D".to_string()],
+            creator: None,
+            producer: "pdfrs".to_string(),
+            creation_date: Utc.with_ymd_and_hms(2019, 6, 2, 14, 28, 0).unwrap(),
+            modification_date: Utc.with_ymd_and_hms(2019, 6, 3, 8, 0, 0).single(),
+            conformance: None,
+        }
+    }
+
+    #[test]
+    fn test_serialize_info() {
+        assert_eq!(
+            serde_pdf::to_string(&metadata().to_info()).unwrap(),
+            "<<\n\t/Title <FEFF0047007200F600DF0065006E0076006500720067006C0065006900630068>\n\
+             \t/Author (Jane Doe)\n\t/Keywords (pdf, R&D)\n\t/Producer (pdfrs)\n\
+             \t/CreationDate (D:20190602142800+00'00')\n\t/ModDate (D:20190603080000+00'00')\n>>"
+        );
+    }
+
+    #[test]
+    fn test_xmp() {
+        let xmp = metadata().to_xmp();
+        assert!(xmp.contains(
+            "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">Größenvergleich</rdf:li></rdf:Alt></dc:title>"
+        ));
+        assert!(
+            xmp.contains("<dc:creator><rdf:Seq><rdf:li>Jane Doe</rdf:li></rdf:Seq></dc:creator>")
+        );
+        assert!(xmp.contains(
+            "<dc:subject><rdf:Bag><rdf:li>pdf</rdf:li><rdf:li>R&amp;D</rdf:li></rdf:Bag></dc:subject>"
+        ));
+        assert!(xmp.contains("<pdf:Keywords>pdf, R&amp;D</pdf:Keywords>"));
+        assert!(xmp.contains("<xmp:CreateDate>2019-06-02T14:28:00Z</xmp:CreateDate>"));
+        assert!(xmp.contains("<xmp:ModifyDate>2019-06-03T08:00:00Z</xmp:ModifyDate>"));
+        assert!(!xmp.contains("dc:description"));
+        assert!(!xmp.contains("pdfaid"));
+
+        let xmp = Metadata {
+            conformance: Some(Conformance::PdfA3b),
+            ..metadata()
+        }
+        .to_xmp();
+        assert!(xmp
+            .contains("<pdfaid:part>3</pdfaid:part>\n<pdfaid:conformance>B</pdfaid:conformance>"));
+    }
+}
@@ -0,0 +1,69 @@
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+use serde_pdf::PdfText;
+
+/// A type used to create PDF references to name trees.
+pub(crate) type NameTreeRef = ();
+
+/// A name tree (PDF spec 1.7 page 88) mapping text strings to values. As documents usually only
+/// contain a handful of entries per tree, the tree consists of a single root node that contains all
+/// of them.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct NameTree<'a, V> {
+    names: NameTreeEntries<'a, V>,
+}
+
+/// The keys and values of a name tree node, serialized as a flat array of alternating keys and
+/// values, sorted by key.
+struct NameTreeEntries<'a, V>(Vec<(PdfText<'a>, V)>);
+
+impl<'a, V> NameTree<'a, V> {
+    pub(crate) fn new(entries: impl IntoIterator<Item = (&'a str, V)>) -> Self {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        // keys are compared by the bytes of their encoded strings, thus ASCII keys (which are
+        // written as is) come before all others (which are encoded as UTF-16BE with a BOM)
+        entries.sort_by(|(a, _), (b, _)| {
+            (!a.is_ascii())
+                .cmp(&!b.is_ascii())
+                .then_with(|| a.encode_utf16().cmp(b.encode_utf16()))
+        });
+        NameTree {
+            names: NameTreeEntries(
+                entries
+                    .into_iter()
+                    .map(|(name, value)| (PdfText(name), value))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a, V: Serialize> Serialize for NameTreeEntries<'a, V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_seq(Some(self.0.len() * 2))?;
+        for (name, value) in &self.0 {
+            s.serialize_element(name)?;
+            s.serialize_element(value)?;
+        }
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize_name_tree() {
+        let tree = NameTree::new(vec![("b", 2), ("ä", 3), ("a", 1), ("B", 0)]);
+        assert_eq!(
+            serde_pdf::to_string(&tree).unwrap(),
+            "<<\n\t/Names [(B) 0 (a) 1 (b) 2 <FEFF00E4> 3]\n>>"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::destination::{Destination, ResolvedDestination};
+use crate::document::Error;
+use crate::page::Page;
+use crate::writer::DocWriter;
+use async_std::io::prelude::Write;
+use serde::Serialize;
+use serde_pdf::{Object, PdfText, Reference};
+
+/// An entry of the document outline (also known as bookmarks), added with
+/// [`Document::add_outline_item`](crate::Document::add_outline_item).
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineItem {
+    title: String,
+    destination: Destination,
+    open: bool,
+    color: Option<(f64, f64, f64)>,
+    bold: bool,
+    italic: bool,
+}
+
+/// A handle to an item of the document outline, used to nest other items below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutlineEntry(usize);
+
+/// All items of the document outline, in the order they have been added.
+#[derive(Default)]
+pub(crate) struct Outline {
+    items: Vec<(Option<OutlineEntry>, OutlineItem)>,
+}
+
+/// A type used to create PDF references to the outline dictionary and its items.
+pub(crate) type OutlineRef = ();
+
+/// The outline dictionary (PDF spec 1.7 page 585).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "Outlines")]
+struct OutlineRoot {
+    first: Reference<OutlineRef>,
+    last: Reference<OutlineRef>,
+    count: usize,
+}
+
+/// An outline item dictionary (PDF spec 1.7 page 586).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+struct OutlineItemDict<'a> {
+    title: PdfText<'a>,
+    parent: Reference<OutlineRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prev: Option<Reference<OutlineRef>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<Reference<OutlineRef>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first: Option<Reference<OutlineRef>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last: Option<Reference<OutlineRef>>,
+    /// The number of visible descendants if the item is open, or the negative number of
+    /// descendants that would be visible if it is closed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<i64>,
+    dest: ResolvedDestination<'a>,
+    #[serde(rename = "C", skip_serializing_if = "Option::is_none")]
+    c: Option<(f64, f64, f64)>,
+    /// Style flags (bit 1 for italic, bit 2 for bold).
+    #[serde(rename = "F", skip_serializing_if = "Option::is_none")]
+    f: Option<u8>,
+}
+
+impl OutlineItem {
+    /// Creates a (closed, unstyled) outline item with the given `title` that jumps to the given
+    /// `destination`.
+    pub fn new(title: impl Into<String>, destination: Destination) -> Self {
+        OutlineItem {
+            title: title.into(),
+            destination,
+            open: false,
+            color: None,
+            bold: false,
+            italic: false,
+        }
+    }
+
+    /// Sets whether the item's nested items are initially visible.
+    pub fn with_open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Sets the RGB color (each component between `0.0` and `1.0`) of the item's title.
+    pub fn with_color(mut self, r: f64, g: f64, b: f64) -> Self {
+        self.color = Some((r, g, b));
+        self
+    }
+
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn destination(&self) -> &Destination {
+        &self.destination
+    }
+}
+
+impl Outline {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Adds the given `item` below `parent` (or to the top level if there is none). Fails if the
+    /// `parent` is not an item of this outline (e.g. if it belongs to another document).
+    pub(crate) fn push(
+        &mut self,
+        parent: Option<OutlineEntry>,
+        item: OutlineItem,
+    ) -> Result<OutlineEntry, Error> {
+        if let Some(OutlineEntry(parent)) = parent {
+            if parent >= self.items.len() {
+                return Err(Error::InvalidOutlineParent);
+            }
+        }
+        self.items.push((parent, item));
+        Ok(OutlineEntry(self.items.len() - 1))
+    }
+
+    /// Writes the outline dictionary and all its items. The destinations of the items are resolved
+    /// among the given `pages` (with their heights).
+    pub(crate) async fn write<W: Write + Unpin>(
+        self,
+        doc: &mut DocWriter<W>,
+        pages: &[(Reference<Page>, f64)],
+    ) -> Result<Reference<OutlineRef>, Error> {
+        let root_id = doc.reserve_object_id();
+        let ids = self
+            .items
+            .iter()
+            .map(|_| doc.reserve_object_id())
+            .collect::<Vec<_>>();
+
+        let mut root_children = Vec::new();
+        let mut children = vec![Vec::new(); self.items.len()];
+        for (i, (parent, _)) in self.items.iter().enumerate() {
+            match parent {
+                Some(OutlineEntry(parent)) => children[*parent].push(i),
+                None => root_children.push(i),
+            }
+        }
+
+        let reference = |i: usize| Reference::new(ids[i].clone());
+        let visible = |siblings: &[usize]| visible_count(&self.items, &children, siblings);
+        doc.write_object(Object::new(
+            root_id.id(),
+            root_id.rev(),
+            OutlineRoot {
+                first: reference(root_children[0]),
+                last: reference(root_children[root_children.len() - 1]),
+                count: visible(&root_children),
+            },
+        ))
+        .await?;
+
+        for (i, (parent, item)) in self.items.iter().enumerate() {
+            let siblings = match parent {
+                Some(OutlineEntry(parent)) => &children[*parent],
+                None => &root_children,
+            };
+            let pos = siblings.iter().position(|s| *s == i).unwrap_or_default();
+            let nested = &children[i];
+            let count = if nested.is_empty() {
+                None
+            } else if item.open {
+                Some(visible(nested) as i64)
+            } else {
+                Some(-(visible(nested) as i64))
+            };
+            let flags = u8::from(item.italic) | u8::from(item.bold) << 1;
+            let dict = OutlineItemDict {
+                title: PdfText(&item.title),
+                parent: match parent {
+                    Some(OutlineEntry(parent)) => reference(*parent),
+                    None => Reference::new(root_id.clone()),
+                },
+                prev: pos.checked_sub(1).map(|p| reference(siblings[p])),
+                next: siblings.get(pos + 1).map(|n| reference(*n)),
+                first: nested.first().map(|n| reference(*n)),
+                last: nested.last().map(|n| reference(*n)),
+                count,
+                dest: item
+                    .destination
+                    .resolve(pages)
+                    .ok_or(Error::InvalidDestination(item.destination.page_index()))?,
+                c: item.color,
+                f: if flags != 0 { Some(flags) } else { None },
+            };
+            let id = &ids[i];
+            doc.write_object(Object::new(id.id(), id.rev(), dict))
+                .await?;
+        }
+
+        Ok(Reference::new(root_id))
+    }
+}
+
+/// The number of items that are visible among the given `siblings` and (for open items) their
+/// descendants.
+fn visible_count(
+    items: &[(Option<OutlineEntry>, OutlineItem)],
+    children: &[Vec<usize>],
+    siblings: &[usize],
+) -> usize {
+    siblings
+        .iter()
+        .map(|&i| {
+            if items[i].1.open {
+                1 + visible_count(items, children, &children[i])
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_visible_count() {
+        let mut outline = Outline::default();
+        let chapter1 = outline
+            .push(None, OutlineItem::new("1", Destination::Page(0)))
+            .unwrap();
+        let section = outline
+            .push(
+                Some(chapter1),
+                OutlineItem::new("1.1", Destination::Page(0)).with_open(true),
+            )
+            .unwrap();
+        outline
+            .push(
+                Some(section),
+                OutlineItem::new("1.1.1", Destination::Page(0)),
+            )
+            .unwrap();
+        let chapter2 = outline
+            .push(
+                None,
+                OutlineItem::new("2", Destination::Page(1)).with_open(true),
+            )
+            .unwrap();
+        outline
+            .push(
+                Some(chapter2),
+                OutlineItem::new("2.1", Destination::Page(1)),
+            )
+            .unwrap();
+
+        let children = vec![vec![1], vec![2], vec![], vec![4], vec![]];
+        // chapter 1 is closed, thus only chapter 2 and its section are visible as well
+        assert_eq!(visible_count(&outline.items, &children, &[0, 3]), 3);
+        // if chapter 1 were open, its section and the open section's item would be visible
+        assert_eq!(visible_count(&outline.items, &children, &[1]), 2);
+    }
+
+    #[test]
+    fn test_push_unknown_parent() {
+        let mut outline = Outline::default();
+        assert!(matches!(
+            outline.push(
+                Some(OutlineEntry(0)),
+                OutlineItem::new("1", Destination::Page(0))
+            ),
+            Err(Error::InvalidOutlineParent)
+        ));
+        assert!(outline.items.is_empty());
+    }
+}
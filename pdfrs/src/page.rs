@@ -1,13 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::annotation::AnnotationRef;
+use crate::color::ColorSpaceRef;
+use crate::graphics::ExtGState;
+use crate::image::XObjectRef;
 use crate::stream::StreamRef;
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 use serde_pdf::Reference;
 
+/// A rectangle given by its lower-left `x`, `y` and upper-right `x`, `y` coordinates (in points).
+pub type Rect = (f64, f64, f64, f64);
+
 #[derive(Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Pages {
-    pub media_box: (f64, f64, f64, f64),
+    pub media_box: Rect,
     pub kids: Vec<Reference<Page>>,
     pub count: usize,
 }
@@ -19,12 +26,284 @@ pub type FontRef = ();
 pub struct Resources {
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub font: HashMap<String, Reference<FontRef>>,
+    #[serde(rename = "ExtGState", skip_serializing_if = "BTreeMap::is_empty")]
+    pub ext_g_state: BTreeMap<String, Reference<ExtGState>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub color_space: BTreeMap<String, Reference<ColorSpaceRef>>,
+    #[serde(rename = "XObject", skip_serializing_if = "BTreeMap::is_empty")]
+    pub x_object: BTreeMap<String, Reference<XObjectRef>>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Page {
     pub parent: Reference<Pages>,
+    /// Only set if it differs from the media box inherited from the page tree.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_box: Option<Rect>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crop_box: Option<Rect>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bleed_box: Option<Rect>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_box: Option<Rect>,
+    #[serde(skip_serializing_if = "Rotation::is_none")]
+    pub rotate: Rotation,
     pub resources: Resources,
     pub contents: Vec<Reference<StreamRef>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annots: Vec<Reference<AnnotationRef>>,
+    /// The key of the page's entry in the structure tree's parent tree (only set for pages with
+    /// marked content).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub struct_parents: Option<usize>,
+}
+
+/// Common paper sizes. All sizes are in points (1/72 inch) and in portrait orientation.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PageSize {
+    A3,
+    #[default]
+    A4,
+    A5,
+    Letter,
+    Legal,
+    Custom {
+        width: f64,
+        height: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+/// The clockwise rotation applied to a page when it is displayed or printed (`/Rotate`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+/// The distances between the edges of a page and its content (in points).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+/// The size, margins and boxes of a page. All boxes are given in default user space units
+/// (points).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLayout {
+    size: PageSize,
+    orientation: Orientation,
+    margins: Margins,
+    crop_box: Option<Rect>,
+    bleed_box: Option<Rect>,
+    trim_box: Option<Rect>,
+    rotation: Rotation,
+}
+
+impl PageSize {
+    /// Returns the `(width, height)` of the paper size in points.
+    pub fn dimensions(&self) -> (f64, f64) {
+        match *self {
+            PageSize::A3 => (841.89, 1190.55),
+            PageSize::A4 => (595.296, 841.896),
+            PageSize::A5 => (419.53, 595.28),
+            PageSize::Letter => (612.0, 792.0),
+            PageSize::Legal => (612.0, 1008.0),
+            PageSize::Custom { width, height } => (width, height),
+        }
+    }
+}
+
+impl Rotation {
+    pub fn degrees(&self) -> u16 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 90,
+            Rotation::Clockwise180 => 180,
+            Rotation::Clockwise270 => 270,
+        }
+    }
+
+    fn is_none(&self) -> bool {
+        *self == Rotation::None
+    }
+}
+
+impl Serialize for Rotation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u16(self.degrees())
+    }
+}
+
+impl Margins {
+    pub fn new(top: f64, right: f64, bottom: f64, left: f64) -> Self {
+        Margins {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Constructs margins with the same distance on all four sides.
+    pub fn all(margin: f64) -> Self {
+        Margins::new(margin, margin, margin, margin)
+    }
+}
+
+impl Default for Margins {
+    /// One inch on all sides.
+    fn default() -> Self {
+        Margins::all(72.0)
+    }
+}
+
+impl PageLayout {
+    pub fn new(size: PageSize) -> Self {
+        PageLayout {
+            size,
+            orientation: Orientation::Portrait,
+            margins: Margins::default(),
+            crop_box: None,
+            bleed_box: None,
+            trim_box: None,
+            rotation: Rotation::None,
+        }
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets the margins used when content is automatically flowed onto the page.
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    /// Sets the region to which the contents of the page are clipped when displayed or printed.
+    pub fn with_crop_box(mut self, crop_box: Rect) -> Self {
+        self.crop_box = Some(crop_box);
+        self
+    }
+
+    /// Sets the region to which the contents of the page are clipped when output in a production
+    /// environment (usually the trim box plus some bleed area).
+    pub fn with_bleed_box(mut self, bleed_box: Rect) -> Self {
+        self.bleed_box = Some(bleed_box);
+        self
+    }
+
+    /// Sets the intended dimensions of the finished page after trimming.
+    pub fn with_trim_box(mut self, trim_box: Rect) -> Self {
+        self.trim_box = Some(trim_box);
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Returns the `(width, height)` of the page in points, taking the orientation into account.
+    pub fn dimensions(&self) -> (f64, f64) {
+        let (width, height) = self.size.dimensions();
+        match self.orientation {
+            Orientation::Portrait => (width, height),
+            Orientation::Landscape => (height, width),
+        }
+    }
+
+    pub fn margins(&self) -> Margins {
+        self.margins
+    }
+
+    /// The width available for content between the left and right margin.
+    pub fn content_width(&self) -> f64 {
+        let (width, _) = self.dimensions();
+        width - self.margins.left - self.margins.right
+    }
+
+    /// The position of the bottom margin, measured from the top of the page.
+    pub fn content_bottom(&self) -> f64 {
+        let (_, height) = self.dimensions();
+        height - self.margins.bottom
+    }
+
+    pub fn media_box(&self) -> Rect {
+        let (width, height) = self.dimensions();
+        (0.0, 0.0, width, height)
+    }
+
+    pub fn crop_box(&self) -> Option<Rect> {
+        self.crop_box
+    }
+
+    pub fn bleed_box(&self) -> Option<Rect> {
+        self.bleed_box
+    }
+
+    pub fn trim_box(&self) -> Option<Rect> {
+        self.trim_box
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+}
+
+impl Default for PageLayout {
+    fn default() -> Self {
+        PageLayout::new(PageSize::default())
+    }
+}
+
+impl From<PageSize> for PageLayout {
+    fn from(size: PageSize) -> Self {
+        PageLayout::new(size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_landscape_dimensions() {
+        let layout = PageLayout::new(PageSize::Letter).with_orientation(Orientation::Landscape);
+        assert_eq!(layout.dimensions(), (792.0, 612.0));
+        assert_eq!(layout.media_box(), (0.0, 0.0, 792.0, 612.0));
+    }
+
+    #[test]
+    fn test_custom_dimensions() {
+        let layout = PageLayout::new(PageSize::Custom {
+            width: 288.0,
+            height: 432.0,
+        });
+        assert_eq!(layout.dimensions(), (288.0, 432.0));
+    }
+
+    #[test]
+    fn test_content_area() {
+        let layout =
+            PageLayout::new(PageSize::Letter).with_margins(Margins::new(10.0, 20.0, 30.0, 40.0));
+        assert_eq!(layout.content_width(), 552.0);
+        assert_eq!(layout.content_bottom(), 762.0);
+    }
 }
@@ -0,0 +1,229 @@
+use std::io;
+
+use crate::stream::ContentStream;
+
+/// The distance of the Bézier control points from the on-curve points (relative to the radius)
+/// used to approximate a quarter circle.
+const KAPPA: f64 = 0.552_284_749_830_793_4;
+
+/// The rule used to determine which areas are inside a path (PDF spec 1.7 page 232).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+/// How a path is painted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    Stroke,
+    Fill(FillRule),
+    FillStroke(FillRule),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathOp {
+    MoveTo(f64, f64),
+    LineTo(f64, f64),
+    CurveTo(f64, f64, f64, f64, f64, f64),
+    Rect(f64, f64, f64, f64),
+    Close,
+}
+
+/// A vector path consisting of one or more subpaths. All coordinates are in points and measured
+/// from the top-left corner of the page (`y` grows downwards).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Path {
+    ops: Vec<PathOp>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path { ops: Vec::new() }
+    }
+
+    /// Constructs a path containing a single rectangle with its top-left corner at `x`, `y`.
+    pub fn rect(x: f64, y: f64, width: f64, height: f64) -> Self {
+        let mut path = Path::new();
+        path.add_rect(x, y, width, height);
+        path
+    }
+
+    /// Constructs a path containing a single circle around `cx`, `cy`.
+    pub fn circle(cx: f64, cy: f64, radius: f64) -> Self {
+        Path::ellipse(cx, cy, radius, radius)
+    }
+
+    /// Constructs a path containing a single ellipse around `cx`, `cy` with the horizontal radius
+    /// `rx` and the vertical radius `ry`.
+    pub fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> Self {
+        let mut path = Path::new();
+        path.add_ellipse(cx, cy, rx, ry);
+        path
+    }
+
+    /// Constructs a path containing a single rectangle with its top-left corner at `x`, `y` and
+    /// corners rounded with the given `radius` (limited to half of the shorter side).
+    pub fn rounded_rect(x: f64, y: f64, width: f64, height: f64, radius: f64) -> Self {
+        let mut path = Path::new();
+        path.add_rounded_rect(x, y, width, height, radius);
+        path
+    }
+
+    /// Begins a new subpath at `x`, `y`.
+    pub fn move_to(&mut self, x: f64, y: f64) -> &mut Self {
+        self.ops.push(PathOp::MoveTo(x, y));
+        self
+    }
+
+    /// Appends a straight line from the current point to `x`, `y`.
+    pub fn line_to(&mut self, x: f64, y: f64) -> &mut Self {
+        self.ops.push(PathOp::LineTo(x, y));
+        self
+    }
+
+    /// Appends a cubic Bézier curve from the current point to `x3`, `y3` using the control points
+    /// `x1`, `y1` and `x2`, `y2`.
+    pub fn curve_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) -> &mut Self {
+        self.ops.push(PathOp::CurveTo(x1, y1, x2, y2, x3, y3));
+        self
+    }
+
+    /// Closes the current subpath with a straight line to its starting point.
+    pub fn close(&mut self) -> &mut Self {
+        self.ops.push(PathOp::Close);
+        self
+    }
+
+    /// Appends a rectangle with its top-left corner at `x`, `y` as a complete subpath.
+    pub fn add_rect(&mut self, x: f64, y: f64, width: f64, height: f64) -> &mut Self {
+        self.ops.push(PathOp::Rect(x, y, width, height));
+        self
+    }
+
+    /// Appends an ellipse around `cx`, `cy` as a complete subpath (approximated with four Bézier
+    /// curves).
+    pub fn add_ellipse(&mut self, cx: f64, cy: f64, rx: f64, ry: f64) -> &mut Self {
+        let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+        self.move_to(cx + rx, cy)
+            .curve_to(cx + rx, cy + ky, cx + kx, cy + ry, cx, cy + ry)
+            .curve_to(cx - kx, cy + ry, cx - rx, cy + ky, cx - rx, cy)
+            .curve_to(cx - rx, cy - ky, cx - kx, cy - ry, cx, cy - ry)
+            .curve_to(cx + kx, cy - ry, cx + rx, cy - ky, cx + rx, cy)
+            .close()
+    }
+
+    /// Appends a rectangle with rounded corners as a complete subpath.
+    pub fn add_rounded_rect(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radius: f64,
+    ) -> &mut Self {
+        let r = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+        let k = r * KAPPA;
+        let (right, bottom) = (x + width, y + height);
+        self.move_to(x + r, y)
+            .line_to(right - r, y)
+            .curve_to(right - r + k, y, right, y + r - k, right, y + r)
+            .line_to(right, bottom - r)
+            .curve_to(
+                right,
+                bottom - r + k,
+                right - r + k,
+                bottom,
+                right - r,
+                bottom,
+            )
+            .line_to(x + r, bottom)
+            .curve_to(x + r - k, bottom, x, bottom - r + k, x, bottom - r)
+            .line_to(x, y + r)
+            .curve_to(x, y + r - k, x + r - k, y, x + r, y)
+            .close()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Writes the path construction operators to the given stream, converting the coordinates
+    /// into PDF's default user space of a page with the given `page_height`.
+    pub(crate) async fn write(
+        &self,
+        wr: &mut dyn ContentStream,
+        page_height: f64,
+    ) -> Result<(), io::Error> {
+        let y = |y: f64| page_height - y;
+        for op in &self.ops {
+            match *op {
+                PathOp::MoveTo(x1, y1) => wr.move_to(x1, y(y1)).await?,
+                PathOp::LineTo(x1, y1) => wr.line_to(x1, y(y1)).await?,
+                PathOp::CurveTo(x1, y1, x2, y2, x3, y3) => {
+                    wr.curve_to(x1, y(y1), x2, y(y2), x3, y(y3)).await?
+                }
+                PathOp::Rect(x, top, width, height) => {
+                    wr.rect(x, y(top + height), width, height).await?
+                }
+                PathOp::Close => wr.close_path().await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::idseq::IdSeq;
+    use crate::writer::DocWriter;
+    use async_std::io::prelude::WriteExt;
+
+    async fn write_path(path: &Path) -> String {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+        path.write(&mut stream, 800.0).await.unwrap();
+        stream.flush().await.unwrap();
+        let result = String::from_utf8_lossy(&buf);
+        result[result.find("stream\n").unwrap() + 7..].to_string()
+    }
+
+    #[async_std::test]
+    async fn test_write_path() {
+        let mut path = Path::new();
+        path.move_to(10.0, 20.0)
+            .line_to(30.0, 40.0)
+            .close()
+            .add_rect(10.0, 20.0, 100.0, 50.0);
+        assert_eq!(
+            write_path(&path).await,
+            "10.000 780.000 m\n30.000 760.000 l\nh\n10.000 730.000 100.000 50.000 re\n"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_write_circle() {
+        let path = Path::circle(100.0, 100.0, 10.0);
+        assert_eq!(
+            write_path(&path).await,
+            "110.000 700.000 m\n\
+             110.000 694.477 105.523 690.000 100.000 690.000 c\n\
+             94.477 690.000 90.000 694.477 90.000 700.000 c\n\
+             90.000 705.523 94.477 710.000 100.000 710.000 c\n\
+             105.523 710.000 110.000 705.523 110.000 700.000 c\nh\n"
+        );
+    }
+
+    #[test]
+    fn test_rounded_rect_radius_limit() {
+        assert_eq!(
+            Path::rounded_rect(0.0, 0.0, 20.0, 10.0, 50.0),
+            Path::rounded_rect(0.0, 0.0, 20.0, 10.0, 5.0)
+        );
+    }
+}
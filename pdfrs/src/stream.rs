@@ -3,7 +3,11 @@ use std::io;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 
+use crate::color::{Color, DeviceColorSpace};
 use crate::fonts::{Font, SubsetRef};
+use crate::graphics::{LineCap, LineJoin, Transform};
+use crate::path::FillRule;
+use crate::structure::Artifact;
 use crate::writer::DocWriter;
 use async_compression::futures::write::ZlibEncoder;
 use async_std::io::prelude::WriteExt;
@@ -11,8 +15,8 @@ use async_std::task::Context;
 use async_std::task::Poll;
 use futures_io::AsyncWrite;
 use pin_project::pin_project;
-use serde::Serialize;
-use serde_pdf::{Object, ObjectId, Reference};
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, Object, ObjectId, Reference};
 
 /// A type used to handle writing a PDF stream to a PDF document. It handles creating a
 /// corresponding PDF object, keeping track of the stream's length as well as writing the stream
@@ -47,12 +51,25 @@ struct StreamMeta {
     filter: Vec<Filter>,
 }
 
-#[derive(Serialize)]
-enum Filter {
-    FlateDecode,
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Filter {
+    FlateDecode(Option<DecodeParms>),
+    DCTDecode,
     // TODO: ASCII85Decode,
 }
 
+/// The parameters of the `FlateDecode` filter (PDF spec 1.7 page 76).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+pub(crate) struct DecodeParms {
+    /// `15` for PNG predictors (which can differ per row).
+    pub predictor: u8,
+    pub colors: u8,
+    pub bits_per_component: u8,
+    pub columns: u32,
+}
+
 /// A type used to create PDF references (`Reference<StreamRef>`).
 pub type StreamRef = ();
 
@@ -64,6 +81,46 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
         with_len1: bool,
     ) -> Result<Stream<W>, io::Error> {
         let id = wr.reserve_object_id();
+        Stream::start_inner(wr, id, compresse, with_len1, |length, length1, filter| {
+            StreamMeta {
+                length,
+                length1,
+                filter,
+            }
+        })
+        .await
+    }
+
+    /// Constructs a new PDF stream for the already reserved object `id`. The stream's dictionary
+    /// is created by `dict` from the reference to the stream's length and the filters applied to
+    /// the stream.
+    pub(crate) async fn start_with_dict<D, F>(
+        wr: DocWriter<W>,
+        id: ObjectId,
+        compresse: bool,
+        dict: F,
+    ) -> Result<Stream<W>, io::Error>
+    where
+        D: Serialize,
+        F: FnOnce(Reference<usize>, Vec<Filter>) -> D,
+    {
+        Stream::start_inner(wr, id, compresse, false, |length, _, filter| {
+            dict(length, filter)
+        })
+        .await
+    }
+
+    async fn start_inner<D, F>(
+        mut wr: DocWriter<W>,
+        id: ObjectId,
+        compresse: bool,
+        with_len1: bool,
+        dict: F,
+    ) -> Result<Stream<W>, io::Error>
+    where
+        D: Serialize,
+        F: FnOnce(Reference<usize>, Option<Reference<usize>>, Vec<Filter>) -> D,
+    {
         let len_obj_id = wr.reserve_object_id();
         let len1_obj_id = if compresse && with_len1 {
             Some(wr.reserve_object_id())
@@ -73,21 +130,22 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
 
         wr.add_xref(id.id());
         writeln!(wr, "{} {} obj", id.id(), id.rev()).await?;
-        to_async_writer(
-            &mut wr,
-            &StreamMeta {
-                length: Reference::new(len_obj_id.clone()),
-                length1: len1_obj_id.clone().map(Reference::new),
-                filter: if compresse {
-                    vec![Filter::FlateDecode]
+        wr.write_encrypted(
+            &id,
+            &dict(
+                Reference::new(len_obj_id.clone()),
+                len1_obj_id.clone().map(Reference::new),
+                if compresse {
+                    vec![Filter::FlateDecode(None)]
                 } else {
                     Vec::new()
                 },
-            },
+            ),
         )
         .await
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
         writeln!(wr, "\nstream").await?;
+        wr.begin_stream_encryption(&id);
 
         Ok(Stream {
             id,
@@ -120,41 +178,48 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
     /// markers and the stream's length object.
     pub async fn end(mut self) -> Result<DocWriter<W>, io::Error> {
         self.flush().await?;
-        let len = self.wr.len() - self.doc_len_before;
         let mut wr = self.wr.into_inner();
+        wr.end_stream_encryption().await?;
+        let len = wr.len() - self.doc_len_before;
         writeln!(wr, "\nendstream\nendobj\n").await?;
 
-        wr.add_xref(self.len_obj_id.id());
         let len_obj = Object::new(self.len_obj_id.id(), self.len_obj_id.rev(), len);
-        to_async_writer(&mut wr, &len_obj)
+        wr.write_object(len_obj)
             .await
             .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
         if let Some(len1_obj_id) = self.len1_obj_id {
-            wr.add_xref(len1_obj_id.id());
             let len1_obj = Object::new(len1_obj_id.id(), len1_obj_id.rev(), self.len1);
-            to_async_writer(&mut wr, &len1_obj)
+            wr.write_object(len1_obj)
                 .await
                 .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
         }
 
         Ok(wr)
     }
+}
+
+/// The operators of a PDF content stream, shared by the content streams of pages and the recorded
+/// content of form XObjects (see [`FormRecorder`](crate::form::FormRecorder)).
+#[async_trait::async_trait(?Send)]
+pub trait ContentStream: AsyncWrite + Unpin {
+    /// The font subset used by the most recently shown text (if its `TJ` array is still open).
+    fn prev_subset(&mut self) -> &mut Option<SubsetRef>;
 
     /// Begins a text object (BT - PDF spec 1.7 page 405).
-    pub async fn begin_text(&mut self) -> Result<(), io::Error> {
+    async fn begin_text(&mut self) -> Result<(), io::Error> {
         // FIXME: move text operations into an object returned here to prevent nested BT.
         writeln!(self, "BT").await
     }
 
     /// Ends a text object (ET - PDF spec 1.7 page 405).
-    pub async fn end_text(&mut self) -> Result<(), io::Error> {
+    async fn end_text(&mut self) -> Result<(), io::Error> {
         writeln!(self, "ET").await
     }
 
     /// Sets the text matrix (Tm - PDF spec 1.7 page 406).
     #[allow(clippy::many_single_char_names)]
-    pub async fn set_text_matrix(
+    async fn set_text_matrix(
         &mut self,
         a: f64,
         b: f64,
@@ -172,34 +237,265 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
     }
 
     /// Sets the text leading (TL - PDF spec 1.7 page 398).
-    pub async fn set_text_leading(&mut self, leading: f64) -> Result<(), io::Error> {
+    async fn set_text_leading(&mut self, leading: f64) -> Result<(), io::Error> {
         writeln!(self, "{:.3} TL", leading).await
     }
 
+    /// Moves to the start of the next line, offset by the text leading (T* - PDF spec 1.7 page
+    /// 406).
+    async fn next_line(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "T*").await
+    }
+
+    /// Sets the additional space added to each single-byte space character (Tw - PDF spec 1.7 page
+    /// 398).
+    async fn set_word_spacing(&mut self, word_spacing: f64) -> Result<(), io::Error> {
+        writeln!(self, "{:.3} Tw", word_spacing).await
+    }
+
     /// Sets the text font and font size (Tf - PDF spec 1.7 page 398).
-    pub async fn set_text_font(&mut self, font_id: usize, size: f64) -> Result<(), io::Error> {
+    async fn set_text_font(&mut self, font_id: usize, size: f64) -> Result<(), io::Error> {
         writeln!(self, "/F{} {:.3} Tf", font_id, size).await
     }
 
-    // Sets the color to use for non-stroking operations (sc - PDF spec 1.7 page 287).
-    pub async fn set_fill_color(&mut self, c1: f64, c2: f64, c3: f64) -> Result<(), io::Error> {
-        writeln!(self, "{:.3} {:.3} {:.3} sc", c1, c2, c3).await
+    /// Sets the color used for non-stroking operations (g, rg, k, or cs and scn for all other color
+    /// spaces - PDF spec 1.7 page 287).
+    async fn set_fill_color(&mut self, color: &Color) -> Result<(), io::Error> {
+        self.write_color(color, ["g", "rg", "k", "cs", "scn"]).await
+    }
+
+    /// Sets the color used for stroking operations (G, RG, K, or CS and SCN for all other color
+    /// spaces - PDF spec 1.7 page 287).
+    async fn set_stroke_color(&mut self, color: &Color) -> Result<(), io::Error> {
+        self.write_color(color, ["G", "RG", "K", "CS", "SCN"]).await
+    }
+
+    /// Writes the components of `color` followed by the operator of its color space.
+    async fn write_color(&mut self, color: &Color, ops: [&str; 5]) -> Result<(), io::Error> {
+        let [gray, rgb, cmyk, cs, scn] = ops;
+        let op = match color.device_color_space() {
+            Some(DeviceColorSpace::Gray) => gray,
+            Some(DeviceColorSpace::Rgb) => rgb,
+            Some(DeviceColorSpace::Cmyk) => cmyk,
+            None => {
+                if let Some(name) = color.color_space_name() {
+                    writeln!(self, "/{} {}", name, cs).await?;
+                }
+                scn
+            }
+        };
+        for c in color.components() {
+            write!(self, "{:.3} ", c).await?;
+        }
+        writeln!(self, "{}", op).await
+    }
+
+    /// Saves the current graphics state on the graphics state stack (q - PDF spec 1.7 page 219).
+    async fn save_state(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "q").await
+    }
+
+    /// Restores the graphics state most recently saved (Q - PDF spec 1.7 page 219).
+    async fn restore_state(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "Q").await
+    }
+
+    /// Modifies the current transformation matrix by concatenating the given `transform` (cm -
+    /// PDF spec 1.7 page 219).
+    async fn transform(&mut self, t: Transform) -> Result<(), io::Error> {
+        writeln!(
+            self,
+            "{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} cm",
+            t.a, t.b, t.c, t.d, t.e, t.f
+        )
+        .await
+    }
+
+    /// Sets the line width (w - PDF spec 1.7 page 219).
+    async fn set_line_width(&mut self, width: f64) -> Result<(), io::Error> {
+        writeln!(self, "{:.3} w", width).await
+    }
+
+    /// Sets the line cap style (J - PDF spec 1.7 page 219).
+    async fn set_line_cap(&mut self, cap: LineCap) -> Result<(), io::Error> {
+        writeln!(self, "{} J", cap as u8).await
+    }
+
+    /// Sets the line join style (j - PDF spec 1.7 page 219).
+    async fn set_line_join(&mut self, join: LineJoin) -> Result<(), io::Error> {
+        writeln!(self, "{} j", join as u8).await
+    }
+
+    /// Sets the miter limit (M - PDF spec 1.7 page 219).
+    async fn set_miter_limit(&mut self, limit: f64) -> Result<(), io::Error> {
+        writeln!(self, "{:.3} M", limit).await
+    }
+
+    /// Sets the line dash pattern (d - PDF spec 1.7 page 219). An empty `dashes` array results in
+    /// solid lines.
+    async fn set_dash_pattern(&mut self, dashes: &[f64], phase: f64) -> Result<(), io::Error> {
+        let dashes = dashes
+            .iter()
+            .map(|d| format!("{:.3}", d))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(self, "[{}] {:.3} d", dashes, phase).await
+    }
+
+    /// Paints the external object (e.g. an image) with the given resource `name` (Do - PDF spec 1.7
+    /// page 332).
+    async fn paint_x_object(&mut self, name: &str) -> Result<(), io::Error> {
+        writeln!(self, "/{} Do", name).await
+    }
+
+    /// Sets the parameters of the graphics state parameter dictionary with the given resource
+    /// `name` (gs - PDF spec 1.7 page 219).
+    async fn set_graphics_state(&mut self, name: &str) -> Result<(), io::Error> {
+        writeln!(self, "/{} gs", name).await
+    }
+
+    /// Begins a marked-content sequence with the given `tag` and without properties (BMC - PDF
+    /// spec 1.7 page 565).
+    async fn begin_marked_content_sequence(&mut self, tag: &str) -> Result<(), io::Error> {
+        writeln!(self, "/{} BMC", tag).await
+    }
+
+    /// Begins a marked-content sequence of the structure element with the given `role`, which
+    /// refers to the sequence by its marked-content `id` (BDC - PDF spec 1.7 page 565).
+    async fn begin_marked_content(&mut self, role: &str, id: usize) -> Result<(), io::Error> {
+        writeln!(self, "/{} <</MCID {}>> BDC", role, id).await
+    }
+
+    /// Begins a marked-content sequence of content that is not part of the document's logical
+    /// structure (BDC - PDF spec 1.7 page 615).
+    async fn begin_artifact(&mut self, artifact: Artifact) -> Result<(), io::Error> {
+        writeln!(self, "/Artifact {} BDC", artifact.properties()).await
+    }
+
+    /// Ends the current marked-content sequence (EMC - PDF spec 1.7 page 565).
+    async fn end_marked_content(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "EMC").await
+    }
+
+    /// Begins a new subpath at `x`, `y` (m - PDF spec 1.7 page 226).
+    async fn move_to(&mut self, x: f64, y: f64) -> Result<(), io::Error> {
+        writeln!(self, "{:.3} {:.3} m", x, y).await
+    }
+
+    /// Appends a straight line from the current point to `x`, `y` (l - PDF spec 1.7 page 226).
+    async fn line_to(&mut self, x: f64, y: f64) -> Result<(), io::Error> {
+        writeln!(self, "{:.3} {:.3} l", x, y).await
+    }
+
+    /// Appends a cubic Bézier curve from the current point to `x3`, `y3` using the control points
+    /// `x1`, `y1` and `x2`, `y2` (c - PDF spec 1.7 page 226).
+    async fn curve_to(
+        &mut self,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        x3: f64,
+        y3: f64,
+    ) -> Result<(), io::Error> {
+        writeln!(
+            self,
+            "{:.3} {:.3} {:.3} {:.3} {:.3} {:.3} c",
+            x1, y1, x2, y2, x3, y3
+        )
+        .await
+    }
+
+    /// Appends a rectangle with its lower-left corner at `x`, `y` as a complete subpath (re - PDF
+    /// spec 1.7 page 227).
+    async fn rect(&mut self, x: f64, y: f64, width: f64, height: f64) -> Result<(), io::Error> {
+        writeln!(self, "{:.3} {:.3} {:.3} {:.3} re", x, y, width, height).await
+    }
+
+    /// Closes the current subpath with a straight line to its starting point (h - PDF spec 1.7
+    /// page 227).
+    async fn close_path(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "h").await
+    }
+
+    /// Strokes the current path (S - PDF spec 1.7 page 230).
+    async fn stroke(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "S").await
+    }
+
+    /// Fills the current path (f, f* - PDF spec 1.7 page 230).
+    async fn fill(&mut self, rule: FillRule) -> Result<(), io::Error> {
+        match rule {
+            FillRule::NonZero => writeln!(self, "f").await,
+            FillRule::EvenOdd => writeln!(self, "f*").await,
+        }
+    }
+
+    /// Fills and then strokes the current path (B, B* - PDF spec 1.7 page 230).
+    async fn fill_stroke(&mut self, rule: FillRule) -> Result<(), io::Error> {
+        match rule {
+            FillRule::NonZero => writeln!(self, "B").await,
+            FillRule::EvenOdd => writeln!(self, "B*").await,
+        }
+    }
+
+    /// Intersects the current clipping path with the current path (W, W* - PDF spec 1.7 page 235).
+    /// The clipping path only takes effect after the path has been painted or ended (see
+    /// [`ContentStream::end_path`]).
+    async fn clip(&mut self, rule: FillRule) -> Result<(), io::Error> {
+        match rule {
+            FillRule::NonZero => writeln!(self, "W").await,
+            FillRule::EvenOdd => writeln!(self, "W*").await,
+        }
+    }
+
+    /// Ends the current path without painting it (n - PDF spec 1.7 page 230).
+    async fn end_path(&mut self) -> Result<(), io::Error> {
+        writeln!(self, "n").await
+    }
+
+    async fn show_text_string(
+        &mut self,
+        text: &str,
+        font: &dyn Font,
+        size: f64,
+    ) -> Result<HashSet<SubsetRef>, io::Error> {
+        self.show_spaced_text_string(text, font, size, 0.0).await
     }
 
-    pub async fn show_text_string(
+    /// Shows the given `text` like [`ContentStream::show_text_string`], but adds `word_spacing` points
+    /// after each space. For fonts that support it, this is expected to be done by the word spacing
+    /// text state (see [`ContentStream::set_word_spacing`]), so the spacing is only added as `TJ`
+    /// adjustments for all other fonts (e.g. subset fonts).
+    async fn show_spaced_text_string(
         &mut self,
         text: &str,
         font: &dyn Font,
         size: f64,
+        word_spacing: f64,
     ) -> Result<HashSet<SubsetRef>, io::Error> {
+        let spacing = if word_spacing != 0.0 && !font.supports_word_spacing() {
+            Some(-word_spacing * 1000.0 / size)
+        } else {
+            None
+        };
+
         let mut subset_refs = HashSet::with_capacity(1);
         let mut prev = None;
         let mut offset = 0;
         for (i, c) in text.char_indices() {
-            if let Some(kerning) = prev.and_then(|p| font.kerning(p, c)) {
+            let kerning = prev.and_then(|p| font.kerning(p, c));
+            let spacing = spacing.filter(|_| prev == Some(' '));
+            if kerning.is_some() || spacing.is_some() {
                 let srfs = self.write_text(&text[offset..i], font, size).await?;
                 subset_refs.extend(srfs);
-                write!(self, " {} ", -kerning).await?;
+                match (kerning, spacing) {
+                    (Some(kerning), None) => write!(self, " {} ", -kerning).await?,
+                    (kerning, spacing) => {
+                        let adjustment = spacing.unwrap_or(0.0) - f64::from(kerning.unwrap_or(0));
+                        write!(self, " {:.3} ", adjustment).await?
+                    }
+                }
                 offset = i;
             }
             prev = Some(c);
@@ -208,12 +504,18 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
             let srfs = self.write_text(&text[offset..], font, size).await?;
             subset_refs.extend(srfs);
         }
+        // a trailing space is followed by the next text string (e.g. one with a different style)
+        if let Some(spacing) = spacing.filter(|_| prev == Some(' ')) {
+            write!(self, " {:.3} ", spacing).await?;
+        }
 
         writeln!(self, "] TJ").await?;
-        self.prev_subset = None;
+        *self.prev_subset() = None;
         Ok(subset_refs)
     }
 
+    /// Writes the encoded `text` into the current `TJ` array, starting a new array (and setting
+    /// the font) whenever the font subset changes.
     async fn write_text(
         &mut self,
         text: &str,
@@ -228,8 +530,8 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
         loop {
             let substr = &text[offset..];
             let (subset_ref, n) = font.encode_into(substr, &mut buf)?;
-            if self.prev_subset != Some(subset_ref) {
-                if self.prev_subset.is_some() {
+            if *self.prev_subset() != Some(subset_ref) {
+                if self.prev_subset().is_some() {
                     writeln!(self, "] TJ").await?
                 }
                 self.set_text_font(subset_ref.font_id(), size).await?;
@@ -238,7 +540,7 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
 
             self.write_all(&buf).await?;
             subset_refs.insert(subset_ref);
-            self.prev_subset = Some(subset_ref);
+            *self.prev_subset() = Some(subset_ref);
             if n < substr.len() {
                 offset += n;
                 buf.clear();
@@ -251,6 +553,12 @@ impl<W: AsyncWrite + Unpin> Stream<W> {
     }
 }
 
+impl<W: AsyncWrite + Unpin> ContentStream for Stream<W> {
+    fn prev_subset(&mut self) -> &mut Option<SubsetRef> {
+        &mut self.prev_subset
+    }
+}
+
 impl<W: AsyncWrite + Unpin> AsyncWrite for Stream<W> {
     fn poll_write(
         self: Pin<&mut Self>,
@@ -287,6 +595,38 @@ impl<W: AsyncWrite + Unpin> AsyncWrite for Stream<W> {
     }
 }
 
+impl Filter {
+    /// The decode parameters of each of the given `filters` (as expected by the `/DecodeParms`
+    /// entry of a stream), or `None` if none of the filters has any parameters.
+    pub(crate) fn decode_parms(filters: &[Filter]) -> Option<Vec<Option<DecodeParms>>> {
+        let parms = filters
+            .iter()
+            .map(|filter| match filter {
+                Filter::FlateDecode(parms) => *parms,
+                Filter::DCTDecode => None,
+            })
+            .collect::<Vec<_>>();
+        if parms.iter().any(Option::is_some) {
+            Some(parms)
+        } else {
+            None
+        }
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Name(match self {
+            Filter::FlateDecode(_) => "FlateDecode",
+            Filter::DCTDecode => "DCTDecode",
+        })
+        .serialize(serializer)
+    }
+}
+
 pub async fn to_async_writer<W, T>(mut w: W, value: &T) -> Result<(), serde_pdf::Error>
 where
     W: AsyncWrite + Unpin,
@@ -338,6 +678,7 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::color::SpotColor;
     use crate::fonts::HELVETICA;
     use crate::idseq::IdSeq;
 
@@ -356,7 +697,158 @@ mod test {
             .unwrap();
         assert_eq!(
             &String::from_utf8_lossy(&buf[len_before..]),
-            "/F0 12.000 Tf\n[(Hello W) 30 (or) -15 (ld)] TJ\n"
+            "/F0 12.000 Tf\n[(Hello ) 40 (W) 30 (or) -15 (ld)] TJ\n"
+        );
+    }
+
+    /// A font without word spacing support, that encodes text like Helvetica.
+    struct SubsetFont;
+
+    impl Font for SubsetFont {
+        fn base_name(&self) -> &str {
+            "Test"
+        }
+
+        fn kerning(&self, lhs: char, rhs: char) -> Option<i32> {
+            if lhs == ' ' && rhs == 'W' {
+                Some(-20)
+            } else {
+                None
+            }
+        }
+
+        fn encode_into(
+            &self,
+            text: &str,
+            buf: &mut Vec<u8>,
+        ) -> Result<(SubsetRef, usize), io::Error> {
+            Font::encode_into(&&*HELVETICA, text, buf)
+        }
+
+        fn supports_word_spacing(&self) -> bool {
+            false
+        }
+
+        fn char_width(&self, _ch: char) -> u32 {
+            500
+        }
+
+        fn ascent(&self) -> i32 {
+            700
+        }
+
+        fn descent(&self) -> i32 {
+            -200
+        }
+
+        fn is_embedded(&self) -> bool {
+            true
+        }
+    }
+
+    #[async_std::test]
+    async fn test_word_spacing_adjustments() {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+
+        let len_before = stream.wr.len();
+        stream
+            .show_spaced_text_string("a b World", &SubsetFont, 10.0, 2.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            &String::from_utf8_lossy(&buf[len_before..]),
+            "/F0 10.000 Tf\n[(a ) -200.000 (b ) -180.000 (World)] TJ\n"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_word_spacing_adjustment_after_trailing_space() {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+
+        // the text might be followed by a text string with a different style
+        let len_before = stream.wr.len();
+        stream
+            .show_spaced_text_string("a ", &SubsetFont, 10.0, 2.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            &String::from_utf8_lossy(&buf[len_before..]),
+            "/F0 10.000 Tf\n[(a ) -200.000 ] TJ\n"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_word_spacing_operator() {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+
+        // fonts that support it use the word spacing text state instead
+        let len_before = stream.wr.len();
+        stream
+            .show_spaced_text_string("a b", &&*HELVETICA, 10.0, 2.0)
+            .await
+            .unwrap();
+        assert_eq!(
+            &String::from_utf8_lossy(&buf[len_before..]),
+            "/F0 10.000 Tf\n[(a b)] TJ\n"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_color_operators() {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+
+        let len_before = stream.wr.len();
+        stream.set_fill_color(&Color::Gray(0.5)).await.unwrap();
+        stream
+            .set_stroke_color(&Color::Rgb(1.0, 0.0, 0.0))
+            .await
+            .unwrap();
+        stream
+            .set_fill_color(&Color::Cmyk(0.0, 0.1, 0.2, 0.3))
+            .await
+            .unwrap();
+        stream
+            .set_stroke_color(&Color::Spot(SpotColor(ObjectId::new(7, 0)), 0.8))
+            .await
+            .unwrap();
+        assert_eq!(
+            &String::from_utf8_lossy(&buf[len_before..]),
+            "0.500 g\n1.000 0.000 0.000 RG\n0.000 0.100 0.200 0.300 k\n/CS7 CS\n0.800 SCN\n"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_marked_content_operators() {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+
+        let len_before = stream.wr.len();
+        stream.begin_marked_content("H1", 0).await.unwrap();
+        stream.end_marked_content().await.unwrap();
+        stream.begin_artifact(Artifact::Footer).await.unwrap();
+        stream.end_marked_content().await.unwrap();
+        assert_eq!(
+            &String::from_utf8_lossy(&buf[len_before..]),
+            "/H1 <</MCID 0>> BDC\nEMC\n/Artifact <</Type /Pagination /Subtype /Footer>> BDC\nEMC\n"
         );
     }
 }
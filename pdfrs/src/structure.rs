@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+
+use crate::document::Error;
+use crate::page::Page;
+use crate::writer::DocWriter;
+use async_std::io::prelude::Write;
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
+use serde_pdf::{Name, Object, ObjectId, PdfText, Reference};
+
+/// A structure element of a tagged PDF, started with
+/// [`Document::begin_tag`](crate::Document::begin_tag). It describes the role of all content added
+/// until it is ended, e.g. for screen readers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    role: Role,
+    alt: Option<String>,
+    actual_text: Option<String>,
+    lang: Option<String>,
+}
+
+/// The standard structure types (PDF spec 1.7 page 590ff).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Document,
+    H1,
+    H2,
+    H3,
+    H4,
+    H5,
+    H6,
+    /// A paragraph.
+    P,
+    /// A list (whose items are [`Role::LI`]).
+    L,
+    /// A list item.
+    LI,
+    Table,
+    /// A table row.
+    TR,
+    /// A table header cell.
+    TH,
+    /// A table data cell.
+    TD,
+    /// An image or graphic (which should have an alternate description, see [`Tag::with_alt`]).
+    Figure,
+}
+
+/// Content that is not part of the document's logical structure (e.g. page numbers), started with
+/// [`Document::begin_artifact`](crate::Document::begin_artifact).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// A running header of a page.
+    Header,
+    /// A running footer of a page (e.g. the page number).
+    Footer,
+    /// Content used for the layout only (e.g. separator lines).
+    Layout,
+    /// A background image or color.
+    Background,
+}
+
+/// The structure tree of a tagged document, recording which marked content on which page belongs
+/// to which structure element.
+#[derive(Default)]
+pub(crate) struct StructTree {
+    elements: Vec<Element>,
+    /// The elements that have been started but not ended yet, the innermost last.
+    open: Vec<usize>,
+    /// The elements the marked content of each page belongs to (by page index). The marked-content
+    /// ids of a page are the indices into its list.
+    parents: BTreeMap<usize, Vec<usize>>,
+}
+
+struct Element {
+    tag: Tag,
+    parent: Option<usize>,
+    kids: Vec<Kid>,
+}
+
+enum Kid {
+    Element(usize),
+    Content { page: usize, mcid: usize },
+}
+
+/// A type used to create PDF references to the structure tree root and its elements.
+pub(crate) type StructTreeRef = ();
+
+/// The structure tree root (PDF spec 1.7 page 577).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "StructTreeRoot")]
+struct StructTreeRoot {
+    k: Vec<Reference<StructTreeRef>>,
+    parent_tree: Reference<StructTreeRef>,
+    parent_tree_next_key: usize,
+}
+
+/// A structure element dictionary (PDF spec 1.7 page 578).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "StructElem")]
+struct StructElem<'a> {
+    #[serde(rename = "S")]
+    s: &'a Role,
+    #[serde(rename = "P")]
+    p: Reference<StructTreeRef>,
+    #[serde(rename = "K")]
+    k: Vec<KidRef<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alt: Option<PdfText<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual_text: Option<PdfText<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lang: Option<PdfText<'a>>,
+}
+
+enum KidRef<'a> {
+    Element(Reference<StructTreeRef>),
+    Content(MarkedContentRef<'a>),
+}
+
+/// A marked-content reference dictionary (PDF spec 1.7 page 580).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "MCR")]
+struct MarkedContentRef<'a> {
+    pg: &'a Reference<Page>,
+    #[serde(rename = "MCID")]
+    mcid: usize,
+}
+
+/// The parent tree (PDF spec 1.7 page 584), a number tree that maps the `StructParents` key of
+/// each page (its index) to the elements of the page's marked content (indexed by their ids).
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[serde(rename = "")]
+struct ParentTree {
+    nums: ParentTreeNums,
+}
+
+struct ParentTreeNums(Vec<(usize, Vec<Reference<StructTreeRef>>)>);
+
+impl Tag {
+    pub fn new(role: Role) -> Self {
+        Tag {
+            role,
+            alt: None,
+            actual_text: None,
+            lang: None,
+        }
+    }
+
+    /// Sets the alternate description of the element (e.g. of a [`Role::Figure`]), which is used
+    /// instead of its content by screen readers.
+    pub fn with_alt(mut self, alt: impl Into<String>) -> Self {
+        self.alt = Some(alt.into());
+        self
+    }
+
+    /// Sets the exact text the element's content represents, for content whose text can not be
+    /// extracted reliably (e.g. shaped text or text drawn as graphic).
+    pub fn with_actual_text(mut self, actual_text: impl Into<String>) -> Self {
+        self.actual_text = Some(actual_text.into());
+        self
+    }
+
+    /// Sets the language (e.g. `de-DE`) of the element's content if it differs from the
+    /// document's language.
+    pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+}
+
+impl Role {
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Document => "Document",
+            Role::H1 => "H1",
+            Role::H2 => "H2",
+            Role::H3 => "H3",
+            Role::H4 => "H4",
+            Role::H5 => "H5",
+            Role::H6 => "H6",
+            Role::P => "P",
+            Role::L => "L",
+            Role::LI => "LI",
+            Role::Table => "Table",
+            Role::TR => "TR",
+            Role::TH => "TH",
+            Role::TD => "TD",
+            Role::Figure => "Figure",
+        }
+    }
+}
+
+impl Artifact {
+    /// The property list of the artifact's marked-content sequence (PDF spec 1.7 page 615).
+    pub(crate) fn properties(self) -> &'static str {
+        match self {
+            Artifact::Header => "<</Type /Pagination /Subtype /Header>>",
+            Artifact::Footer => "<</Type /Pagination /Subtype /Footer>>",
+            Artifact::Layout => "<</Type /Layout>>",
+            Artifact::Background => "<</Type /Background>>",
+        }
+    }
+}
+
+impl StructTree {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Starts the given element as child of the innermost open element.
+    pub(crate) fn begin(&mut self, tag: Tag) {
+        let parent = self.open.last().copied();
+        let index = self.elements.len();
+        if let Some(parent) = parent {
+            self.elements[parent].kids.push(Kid::Element(index));
+        }
+        self.elements.push(Element {
+            tag,
+            parent,
+            kids: Vec::new(),
+        });
+        self.open.push(index);
+    }
+
+    /// Ends the innermost open element. Returns `false` if there is none.
+    pub(crate) fn end(&mut self) -> bool {
+        self.open.pop().is_some()
+    }
+
+    pub(crate) fn has_open_elements(&self) -> bool {
+        !self.open.is_empty()
+    }
+
+    /// Adds a new marked-content sequence on the page with the given index to the innermost open
+    /// element. Returns the role of the element and the id of the sequence, or `None` if there is
+    /// no open element.
+    pub(crate) fn add_content(&mut self, page: usize) -> Option<(Role, usize)> {
+        let index = *self.open.last()?;
+        let parents = self.parents.entry(page).or_default();
+        let mcid = parents.len();
+        parents.push(index);
+        let element = &mut self.elements[index];
+        element.kids.push(Kid::Content { page, mcid });
+        Some((element.tag.role, mcid))
+    }
+
+    /// Whether there is any marked content on the page with the given index.
+    pub(crate) fn has_content_on(&self, page: usize) -> bool {
+        self.parents.contains_key(&page)
+    }
+
+    /// Writes the structure tree root, all structure elements and the parent tree. The marked
+    /// content is resolved among the given `pages`.
+    pub(crate) async fn write<W: Write + Unpin>(
+        self,
+        doc: &mut DocWriter<W>,
+        pages: &[(Reference<Page>, f64)],
+    ) -> Result<Reference<StructTreeRef>, Error> {
+        if self.has_open_elements() {
+            return Err(Error::TagNotEnded);
+        }
+
+        let root_id = doc.reserve_object_id();
+        let parent_tree_id = doc.reserve_object_id();
+        let ids = self
+            .elements
+            .iter()
+            .map(|_| doc.reserve_object_id())
+            .collect::<Vec<ObjectId>>();
+        let reference = |i: usize| Reference::new(ids[i].clone());
+
+        let root = StructTreeRoot {
+            k: self
+                .elements
+                .iter()
+                .enumerate()
+                .filter(|(_, element)| element.parent.is_none())
+                .map(|(i, _)| reference(i))
+                .collect(),
+            parent_tree: Reference::new(parent_tree_id.clone()),
+            parent_tree_next_key: pages.len(),
+        };
+        doc.write_object(Object::new(root_id.id(), root_id.rev(), root))
+            .await?;
+
+        let parent_tree = ParentTree {
+            nums: ParentTreeNums(
+                self.parents
+                    .iter()
+                    .map(|(page, parents)| (*page, parents.iter().map(|p| reference(*p)).collect()))
+                    .collect(),
+            ),
+        };
+        doc.write_object(Object::new(
+            parent_tree_id.id(),
+            parent_tree_id.rev(),
+            parent_tree,
+        ))
+        .await?;
+
+        for (element, id) in self.elements.iter().zip(&ids) {
+            let kids = element
+                .kids
+                .iter()
+                .map(|kid| match kid {
+                    Kid::Element(i) => Ok(KidRef::Element(reference(*i))),
+                    Kid::Content { page, mcid } => Ok(KidRef::Content(MarkedContentRef {
+                        pg: &pages.get(*page).ok_or(Error::StreamGone)?.0,
+                        mcid: *mcid,
+                    })),
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let tag = &element.tag;
+            let dict = StructElem {
+                s: &tag.role,
+                p: match element.parent {
+                    Some(parent) => reference(parent),
+                    None => Reference::new(root_id.clone()),
+                },
+                k: kids,
+                alt: tag.alt.as_deref().map(PdfText),
+                actual_text: tag.actual_text.as_deref().map(PdfText),
+                lang: tag.lang.as_deref().map(PdfText),
+            };
+            doc.write_object(Object::new(id.id(), id.rev(), dict))
+                .await?;
+        }
+
+        Ok(Reference::new(root_id))
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Name(self.name()).serialize(serializer)
+    }
+}
+
+impl<'a> Serialize for KidRef<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            KidRef::Element(reference) => reference.serialize(serializer),
+            KidRef::Content(content) => content.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for ParentTreeNums {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_seq(Some(self.0.len() * 2))?;
+        for (key, parents) in &self.0 {
+            s.serialize_element(key)?;
+            s.serialize_element(parents)?;
+        }
+        s.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_add_content() {
+        let mut tree = StructTree::default();
+        assert_eq!(tree.add_content(0), None);
+
+        tree.begin(Tag::new(Role::Document));
+        tree.begin(Tag::new(Role::H1));
+        assert_eq!(tree.add_content(0), Some((Role::H1, 0)));
+        assert!(tree.end());
+        tree.begin(Tag::new(Role::P));
+        assert_eq!(tree.add_content(0), Some((Role::P, 1)));
+        // ids start over on each page
+        assert_eq!(tree.add_content(1), Some((Role::P, 0)));
+        assert!(tree.end());
+        assert_eq!(tree.add_content(1), Some((Role::Document, 1)));
+        assert!(tree.end());
+        assert!(!tree.end());
+
+        assert_eq!(tree.parents[&0], vec![1, 2]);
+        assert_eq!(tree.parents[&1], vec![2, 0]);
+        assert!(tree.has_content_on(1));
+        assert!(!tree.has_content_on(2));
+    }
+}
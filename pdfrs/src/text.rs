@@ -1,23 +1,213 @@
 use std::collections::HashSet;
 use std::io;
 
+use crate::color::Color;
 use crate::fonts::{Font, SubsetRef};
-use crate::stream::Stream;
-use async_std::io::prelude::Write;
+use crate::page::Rect;
+use crate::stream::ContentStream;
 
-// TODO: non-Arc font
-pub async fn write_text<W: Write + Unpin>(
+/// The corner of the page the coordinates of [`TextOptions`] are relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// `y` grows downwards and is the distance between the top of the page and the top of the
+    /// first line of text.
+    TopLeft,
+    /// `y` grows upwards and is the position of the baseline of the first line of text (PDF's
+    /// default user space).
+    BottomLeft,
+}
+
+/// The position and appearance of text written to a page. All values are in points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOptions {
+    x: f64,
+    y: f64,
+    origin: Origin,
+    font_size: f64,
+    line_height: f64,
+    color: Color,
+}
+
+impl TextOptions {
+    pub fn new() -> Self {
+        TextOptions {
+            x: 10.0,
+            y: 10.0,
+            origin: Origin::TopLeft,
+            font_size: 11.0,
+            line_height: 10.175,
+            color: Color::BLACK,
+        }
+    }
+
+    pub fn with_position(mut self, x: f64, y: f64) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: f64) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Sets the distance between the baselines of two consecutive lines (the text leading).
+    pub fn with_line_height(mut self, line_height: f64) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Sets the fill color of the text.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn font_size(&self) -> f64 {
+        self.font_size
+    }
+
+    pub fn line_height(&self) -> f64 {
+        self.line_height
+    }
+
+    pub fn color(&self) -> &Color {
+        &self.color
+    }
+
+    /// Returns the position of the first line's baseline in PDF's default user space (origin
+    /// bottom-left) for a page of the given `page_height`.
+    pub fn baseline(&self, page_height: f64) -> (f64, f64) {
+        match self.origin {
+            Origin::TopLeft => (self.x, page_height - self.y - self.line_height),
+            Origin::BottomLeft => (self.x, self.y),
+        }
+    }
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions::new()
+    }
+}
+
+/// Writes the given `text` as a text object. Each line of the text (separated by `\n`) is written
+/// onto its own line, one line height below the previous one.
+pub async fn write_text(
     text: &str,
     font: &dyn Font,
-    wr: &mut Stream<W>,
+    options: &TextOptions,
+    page_height: f64,
+    wr: &mut dyn ContentStream,
 ) -> Result<HashSet<SubsetRef>, io::Error> {
+    let (x, y) = options.baseline(page_height);
+
     wr.begin_text().await?;
-    wr.set_text_matrix(1.0, 0.0, 0.0, 1.0, 10.0, 821.721)
-        .await?;
-    wr.set_text_leading(10.175).await?;
-    wr.set_fill_color(0.0, 0.0, 0.0).await?;
-    let subset_refs = wr.show_text_string(text, font, 11.0).await?;
+    wr.set_text_matrix(1.0, 0.0, 0.0, 1.0, x, y).await?;
+    wr.set_text_leading(options.line_height).await?;
+    wr.set_fill_color(&options.color).await?;
+
+    let mut subset_refs = HashSet::new();
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            wr.next_line().await?;
+        }
+        if !line.is_empty() {
+            subset_refs.extend(wr.show_text_string(line, font, options.font_size).await?);
+        }
+    }
+
     wr.end_text().await?;
 
     Ok(subset_refs)
 }
+
+/// Returns the area covered by the given `text` (as written by [`write_text`]) in PDF's default
+/// user space. It spans from the font's ascent above the first baseline to its descent below the
+/// last one, and is as wide as the longest line.
+pub fn text_extent(text: &str, font: &dyn Font, options: &TextOptions, page_height: f64) -> Rect {
+    let (x, y) = options.baseline(page_height);
+    let size = options.font_size;
+    let line_count = text.lines().count().max(1);
+    let width = text
+        .lines()
+        .map(|line| font.text_width(line, size))
+        .fold(0.0, f64::max);
+    let top = y + f64::from(font.ascent()) * size / 1000.0;
+    let bottom = y - (line_count - 1) as f64 * options.line_height
+        + f64::from(font.descent()) * size / 1000.0;
+    (x, bottom, x + width, top)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fonts::HELVETICA;
+    use crate::idseq::IdSeq;
+    use crate::writer::DocWriter;
+    use async_std::io::prelude::WriteExt;
+
+    #[test]
+    fn test_baseline_top_left() {
+        let options = TextOptions::new()
+            .with_position(50.0, 100.0)
+            .with_line_height(12.0);
+        assert_eq!(options.baseline(800.0), (50.0, 688.0));
+    }
+
+    #[test]
+    fn test_baseline_bottom_left() {
+        let options = TextOptions::new()
+            .with_position(50.0, 100.0)
+            .with_origin(Origin::BottomLeft);
+        assert_eq!(options.baseline(800.0), (50.0, 100.0));
+    }
+
+    #[test]
+    fn test_text_extent() {
+        let options = TextOptions::new()
+            .with_position(50.0, 100.0)
+            .with_font_size(10.0)
+            .with_line_height(12.0);
+        let (x1, y1, x2, y2) = text_extent("Hi\nWorld", &&*HELVETICA, &options, 800.0);
+        // Helvetica's ascender is 718, its descender -207
+        assert_eq!(
+            (x1, x2),
+            (50.0, 50.0 + Font::text_width(&&*HELVETICA, "World", 10.0))
+        );
+        assert!((y2 - (688.0 + 7.18)).abs() < 1e-9);
+        assert!((y1 - (676.0 - 2.07)).abs() < 1e-9);
+    }
+
+    #[async_std::test]
+    async fn test_write_multiline_text() {
+        let mut buf = Vec::new();
+        let mut stream = DocWriter::new(&mut buf, IdSeq::new(1))
+            .start_stream(false)
+            .await
+            .unwrap();
+
+        let options = TextOptions::new()
+            .with_position(20.0, 30.0)
+            .with_origin(Origin::BottomLeft)
+            .with_font_size(12.0)
+            .with_line_height(14.0)
+            .with_color(Color::Rgb(1.0, 0.0, 0.0));
+        write_text("foo\n\nbar", &&*HELVETICA, &options, 800.0, &mut stream)
+            .await
+            .unwrap();
+        stream.flush().await.unwrap();
+
+        let result = String::from_utf8_lossy(&buf);
+        assert_eq!(
+            &result[result.find("BT").unwrap()..],
+            "BT\n1.000 0.000 0.000 1.000 20.000 30.000 Tm\n14.000 TL\n1.000 0.000 0.000 rg\n\
+             /F0 12.000 Tf\n[(f) 30 (oo)] TJ\nT*\nT*\n/F0 12.000 Tf\n[(bar)] TJ\nET\n"
+        );
+    }
+}
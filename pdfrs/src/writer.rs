@@ -1,18 +1,24 @@
 use std::collections::HashMap;
-use std::io;
+use std::io::{self, Write as _};
 
+use crate::encryption::{SecurityHandler, StreamCipher};
 use crate::idseq::IdSeq;
-use crate::stream::{to_async_writer, Stream};
+use crate::stream::{to_async_writer, Filter, Stream};
 use async_std::io::prelude::WriteExt;
 use async_std::io::BufWriter;
 use async_std::task::Context;
 use async_std::task::Poll;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use futures_io::AsyncWrite;
 use pin_project::pin_project;
 use serde::Serialize;
-use serde_pdf::{Object, ObjectId, Reference};
+use serde_pdf::{Name, Object, ObjectId, Reference};
 use std::pin::Pin;
 
+/// The maximum number of objects packed into a single object stream.
+const OBJECT_STREAM_CAPACITY: usize = 100;
+
 /// A type that keeps track of a PDF XREF table while forwarding writes to its wrapped writer.
 ///
 /// It keeps track of how many bytes have already been written to correctly reference objects
@@ -24,6 +30,22 @@ pub struct DocWriter<W> {
     len: usize,
     id_seq: IdSeq,
     xref: HashMap<usize, usize>, // <object id, offset>
+    /// The XREF entries of objects inside of object streams (compact mode only).
+    compressed_xref: HashMap<usize, (usize, usize)>, // <object id, (object stream id, index)>
+    /// Whether non-stream objects are packed into object streams (which also requires an XREF
+    /// stream instead of a XREF table).
+    compact: bool,
+    /// The object stream objects are currently collected in (compact mode only).
+    object_stream: Option<ObjectStream>,
+    /// The security handler used to encrypt all strings and streams (if the document is
+    /// encrypted).
+    security_handler: Option<SecurityHandler>,
+    /// The cipher of the stream that is currently written (see
+    /// [`DocWriter::begin_stream_encryption`]).
+    stream_cipher: Option<StreamCipher>,
+    /// Encrypted stream data that has not been written to the wrapped writer yet (it is already
+    /// accounted for in `len`).
+    pending: Vec<u8>,
 }
 
 impl<W: AsyncWrite + Unpin> DocWriter<W> {
@@ -33,10 +55,40 @@ impl<W: AsyncWrite + Unpin> DocWriter<W> {
             w: BufWriter::new(w),
             len: 0,
             xref: HashMap::new(),
+            compressed_xref: HashMap::new(),
+            compact: false,
+            object_stream: None,
             id_seq,
+            security_handler: None,
+            stream_cipher: None,
+            pending: Vec::new(),
         }
     }
 
+    /// Encrypts all subsequently written strings and streams with the given `security_handler`.
+    pub(crate) fn set_security_handler(&mut self, security_handler: SecurityHandler) {
+        self.security_handler = Some(security_handler);
+    }
+
+    pub(crate) fn security_handler(&self) -> Option<&SecurityHandler> {
+        self.security_handler.as_ref()
+    }
+
+    /// Stops encrypting subsequently written strings and streams.
+    pub(crate) fn take_security_handler(&mut self) -> Option<SecurityHandler> {
+        self.security_handler.take()
+    }
+
+    /// Packs all subsequently written non-stream objects into object streams (see
+    /// [`DocWriter::write_xref_stream`]).
+    pub(crate) fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    pub(crate) fn is_compact(&self) -> bool {
+        self.compact
+    }
+
     /// The length in bytes of the already written PDF output.
     pub fn len(&self) -> usize {
         self.len
@@ -79,13 +131,172 @@ impl<W: AsyncWrite + Unpin> DocWriter<W> {
         Ok(r)
     }
 
-    /// Writes the provided `object` to the PDF output.
+    /// Writes the provided `object` to the PDF output. In compact mode, the object is added to the
+    /// current object stream instead.
     pub async fn write_object<D: Serialize>(
         &mut self,
         object: Object<D>,
     ) -> Result<(), serde_pdf::Error> {
+        if self.compact && object.rev() == 0 {
+            // the strings of objects inside of object streams are not encrypted individually,
+            // as the whole object stream is encrypted
+            let content = serde_pdf::to_string(object.content())?;
+            let id_seq = &mut self.id_seq;
+            let object_stream = self
+                .object_stream
+                .get_or_insert_with(|| ObjectStream::new(ObjectId::new(id_seq.next(), 0)));
+            self.compressed_xref.insert(
+                object.id(),
+                (object_stream.id.id(), object_stream.offsets.len()),
+            );
+            object_stream.push(object.id(), &content);
+            if object_stream.offsets.len() >= OBJECT_STREAM_CAPACITY {
+                self.end_object_stream().await?;
+            }
+            return Ok(());
+        }
+
         self.add_xref(object.id());
-        to_async_writer(self, &object).await
+        let id = ObjectId::new(object.id(), object.rev());
+        self.write_encrypted(&id, &object).await
+    }
+
+    /// Writes the provided `value`, which is part of the object with the given `id`, to the PDF
+    /// output. If the document is encrypted, its strings are encrypted with the object's key.
+    pub(crate) async fn write_encrypted<D: Serialize>(
+        &mut self,
+        id: &ObjectId,
+        value: &D,
+    ) -> Result<(), serde_pdf::Error> {
+        match &self.security_handler {
+            Some(security_handler) => {
+                let key = security_handler.object_key(id);
+                let s = serde_pdf::to_string_encrypted(value, move |data| key.encrypt(data))?;
+                self.write_all(s.as_bytes()).await?;
+                Ok(())
+            }
+            None => to_async_writer(self, value).await,
+        }
+    }
+
+    /// Encrypts all subsequent writes with the key of the stream object with the given `id` (until
+    /// [`DocWriter::end_stream_encryption`] is called). Does nothing for unencrypted documents.
+    pub(crate) fn begin_stream_encryption(&mut self, id: &ObjectId) {
+        self.stream_cipher = self
+            .security_handler
+            .as_ref()
+            .map(|security_handler| security_handler.object_key(id).stream_cipher());
+    }
+
+    /// Writes the remaining encrypted data of the current stream.
+    pub(crate) async fn end_stream_encryption(&mut self) -> Result<(), io::Error> {
+        if let Some(cipher) = self.stream_cipher.take() {
+            self.write_all(&cipher.finish()).await?;
+        }
+        self.flush().await
+    }
+
+    /// Writes the current object stream (if there is any) to the PDF output.
+    pub(crate) async fn end_object_stream(&mut self) -> Result<(), serde_pdf::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        #[serde(rename = "ObjStm")]
+        struct ObjectStreamDict {
+            n: usize,
+            first: usize,
+            length: usize,
+            filter: Vec<Filter>,
+        }
+
+        let object_stream = match self.object_stream.take() {
+            Some(object_stream) => object_stream,
+            None => return Ok(()),
+        };
+        let (first, data) = object_stream.to_data();
+        let mut data = deflate(&data)?;
+        if let Some(security_handler) = &self.security_handler {
+            data = security_handler
+                .object_key(&object_stream.id)
+                .encrypt(&data);
+        }
+        let dict = ObjectStreamDict {
+            n: object_stream.offsets.len(),
+            first,
+            length: data.len(),
+            filter: vec![Filter::FlateDecode(None)],
+        };
+        self.add_xref(object_stream.id.id());
+        self.write_stream_object(&object_stream.id, &dict, &data)
+            .await
+    }
+
+    /// Writes the XREF stream (PDF spec 1.7 page 106), which replaces both the XREF table and the
+    /// trailer in compact mode. The `trailer` contains the entries usually found in the trailer
+    /// dictionary; they must not contain any strings that need to be encrypted.
+    pub(crate) async fn write_xref_stream<T: Serialize>(
+        &mut self,
+        id: ObjectId,
+        trailer: &T,
+    ) -> Result<(), serde_pdf::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "PascalCase")]
+        #[serde(rename = "")]
+        struct XrefStreamDict<'a, T> {
+            #[serde(rename = "Type")]
+            ty: Name<'static>,
+            #[serde(flatten)]
+            trailer: &'a T,
+            #[serde(rename = "W")]
+            w: [u8; 3],
+            length: usize,
+            filter: Vec<Filter>,
+        }
+
+        // the XREF stream contains an entry for itself
+        self.add_xref(id.id());
+        let size = self.id_seq.count() + 1;
+        let mut data = Vec::with_capacity(size * 7);
+        for i in 0..size {
+            if i == 0 {
+                data.extend_from_slice(&[0, 0, 0, 0, 0, 0xFF, 0xFF]);
+            } else if let Some(offset) = self.xref.remove(&i) {
+                data.push(1);
+                data.extend_from_slice(&(offset as u32).to_be_bytes());
+                data.extend_from_slice(&[0, 0]);
+            } else if let Some((stream_id, index)) = self.compressed_xref.remove(&i) {
+                data.push(2);
+                data.extend_from_slice(&(stream_id as u32).to_be_bytes());
+                data.extend_from_slice(&(index as u16).to_be_bytes());
+            } else {
+                // ids that have been reserved but never used
+                data.extend_from_slice(&[0; 7]);
+            }
+        }
+
+        let data = deflate(&data)?;
+        let dict = XrefStreamDict {
+            ty: Name("XRef"),
+            trailer,
+            w: [1, 4, 2],
+            length: data.len(),
+            filter: vec![Filter::FlateDecode(None)],
+        };
+        self.write_stream_object(&id, &dict, &data).await
+    }
+
+    /// Writes a stream object whose (already encoded) `data` is known upfront.
+    async fn write_stream_object<D: Serialize>(
+        &mut self,
+        id: &ObjectId,
+        dict: &D,
+        data: &[u8],
+    ) -> Result<(), serde_pdf::Error> {
+        writeln!(self, "{} {} obj", id.id(), id.rev()).await?;
+        to_async_writer(&mut *self, dict).await?;
+        writeln!(self, "\nstream").await?;
+        self.write_all(data).await?;
+        writeln!(self, "\nendstream\nendobj\n").await?;
+        Ok(())
     }
 
     /// Writes the XREF table into into the wrapped writer of the `DocWriter<W>`.
@@ -137,7 +348,20 @@ impl<W: AsyncWrite + Unpin> AsyncWrite for DocWriter<W> {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        let project = self.project();
+        let mut project = self.project();
+        if let Poll::Ready(result) = poll_write_pending(project.w.as_mut(), cx, project.pending) {
+            result?;
+        } else {
+            return Poll::Pending;
+        }
+
+        if let Some(cipher) = project.stream_cipher {
+            // the encrypted data is written with the next write (or flush)
+            *project.pending = cipher.update(buf);
+            *project.len += project.pending.len();
+            return Poll::Ready(Ok(buf.len()));
+        }
+
         match project.w.poll_write(cx, buf) {
             Poll::Ready(result) => {
                 let len = result?;
@@ -149,12 +373,88 @@ impl<W: AsyncWrite + Unpin> AsyncWrite for DocWriter<W> {
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        self.project().w.poll_flush(cx)
+        let mut project = self.project();
+        if let Poll::Ready(result) = poll_write_pending(project.w.as_mut(), cx, project.pending) {
+            result?;
+        } else {
+            return Poll::Pending;
+        }
+        project.w.poll_flush(cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        self.project().w.poll_close(cx)
+        let mut project = self.project();
+        if let Poll::Ready(result) = poll_write_pending(project.w.as_mut(), cx, project.pending) {
+            result?;
+        } else {
+            return Poll::Pending;
+        }
+        project.w.poll_close(cx)
+    }
+}
+
+/// Non-stream objects that are packed into a single object stream (PDF spec 1.7 page 100).
+struct ObjectStream {
+    id: ObjectId,
+    /// The ids of the contained objects and their offsets in `objects`.
+    offsets: Vec<(usize, usize)>,
+    objects: Vec<u8>,
+}
+
+impl ObjectStream {
+    fn new(id: ObjectId) -> Self {
+        ObjectStream {
+            id,
+            offsets: Vec::new(),
+            objects: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, id: usize, content: &str) {
+        self.offsets.push((id, self.objects.len()));
+        self.objects.extend_from_slice(content.as_bytes());
+        self.objects.push(b'\n');
+    }
+
+    /// Returns the offset of the first object (`/First`) and the decoded stream data.
+    fn to_data(&self) -> (usize, Vec<u8>) {
+        let mut data = self
+            .offsets
+            .iter()
+            .map(|(id, offset)| format!("{} {}", id, offset))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .into_bytes();
+        data.push(b'\n');
+        let first = data.len();
+        data.extend_from_slice(&self.objects);
+        (first, data)
+    }
+}
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Writes the `pending` encrypted data to `w`.
+fn poll_write_pending<W: AsyncWrite>(
+    mut w: Pin<&mut BufWriter<W>>,
+    cx: &mut Context<'_>,
+    pending: &mut Vec<u8>,
+) -> Poll<io::Result<()>> {
+    while !pending.is_empty() {
+        match w.as_mut().poll_write(cx, pending) {
+            Poll::Ready(Ok(0)) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+            Poll::Ready(Ok(len)) => {
+                pending.drain(..len);
+            }
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            Poll::Pending => return Poll::Pending,
+        }
     }
+    Poll::Ready(Ok(()))
 }
 
 #[cfg(test)]
@@ -180,6 +480,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn object_stream_data() {
+        let mut object_stream = ObjectStream::new(ObjectId::new(5, 0));
+        object_stream.push(3, "42");
+        object_stream.push(4, "<< /Count 1 >>");
+        let (first, data) = object_stream.to_data();
+        assert_eq!(first, 8);
+        assert_eq!(
+            String::from_utf8_lossy(&data),
+            "3 0 4 3\n42\n<< /Count 1 >>\n"
+        );
+    }
+
     #[async_std::test]
     async fn xref_2() {
         let mut b = Vec::new();
@@ -2,7 +2,13 @@ use std::fs::File;
 use std::ops::Deref;
 
 use pdfrs::fonts::{FontCollection, OpenTypeFont, HELVETICA};
-use pdfrs::Document;
+use pdfrs::{
+    Align, Artifact, Border, BorderStyle, ButtonAction, Color, Conformance, Destination,
+    DeviceColorSpace, Document, EmbeddedFile, Encryption, EncryptionAlgorithm, Error, Field,
+    FileRelationship, FillRule, Language, LineCap, LineJoin, Link, Margins, Orientation, Origin,
+    OutlineItem, PageLayout, PageSize, Paint, Paragraph, Path, Role, Rotation, Style, Tag,
+    TextOptions, Transform, Violation,
+};
 use pdfrs_macros::test as pdf_test;
 
 fn afm_helvetica() -> impl FontCollection {
@@ -24,6 +30,791 @@ async fn basic_compressed(doc: &mut Document<_, File>) {
     doc.text("Hello World", None).await.unwrap();
 }
 
+#[pdf_test(
+    "./fixtures/compact.pdf",
+    afm_helvetica,
+    compressed = true,
+    compact = true
+)]
+async fn compact(doc: &mut Document<_, File>) {
+    doc.text("Page 1", None).await.unwrap();
+    doc.add_outline_item(None, OutlineItem::new("Page 1", Destination::Page(0)))
+        .unwrap();
+    doc.new_page().await.unwrap();
+    doc.text("Page 2", None).await.unwrap();
+    doc.add_outline_item(None, OutlineItem::new("Page 2", Destination::Page(1)))
+        .unwrap();
+}
+
+#[pdf_test("./fixtures/multiple_pages.pdf", afm_helvetica)]
+async fn multiple_pages(doc: &mut Document<_, File>) {
+    doc.text("Page 1", None).await.unwrap();
+    doc.new_page().await.unwrap();
+    doc.text("Page 2", None).await.unwrap();
+    doc.new_page().await.unwrap();
+    doc.text("Page 3", None).await.unwrap();
+}
+
+#[pdf_test("./fixtures/page_layouts.pdf", afm_helvetica)]
+async fn page_layouts(doc: &mut Document<_, File>) {
+    doc.text("A4", None).await.unwrap();
+    doc.new_page_with_layout(
+        PageLayout::new(PageSize::Letter).with_orientation(Orientation::Landscape),
+    )
+    .await
+    .unwrap();
+    doc.text("Letter landscape", None).await.unwrap();
+    doc.new_page_with_layout(
+        PageLayout::new(PageSize::Custom {
+            width: 288.0,
+            height: 432.0,
+        })
+        .with_bleed_box((0.0, 0.0, 288.0, 432.0))
+        .with_trim_box((9.0, 9.0, 279.0, 423.0))
+        .with_rotation(Rotation::Clockwise90),
+    )
+    .await
+    .unwrap();
+    doc.text("Shipping label", None).await.unwrap();
+}
+
+#[pdf_test("./fixtures/first_page_layout.pdf", afm_helvetica)]
+async fn first_page_layout(doc: &mut Document<_, File>) {
+    // choosing the layout of the first page does not leave an empty page behind
+    doc.new_page_with_layout(PageSize::Letter).await.unwrap();
+    assert_eq!(doc.page_index(), 0);
+    doc.text("Letter", None).await.unwrap();
+}
+
+#[pdf_test("./fixtures/positioned_text.pdf", afm_helvetica)]
+async fn positioned_text(doc: &mut Document<_, File>) {
+    doc.text_with(
+        "Invoice\nNo. 42",
+        None,
+        &TextOptions::new()
+            .with_position(72.0, 72.0)
+            .with_font_size(18.0)
+            .with_line_height(22.0)
+            .with_color(Color::Rgb(0.2, 0.2, 0.6)),
+    )
+    .await
+    .unwrap();
+    doc.text_with(
+        "Page 1",
+        None,
+        &TextOptions::new()
+            .with_position(500.0, 30.0)
+            .with_origin(Origin::BottomLeft)
+            .with_font_size(9.0),
+    )
+    .await
+    .unwrap();
+}
+
+#[pdf_test("./fixtures/paragraph.pdf", afm_helvetica)]
+async fn paragraph(doc: &mut Document<_, File>) {
+    let regular = Style::new(Default::default());
+    let highlighted = Style {
+        font_size: 14.0,
+        line_height: 16.0,
+        color: Color::Rgb(0.8, 0.0, 0.0),
+        ..Style::new(Default::default())
+    };
+
+    let mut p = Paragraph::new();
+    p.push(
+        "Lorem ipsum dolor sit amet, consetetur sadipscing elitr, sed diam ",
+        &regular,
+    );
+    p.push("nonumy eirmod tempor", &highlighted);
+    p.push(
+        " invidunt ut labore et dolore magna aliquyam erat.\nSed diam voluptua.",
+        &regular,
+    );
+
+    let height = doc.paragraph(&p, 72.0, 72.0, 200.0).await.unwrap();
+    // two lines contain the larger text
+    assert!((height - (2.0 * 16.0 + 3.0 * 10.175)).abs() < 1e-9);
+}
+
+#[pdf_test("./fixtures/paragraph_page_flow.pdf", afm_helvetica)]
+async fn paragraph_page_flow(doc: &mut Document<_, File>) {
+    let style = Style::new(Default::default());
+    let mut p = Paragraph::new();
+    p.push(
+        "Lorem ipsum dolor sit amet, consetetur sadipscing elitr, sed diam nonumy eirmod tempor \
+         invidunt ut labore et dolore magna aliquyam erat, sed diam voluptua. At vero eos et \
+         accusam et justo duo dolores et ea rebum. Stet clita kasd gubergren, no sea takimata \
+         sanctus est Lorem ipsum dolor sit amet.",
+        &style,
+    );
+
+    // start close to the bottom margin so that the paragraph continues on a second page
+    doc.set_cursor(750.0);
+    doc.flow_paragraph(&p).await.unwrap();
+    doc.flow_paragraph(&p).await.unwrap();
+    assert!((doc.cursor() - (72.0 + 7.0 * 10.175)).abs() < 1e-9);
+}
+
+#[pdf_test("./fixtures/paragraph_page_flow_layouts.pdf", afm_helvetica)]
+async fn paragraph_page_flow_layouts(doc: &mut Document<_, File>) {
+    let style = Style::new(Default::default());
+    let mut p = Paragraph::new().with_align(Align::Justify);
+    p.push(LOREM_IPSUM, &style);
+
+    // the lines continued on the second page use its (default) wider content area
+    doc.new_page_with_layout(
+        PageLayout::new(PageSize::A5).with_margins(Margins::new(36.0, 144.0, 36.0, 144.0)),
+    )
+    .await
+    .unwrap();
+    doc.set_cursor(530.0);
+    doc.flow_paragraph(&p).await.unwrap();
+    assert_eq!(doc.page_index(), 1);
+
+    // a line taller than the page is placed at the top of the empty page it starts on
+    let tall = Style {
+        line_height: 1000.0,
+        ..Style::new(Default::default())
+    };
+    let mut p = Paragraph::new();
+    p.push("Tall", &tall);
+    doc.new_page().await.unwrap();
+    doc.flow_paragraph(&p).await.unwrap();
+    assert_eq!(doc.page_index(), 2);
+}
+
+const LOREM_IPSUM: &str = "Lorem ipsum dolor sit amet, consetetur sadipscing elitr, sed diam \
+                           nonumy eirmod tempor invidunt ut labore et dolore magna aliquyam \
+                           erat, sed diam voluptua.";
+
+#[pdf_test("./fixtures/paragraph_alignment.pdf", afm_helvetica)]
+async fn paragraph_alignment(doc: &mut Document<_, File>) {
+    let style = Style::new(Default::default());
+    for align in [Align::Left, Align::Right, Align::Center, Align::Justify] {
+        let mut p = Paragraph::new().with_align(align);
+        p.push(LOREM_IPSUM, &style);
+        doc.flow_paragraph(&p).await.unwrap();
+        doc.set_cursor(doc.cursor() + 20.0);
+    }
+}
+
+#[pdf_test("./fixtures/paragraph_hyphenation.pdf", afm_helvetica)]
+async fn paragraph_hyphenation(doc: &mut Document<_, File>) {
+    let style = Style::new(Default::default());
+
+    let mut p = Paragraph::new()
+        .with_align(Align::Justify)
+        .with_hyphenation(Language::German);
+    p.push(
+        "Die Donaudampfschifffahrtsgesellschaft bietet Rundfahrten mit \
+         Ausflugsschiffen an. Fahrkarten erhalten Sie an der Schiffs\u{AD}anlegestelle.",
+        &style,
+    );
+    doc.paragraph(&p, 72.0, 72.0, 120.0).await.unwrap();
+
+    let mut p = Paragraph::new()
+        .with_align(Align::Justify)
+        .with_hyphenation(Language::EnglishUS);
+    p.push(
+        "Hyphenation considerably improves the typographical appearance of justified \
+         paragraphs in narrow columns.",
+        &style,
+    );
+    doc.paragraph(&p, 300.0, 72.0, 120.0).await.unwrap();
+}
+
+#[pdf_test("./fixtures/vector_graphics.pdf", afm_helvetica)]
+async fn vector_graphics(doc: &mut Document<_, File>) {
+    // separator line
+    let mut line = Path::new();
+    line.move_to(72.0, 72.0).line_to(523.0, 72.0);
+    doc.draw(&line, Paint::Stroke).await.unwrap();
+
+    // bar chart
+    doc.set_fill_color(&Color::Rgb(0.2, 0.4, 0.8))
+        .await
+        .unwrap();
+    for (i, value) in [40.0, 80.0, 60.0, 120.0].iter().enumerate() {
+        let x = 72.0 + i as f64 * 40.0;
+        doc.draw(
+            &Path::rect(x, 220.0 - value, 30.0, *value),
+            Paint::Fill(FillRule::NonZero),
+        )
+        .await
+        .unwrap();
+    }
+
+    // shapes
+    doc.set_stroke_color(&Color::Rgb(0.8, 0.0, 0.0))
+        .await
+        .unwrap();
+    doc.set_fill_color(&Color::Rgb(1.0, 0.9, 0.9))
+        .await
+        .unwrap();
+    doc.draw(
+        &Path::circle(320.0, 160.0, 40.0),
+        Paint::FillStroke(FillRule::NonZero),
+    )
+    .await
+    .unwrap();
+    doc.draw(&Path::ellipse(440.0, 160.0, 60.0, 30.0), Paint::Stroke)
+        .await
+        .unwrap();
+
+    // ring using the even-odd rule
+    let mut ring = Path::circle(120.0, 320.0, 50.0);
+    ring.add_ellipse(120.0, 320.0, 25.0, 25.0);
+    doc.draw(&ring, Paint::Fill(FillRule::EvenOdd))
+        .await
+        .unwrap();
+
+    // text clipped to a rounded rectangle
+    doc.clip(
+        &Path::rounded_rect(250.0, 280.0, 200.0, 80.0, 15.0),
+        FillRule::NonZero,
+    )
+    .await
+    .unwrap();
+    doc.draw(
+        &Path::rect(200.0, 250.0, 300.0, 150.0),
+        Paint::Fill(FillRule::NonZero),
+    )
+    .await
+    .unwrap();
+}
+
+#[pdf_test("./fixtures/graphics_state.pdf", afm_helvetica)]
+async fn graphics_state(doc: &mut Document<_, File>) {
+    let mut zigzag = Path::new();
+    zigzag
+        .move_to(72.0, 120.0)
+        .line_to(112.0, 80.0)
+        .line_to(152.0, 120.0)
+        .line_to(192.0, 80.0);
+
+    doc.save_state().await.unwrap();
+    doc.set_line_width(8.0).await.unwrap();
+    doc.set_line_cap(LineCap::Round).await.unwrap();
+    doc.set_line_join(LineJoin::Bevel).await.unwrap();
+    doc.draw(&zigzag, Paint::Stroke).await.unwrap();
+
+    doc.set_line_cap(LineCap::Butt).await.unwrap();
+    doc.set_line_join(LineJoin::Miter).await.unwrap();
+    doc.set_miter_limit(2.0).await.unwrap();
+    doc.transform(Transform::translate(160.0, 0.0))
+        .await
+        .unwrap();
+    doc.draw(&zigzag, Paint::Stroke).await.unwrap();
+    doc.restore_state().await.unwrap();
+
+    // dashed separator
+    doc.save_state().await.unwrap();
+    doc.set_dash_pattern(&[6.0, 3.0], 0.0).await.unwrap();
+    let mut line = Path::new();
+    line.move_to(72.0, 160.0).line_to(523.0, 160.0);
+    doc.draw(&line, Paint::Stroke).await.unwrap();
+    doc.restore_state().await.unwrap();
+
+    // a rotated square around its center
+    doc.save_state().await.unwrap();
+    doc.transform(
+        Transform::translate(-122.0, -250.0)
+            .then(Transform::rotate(30.0))
+            .then(Transform::scale(1.5, 1.5))
+            .then(Transform::translate(122.0, 250.0)),
+    )
+    .await
+    .unwrap();
+    doc.draw(&Path::rect(97.0, 225.0, 50.0, 50.0), Paint::Stroke)
+        .await
+        .unwrap();
+    doc.restore_state().await.unwrap();
+
+    // overlapping semi-transparent circles (the second opacity reuses the first graphics state)
+    doc.save_state().await.unwrap();
+    doc.set_fill_opacity(0.5).await.unwrap();
+    doc.set_stroke_opacity(0.25).await.unwrap();
+    doc.set_fill_color(&Color::Rgb(1.0, 0.0, 0.0))
+        .await
+        .unwrap();
+    doc.draw(
+        &Path::circle(300.0, 250.0, 40.0),
+        Paint::FillStroke(FillRule::NonZero),
+    )
+    .await
+    .unwrap();
+    doc.set_fill_color(&Color::Rgb(0.0, 0.0, 1.0))
+        .await
+        .unwrap();
+    doc.set_fill_opacity(0.5).await.unwrap();
+    doc.draw(
+        &Path::circle(340.0, 250.0, 40.0),
+        Paint::Fill(FillRule::NonZero),
+    )
+    .await
+    .unwrap();
+    doc.restore_state().await.unwrap();
+}
+
+#[pdf_test("./fixtures/colors.pdf", afm_helvetica)]
+async fn colors(doc: &mut Document<_, File>) {
+    let srgb = doc
+        .add_icc_profile(
+            include_bytes!("../icc/sRGB.icc").to_vec(),
+            DeviceColorSpace::Rgb,
+        )
+        .unwrap();
+    let pantone = doc
+        .add_spot_color("PANTONE 300 C", Color::Cmyk(1.0, 0.44, 0.0, 0.0))
+        .unwrap();
+
+    let swatches = [
+        Color::Gray(0.5),
+        Color::Rgb(0.8, 0.2, 0.2),
+        Color::Cmyk(0.0, 0.2, 1.0, 0.0),
+        Color::IccBased(srgb.clone(), vec![0.2, 0.6, 0.2]),
+        Color::Spot(pantone.clone(), 1.0),
+        Color::Spot(pantone.clone(), 0.4),
+    ];
+    for (i, color) in swatches.iter().enumerate() {
+        doc.set_fill_color(color).await.unwrap();
+        doc.set_stroke_color(&Color::Gray(0.0)).await.unwrap();
+        doc.draw(
+            &Path::rect(72.0 + i as f64 * 60.0, 72.0, 50.0, 50.0),
+            Paint::FillStroke(FillRule::NonZero),
+        )
+        .await
+        .unwrap();
+    }
+
+    // the number of components must match the color space
+    assert!(matches!(
+        doc.set_fill_color(&Color::IccBased(srgb, vec![0.2])).await,
+        Err(Error::InvalidColorComponents {
+            expected: 3,
+            actual: 1
+        })
+    ));
+
+    doc.text_with(
+        "Spot color text",
+        None,
+        &TextOptions::new()
+            .with_position(72.0, 150.0)
+            .with_color(Color::Spot(pantone, 1.0)),
+    )
+    .await
+    .unwrap();
+}
+
+#[pdf_test("./fixtures/jpeg_images.pdf", afm_helvetica)]
+async fn jpeg_images(doc: &mut Document<_, File>) {
+    let images = [
+        doc.add_jpeg(include_bytes!("../../images/rgb.jpg").to_vec())
+            .unwrap(),
+        doc.add_jpeg(include_bytes!("../../images/rgb_progressive.jpg").to_vec())
+            .unwrap(),
+        doc.add_jpeg(include_bytes!("../../images/gray.jpg").to_vec())
+            .unwrap(),
+        doc.add_jpeg(include_bytes!("../../images/cmyk_adobe.jpg").to_vec())
+            .unwrap(),
+    ];
+    for (i, image) in images.iter().enumerate() {
+        let width = f64::from(image.width()) * 2.0;
+        let height = f64::from(image.height()) * 2.0;
+        doc.draw_image(image, 72.0 + i as f64 * 110.0, 72.0, width, height)
+            .await
+            .unwrap();
+    }
+
+    // the image is only written once, even though it is used on multiple pages
+    doc.new_page().await.unwrap();
+    doc.draw_image(&images[0], 72.0, 72.0, 192.0, 128.0)
+        .await
+        .unwrap();
+}
+
+#[pdf_test("./fixtures/png_images.pdf", afm_helvetica)]
+async fn png_images(doc: &mut Document<_, File>) {
+    // a checkerboard background to make the transparency visible
+    doc.set_fill_color(&Color::Gray(0.8)).await.unwrap();
+    for i in 0..12 {
+        doc.draw(
+            &Path::rect(72.0 + i as f64 * 40.0, 72.0, 20.0, 20.0),
+            Paint::Fill(FillRule::NonZero),
+        )
+        .await
+        .unwrap();
+    }
+
+    let images = [
+        doc.add_png(include_bytes!("../../images/rgb.png")).unwrap(),
+        doc.add_png(include_bytes!("../../images/gray4.png"))
+            .unwrap(),
+        doc.add_png(include_bytes!("../../images/rgb16_interlaced.png"))
+            .unwrap(),
+        doc.add_png(include_bytes!("../../images/rgba.png"))
+            .unwrap(),
+        doc.add_png(include_bytes!("../../images/gray_alpha.png"))
+            .unwrap(),
+        doc.add_png(include_bytes!("../../images/palette_alpha.png"))
+            .unwrap(),
+    ];
+    for (i, image) in images.iter().enumerate() {
+        doc.draw_image(image, 72.0 + i as f64 * 80.0, 72.0, 60.0, 45.0)
+            .await
+            .unwrap();
+    }
+}
+
+#[pdf_test("./fixtures/links.pdf", afm_helvetica)]
+async fn links(doc: &mut Document<_, File>) {
+    doc.text_link(
+        "Pay your invoice online",
+        None,
+        &TextOptions::new()
+            .with_position(72.0, 72.0)
+            .with_font_size(14.0)
+            .with_color(Color::Rgb(0.0, 0.0, 0.8)),
+        &Link::new("https://example.com/pay?invoice=42"),
+    )
+    .await
+    .unwrap();
+
+    doc.draw(
+        &Path::rect(72.0, 120.0, 150.0, 40.0),
+        Paint::Fill(FillRule::NonZero),
+    )
+    .await
+    .unwrap();
+    doc.add_link(
+        72.0,
+        120.0,
+        150.0,
+        40.0,
+        &Link::new("https://example.com/")
+            .with_border(Border::new(2.0).with_style(BorderStyle::Dashed(vec![4.0, 2.0]))),
+    )
+    .await
+    .unwrap();
+}
+
+#[pdf_test("./fixtures/outline.pdf", afm_helvetica)]
+async fn outline(doc: &mut Document<_, File>) {
+    let intro = doc
+        .add_outline_item(
+            None,
+            OutlineItem::new("Introduction", Destination::Page(doc.page_index()))
+                .with_open(true)
+                .with_bold(true),
+        )
+        .unwrap();
+    doc.text("Introduction", None).await.unwrap();
+    doc.text_with(
+        "Motivation",
+        None,
+        &TextOptions::new().with_position(10.0, 300.0),
+    )
+    .await
+    .unwrap();
+    doc.add_outline_item(
+        Some(intro),
+        OutlineItem::new(
+            "Motivation",
+            Destination::Position {
+                page: doc.page_index(),
+                x: 0.0,
+                y: 300.0,
+            },
+        ),
+    )
+    .unwrap();
+
+    // the destination's page does not exist yet
+    let results = doc
+        .add_outline_item(
+            None,
+            OutlineItem::new("Ergebnisse für Größen", Destination::Page(1))
+                .with_color(0.8, 0.0, 0.0)
+                .with_italic(true),
+        )
+        .unwrap();
+    doc.add_outline_item(
+        Some(results),
+        OutlineItem::new("Überblick", Destination::Page(1)),
+    )
+    .unwrap();
+
+    doc.new_page().await.unwrap();
+    doc.text("Ergebnisse für Größen", None).await.unwrap();
+}
+
+#[async_std::test]
+async fn outline_parent_of_other_document() {
+    let mut other = Document::builder(afm_helvetica())
+        .start(Vec::new())
+        .await
+        .unwrap();
+    let entry = other
+        .add_outline_item(None, OutlineItem::new("Other", Destination::Page(0)))
+        .unwrap();
+
+    let mut doc = Document::builder(afm_helvetica())
+        .start(Vec::new())
+        .await
+        .unwrap();
+    assert!(matches!(
+        doc.add_outline_item(
+            Some(entry),
+            OutlineItem::new("Nested", Destination::Page(0))
+        ),
+        Err(Error::InvalidOutlineParent)
+    ));
+}
+
+#[pdf_test("./fixtures/table_of_contents.pdf", afm_helvetica)]
+async fn table_of_contents(doc: &mut Document<_, File>) {
+    doc.text("Contents", None).await.unwrap();
+    // the linked pages do not exist yet
+    doc.text_link(
+        "1. Introduction",
+        None,
+        &TextOptions::new().with_position(72.0, 100.0),
+        &Link::destination(Destination::Page(1)),
+    )
+    .await
+    .unwrap();
+    doc.text_link(
+        "2. Results",
+        None,
+        &TextOptions::new().with_position(72.0, 120.0),
+        &Link::named("results"),
+    )
+    .await
+    .unwrap();
+
+    doc.new_page().await.unwrap();
+    doc.text("1. Introduction", None).await.unwrap();
+    doc.text_with(
+        "2. Results",
+        None,
+        &TextOptions::new().with_position(72.0, 400.0),
+    )
+    .await
+    .unwrap();
+    doc.add_named_destination(
+        "results",
+        Destination::Position {
+            page: doc.page_index(),
+            x: 0.0,
+            y: 400.0,
+        },
+    );
+}
+
+#[pdf_test(
+    "./fixtures/pdf_a3.pdf",
+    source_sans_pro_regular,
+    conformance = "PdfA3b"
+)]
+async fn pdf_a3(doc: &mut Document<_, File>) {
+    doc.text("Invoice 42", None).await.unwrap();
+    doc.embed_file(
+        EmbeddedFile::new(
+            "factur-x.xml",
+            b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Invoice/>\n".to_vec(),
+        )
+        .with_mime_type("text/xml")
+        .with_description("Factur-X invoice data")
+        .with_relationship(FileRelationship::Data),
+    )
+    .unwrap();
+    assert!(matches!(
+        doc.embed_file(EmbeddedFile::new("data.bin", Vec::new())),
+        Err(Error::NotConformant(Violation::EmbeddedFileMimeType(name))) if name == "data.bin"
+    ));
+
+    assert!(matches!(
+        doc.set_fill_color(&Color::Cmyk(0.0, 0.2, 1.0, 0.0)).await,
+        Err(Error::NotConformant(Violation::DeviceCmyk))
+    ));
+}
+
+#[pdf_test(
+    "./fixtures/pdf_a2_violations.pdf",
+    afm_helvetica,
+    conformance = "PdfA2b"
+)]
+async fn pdf_a2_violations(doc: &mut Document<_, File>) {
+    assert!(matches!(
+        doc.text("Not embedded", None).await,
+        Err(Error::NotConformant(Violation::FontNotEmbedded(name))) if name == "Helvetica"
+    ));
+    assert!(matches!(
+        doc.embed_file(EmbeddedFile::new("data.xml", Vec::new())),
+        Err(Error::NotConformant(Violation::EmbeddedFile))
+    ));
+}
+
+#[async_std::test]
+async fn pdf_a2_encryption() {
+    let result = Document::builder(afm_helvetica())
+        .with_conformance(Conformance::PdfA2b)
+        .with_encryption(Encryption::new(EncryptionAlgorithm::Aes256))
+        .start(Vec::new())
+        .await;
+    assert!(matches!(
+        result,
+        Err(Error::NotConformant(Violation::Encryption))
+    ));
+}
+
+fn rc4_encryption() -> Encryption {
+    Encryption::new(EncryptionAlgorithm::Rc4)
+        .with_user_password("user")
+        .with_owner_password("owner")
+        .with_copy(false)
+}
+
+#[pdf_test(
+    "./fixtures/encrypted_rc4.pdf",
+    afm_helvetica,
+    compressed = true,
+    encryption = "rc4_encryption"
+)]
+async fn encrypted_rc4(doc: &mut Document<_, File>) {
+    doc.text("Hello (encrypted) World", None).await.unwrap();
+    doc.add_link(
+        72.0,
+        700.0,
+        100.0,
+        20.0,
+        &Link::new("https://github.com/rkusa/pdfrs"),
+    )
+    .await
+    .unwrap();
+}
+
+fn aes_128_encryption() -> Encryption {
+    Encryption::new(EncryptionAlgorithm::Aes128)
+        .with_user_password("user")
+        .with_owner_password("owner")
+        .with_print(false)
+        .with_seed("test")
+}
+
+#[pdf_test(
+    "./fixtures/encrypted_aes_128.pdf",
+    afm_helvetica,
+    compressed = true,
+    encryption = "aes_128_encryption"
+)]
+async fn encrypted_aes_128(doc: &mut Document<_, File>) {
+    doc.text("Hello (encrypted) World", None).await.unwrap();
+    doc.add_link(
+        72.0,
+        700.0,
+        100.0,
+        20.0,
+        &Link::new("https://github.com/rkusa/pdfrs"),
+    )
+    .await
+    .unwrap();
+}
+
+fn aes_256_encryption() -> Encryption {
+    Encryption::new(EncryptionAlgorithm::Aes256)
+        .with_owner_password("owner")
+        .with_modify(false)
+        .with_seed("test")
+}
+
+#[pdf_test(
+    "./fixtures/encrypted_aes_256.pdf",
+    afm_helvetica,
+    compressed = true,
+    compact = true,
+    encryption = "aes_256_encryption"
+)]
+async fn encrypted_aes_256(doc: &mut Document<_, File>) {
+    doc.text("Hello (encrypted) Wörld", None).await.unwrap();
+    doc.add_link(
+        72.0,
+        700.0,
+        100.0,
+        20.0,
+        &Link::new("https://github.com/rkusa/pdfrs"),
+    )
+    .await
+    .unwrap();
+}
+
+#[pdf_test("./fixtures/tagged.pdf", afm_helvetica, lang = "en-US")]
+async fn tagged(doc: &mut Document<_, File>) {
+    doc.begin_artifact(Artifact::Footer);
+    doc.text_with(
+        "Page 1",
+        None,
+        &TextOptions::new().with_position(72.0, 780.0),
+    )
+    .await
+    .unwrap();
+    doc.end_artifact().unwrap();
+
+    doc.begin_tag(Tag::new(Role::Document));
+
+    doc.begin_tag(Tag::new(Role::H1));
+    doc.text_with(
+        "Annual Report",
+        None,
+        &TextOptions::new()
+            .with_position(72.0, 72.0)
+            .with_font_size(18.0),
+    )
+    .await
+    .unwrap();
+    doc.end_tag().unwrap();
+
+    doc.begin_tag(Tag::new(Role::P));
+    let style = Style::new(Default::default());
+    let mut p = Paragraph::new();
+    p.push("Revenue grew in all regions.", &style);
+    doc.set_cursor(100.0);
+    doc.flow_paragraph(&p).await.unwrap();
+    doc.end_tag().unwrap();
+
+    doc.begin_tag(Tag::new(Role::Figure).with_alt("A rising bar"));
+    let bar = Path::rect(72.0, 140.0, 40.0, 100.0);
+    doc.draw(&bar, Paint::Fill(FillRule::NonZero))
+        .await
+        .unwrap();
+    doc.end_tag().unwrap();
+
+    doc.begin_tag(Tag::new(Role::L));
+    for (i, item) in ["North", "South"].iter().enumerate() {
+        doc.begin_tag(Tag::new(Role::LI).with_actual_text(format!("{}. {}", i + 1, item)));
+        doc.text_with(
+            item,
+            None,
+            &TextOptions::new().with_position(72.0, 260.0 + 14.0 * i as f64),
+        )
+        .await
+        .unwrap();
+        doc.end_tag().unwrap();
+    }
+    doc.end_tag().unwrap();
+
+    doc.new_page().await.unwrap();
+    doc.begin_tag(Tag::new(Role::P).with_lang("de-DE"));
+    doc.text("Umsatz", None).await.unwrap();
+    doc.end_tag().unwrap();
+
+    doc.end_tag().unwrap();
+    assert!(matches!(doc.end_tag(), Err(Error::NoTag)));
+    assert!(matches!(doc.end_artifact(), Err(Error::NoArtifact)));
+}
+
 fn iosevka_regular() -> impl FontCollection {
     let data = include_bytes!("../../fonts/Iosevka/iosevka-regular.ttf");
     OpenTypeFont::from_slice(&data[..]).unwrap()
@@ -44,6 +835,129 @@ async fn basic_proportional_otf_text(doc: &mut Document<_, File>) {
     doc.text("Hello World — Привет мир", None).await.unwrap();
 }
 
+#[pdf_test("./fixtures/justified_otf_paragraph.pdf", source_sans_pro_regular)]
+async fn justified_otf_paragraph(doc: &mut Document<_, File>) {
+    let style = Style::new(Default::default());
+    let mut p = Paragraph::new().with_align(Align::Justify);
+    p.push(LOREM_IPSUM, &style);
+    doc.flow_paragraph(&p).await.unwrap();
+}
+
+#[pdf_test(
+    "./fixtures/justified_mixed_otf_paragraph.pdf",
+    source_sans_pro_regular
+)]
+async fn justified_mixed_otf_paragraph(doc: &mut Document<_, File>) {
+    let regular = Style::new(Default::default());
+    let red = Style {
+        color: Color::Rgb(1.0, 0.0, 0.0),
+        ..Style::new(Default::default())
+    };
+    // styled runs that end with a space are stretched as well
+    let mut p = Paragraph::new().with_align(Align::Justify);
+    p.push("Lorem ipsum ", &red);
+    p.push("dolor sit amet, consetetur ", &regular);
+    p.push("sadipscing elitr, ", &red);
+    p.push(LOREM_IPSUM, &regular);
+    doc.flow_paragraph(&p).await.unwrap();
+}
+
+#[pdf_test("./fixtures/acro_form.pdf", source_sans_pro_regular)]
+async fn acro_form(doc: &mut Document<_, File>) {
+    doc.add_field(
+        72.0,
+        72.0,
+        200.0,
+        20.0,
+        &Field::text("name")
+            .with_value("Jane Doe")
+            .with_max_length(40)
+            .with_required(true),
+        None,
+    )
+    .await
+    .unwrap();
+    doc.add_field(
+        72.0,
+        100.0,
+        200.0,
+        48.0,
+        &Field::text("address")
+            .with_multiline(true)
+            .with_value("Main Street 1\nSpringfield"),
+        None,
+    )
+    .await
+    .unwrap();
+    doc.add_field(
+        72.0,
+        156.0,
+        12.0,
+        12.0,
+        &Field::checkbox("newsletter")
+            .with_checked(true)
+            .with_tooltip("Subscribe to the newsletter"),
+        None,
+    )
+    .await
+    .unwrap();
+    for (i, plan) in ["basic", "premium"].iter().enumerate() {
+        doc.add_field(
+            72.0 + 20.0 * i as f64,
+            176.0,
+            12.0,
+            12.0,
+            &Field::radio("plan", *plan).with_checked(i == 1),
+            None,
+        )
+        .await
+        .unwrap();
+    }
+    doc.add_field(
+        72.0,
+        196.0,
+        120.0,
+        20.0,
+        &Field::combo_box("country", ["Austria", "Germany"]).with_value("Germany"),
+        None,
+    )
+    .await
+    .unwrap();
+    doc.add_field(
+        72.0,
+        224.0,
+        120.0,
+        40.0,
+        &Field::list_box("language", ["English", "German"]).with_value("German"),
+        None,
+    )
+    .await
+    .unwrap();
+    doc.add_field(
+        72.0,
+        272.0,
+        80.0,
+        24.0,
+        &Field::push_button("reset", "Reset").with_action(ButtonAction::ResetForm),
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        doc.add_field(72.0, 300.0, 10.0, 10.0, &Field::checkbox("name"), None).await,
+        Err(Error::DuplicateField(name)) if name == "name"
+    ));
+    assert!(matches!(
+        doc.add_field(72.0, 300.0, 10.0, 10.0, &Field::text("address.street"), None).await,
+        Err(Error::InvalidFieldName(name)) if name == "address.street"
+    ));
+    assert!(matches!(
+        doc.add_field(72.0, 300.0, 10.0, 10.0, &Field::radio("size", "Off"), None).await,
+        Err(Error::InvalidRadioValue(value)) if value == "Off"
+    ));
+}
+
 fn noto_sans_brahmi_regular() -> impl FontCollection {
     let data = include_bytes!("../../fonts/NotoSansBrahmi/NotoSansBrahmi-Regular.ttf");
     OpenTypeFont::from_slice(&data[..]).unwrap()
@@ -53,3 +967,61 @@ fn noto_sans_brahmi_regular() -> impl FontCollection {
 async fn cmap_non_bmp(doc: &mut Document<_, File>) {
     doc.text("𑀅𑀆𑀇𑀈𑀉𑀊𑀋𑀌𑀍𑀎𑀏", None).await.unwrap();
 }
+
+#[pdf_test("./fixtures/form_xobjects.pdf", source_sans_pro_regular)]
+async fn form_xobjects(doc: &mut Document<_, File>) {
+    // the letterhead uses glyphs that do not appear anywhere else in the document
+    doc.begin_form(451.0, 40.0).unwrap();
+    doc.text_with(
+        "ACME Corp.",
+        None,
+        &TextOptions::new()
+            .with_position(0.0, 0.0)
+            .with_font_size(18.0)
+            .with_line_height(24.0)
+            .with_color(Color::Rgb(0.1, 0.3, 0.6)),
+    )
+    .await
+    .unwrap();
+    doc.set_line_width(2.0).await.unwrap();
+    let mut rule = Path::new();
+    rule.move_to(0.0, 39.0).line_to(451.0, 39.0);
+    doc.draw(&rule, Paint::Stroke).await.unwrap();
+    let letterhead = doc.end_form().unwrap();
+
+    doc.begin_form(300.0, 60.0).unwrap();
+    doc.set_fill_opacity(0.2).await.unwrap();
+    doc.text_with(
+        "DRAFT",
+        None,
+        &TextOptions::new()
+            .with_position(0.0, 0.0)
+            .with_font_size(48.0)
+            .with_line_height(48.0),
+    )
+    .await
+    .unwrap();
+    let watermark = doc.end_form().unwrap();
+
+    for i in 1..=2 {
+        if i > 1 {
+            doc.new_page().await.unwrap();
+        }
+        doc.draw_form(&letterhead, Transform::translate(72.0, 72.0))
+            .await
+            .unwrap();
+        doc.draw_form(
+            &watermark,
+            Transform::rotate(-45.0).then(Transform::translate(150.0, 600.0)),
+        )
+        .await
+        .unwrap();
+        doc.text_with(
+            &format!("Page {}", i),
+            None,
+            &TextOptions::new().with_position(72.0, 140.0),
+        )
+        .await
+        .unwrap();
+    }
+}
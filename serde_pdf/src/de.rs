@@ -1,18 +1,29 @@
+use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::io::prelude::*;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg};
 
 use num_traits::float::Float;
 use serde::de::{
-    self, Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    self,
+    value::{BytesDeserializer, SeqDeserializer},
+    Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
     VariantAccess, Visitor,
 };
 
 use crate::error::{Error, Result};
+use crate::ser::{NAME_OBJECT, NAME_REFERENCE, NAME_STREAM};
+use crate::string::{decode_string, decode_text};
 
 pub struct Deserializer<R> {
     input: R,
-    peek: Option<u8>,
+    /// Characters that have already been read from the input, but not consumed yet.
+    lookahead: VecDeque<u8>,
+    /// The `/Length` entries of the dictionaries that are currently being parsed.
+    lengths: Vec<Option<usize>>,
+    /// The `/Length` of the most recently parsed dictionary, used to read the data of the stream
+    /// that follows it.
+    stream_length: Option<usize>,
 }
 
 impl<R> Deserializer<R>
@@ -20,7 +31,21 @@ where
     R: Read,
 {
     pub fn new(input: R) -> Self {
-        Deserializer { input, peek: None }
+        Deserializer {
+            input,
+            lookahead: VecDeque::new(),
+            lengths: Vec::new(),
+            stream_length: None,
+        }
+    }
+
+    /// Makes sure that there is nothing but whitespace left in the input.
+    pub fn end(&mut self) -> Result<()> {
+        self.discard_whitespace()?;
+        match self.peek_char()? {
+            Some(_) => Err(Error::TrailingCharacters),
+            None => Ok(()),
+        }
     }
 }
 
@@ -28,93 +53,177 @@ pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
     T: Deserialize<'a>,
 {
-    let mut input = s.as_bytes();
-    let mut deserializer = Deserializer::new(input);
+    from_slice(s.as_bytes())
+}
+
+pub fn from_slice<'a, T>(v: &'a [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::new(v);
     let t = T::deserialize(&mut deserializer)?;
-    if deserializer.input.is_empty() {
-        Ok(t)
-    } else {
-        Err(Error::TrailingCharacters)
-    }
+    deserializer.end()?;
+    Ok(t)
 }
 
 impl<R> Deserializer<R>
 where
     R: Read,
 {
-    /// Look at the first character in the input without consuming it.
-    fn peek_char(&mut self) -> Result<Option<u8>> {
-        if self.peek.is_some() {
-            return Ok(self.peek);
+    /// Look at the `n`-th next character in the input without consuming it.
+    fn peek_nth(&mut self, n: usize) -> Result<Option<u8>> {
+        while self.lookahead.len() <= n {
+            let mut b = [0; 1];
+            if self.input.read(&mut b)? == 0 {
+                return Ok(None);
+            }
+            self.lookahead.push_back(b[0]);
         }
+        Ok(self.lookahead.get(n).copied())
+    }
 
-        let next = self.next_char()?;
-        self.peek = next;
-        Ok(next)
+    /// Look at the first character in the input without consuming it.
+    fn peek_char(&mut self) -> Result<Option<u8>> {
+        self.peek_nth(0)
     }
 
     /// Consume the first character in the input.
     fn next_char(&mut self) -> Result<Option<u8>> {
-        if let Some(ch) = self.peek.take() {
-            Ok(Some(ch))
-        } else {
-            let mut b = [0; 1];
-            let n = self.input.read(&mut b)?;
-            if n == 1 {
-                Ok(Some(b[0]))
-            } else {
-                Ok(None)
-            }
-        }
+        self.peek_char()?;
+        Ok(self.lookahead.pop_front())
     }
 
     /// Discard the char from a previous look ahead.
     fn discard_char(&mut self) {
-        self.peek = None;
+        self.lookahead.pop_front();
     }
 
-    /// Discard any whitespace (as defined by PDF Spec 1.7)
+    /// Discard any whitespace (as defined by PDF Spec 1.7) and comments.
     fn discard_whitespace(&mut self) -> Result<bool> {
         let mut has_whitespace = false;
         loop {
             match self.peek_char()? {
-                Some(0x00) | Some(0x09) | Some(0x0A) | Some(0x0C) | Some(0x0D) | Some(0x20) => {
+                Some(ch) if is_whitespace(ch) => {
                     self.discard_char();
                     has_whitespace = true;
                 }
+                Some(b'%') => {
+                    while !matches!(self.peek_char()?, None | Some(b'\r') | Some(b'\n')) {
+                        self.discard_char();
+                    }
+                    has_whitespace = true;
+                }
                 _ => return Ok(has_whitespace),
             }
         }
     }
 
-    fn parse_ident(&mut self, ident: &[u8]) -> Result<bool> {
-        for expected in ident {
-            if let Some(ch) = self.next_char()? {
-                if ch != *expected {
-                    return Ok(false);
-                }
-            } else {
-                return Err(Error::Eof);
+    /// Checks whether `keyword` (followed by a delimiter) starts at the `n`-th next character.
+    fn peek_keyword(&mut self, n: usize, keyword: &[u8]) -> Result<bool> {
+        for (i, expected) in keyword.iter().enumerate() {
+            if self.peek_nth(n + i)? != Some(*expected) {
+                return Ok(false);
+            }
+        }
+        Ok(self.peek_nth(n + keyword.len())?.is_none_or(is_delimiter))
+    }
+
+    /// Consume the given keyword (e.g. `endobj`) if it is next in the input.
+    fn parse_keyword(&mut self, keyword: &[u8]) -> Result<bool> {
+        self.discard_whitespace()?;
+        if self.peek_keyword(0, keyword)? {
+            self.lookahead.drain(..keyword.len());
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Looks ahead for an object number and generation number followed by `keyword` (i.e. either
+    /// a reference `n g R` or an object header `n g obj`), without consuming anything. Returns
+    /// both numbers and the count of characters they span (including the keyword).
+    fn peek_id(&mut self, keyword: &[u8]) -> Result<Option<(usize, usize, usize)>> {
+        let mut numbers = [0usize; 2];
+        let mut i = 0;
+        for number in numbers.iter_mut() {
+            while self.peek_nth(i)?.is_some_and(is_whitespace) {
+                i += 1;
+            }
+            let start = i;
+            while let Some(ch @ b'0'..=b'9') = self.peek_nth(i)? {
+                *number = match number
+                    .checked_mul(10)
+                    .and_then(|n| n.checked_add(usize::from(ch - b'0')))
+                {
+                    Some(n) => n,
+                    None => return Ok(None),
+                };
+                i += 1;
+            }
+            if i == start || !self.peek_nth(i)?.is_some_and(is_whitespace) {
+                return Ok(None);
             }
         }
+        while self.peek_nth(i)?.is_some_and(is_whitespace) {
+            i += 1;
+        }
+
+        if self.peek_keyword(i, keyword)? {
+            Ok(Some((numbers[0], numbers[1], i + keyword.len())))
+        } else {
+            Ok(None)
+        }
+    }
 
-        Ok(true)
+    /// Consumes `n g <keyword>` and returns the object and generation number.
+    fn parse_id(&mut self, keyword: &[u8], err: Error) -> Result<(usize, usize)> {
+        self.discard_whitespace()?;
+        let (id, rev, len) = self.peek_id(keyword)?.ok_or(err)?;
+        self.lookahead.drain(..len);
+        Ok((id, rev))
+    }
+
+    /// Consumes the `endobj` keyword at the end of an indirect object, skipping the data of
+    /// stream objects whose content was not read as a stream.
+    fn parse_object_end(&mut self) -> Result<()> {
+        self.discard_whitespace()?;
+        if self.peek_keyword(0, b"stream")? {
+            self.parse_stream()?;
+        }
+
+        if self.parse_keyword(b"endobj")? {
+            Ok(())
+        } else {
+            Err(Error::ExpectedObjectEnd)
+        }
+    }
+
+    fn parse_ident(&mut self, ident: &[u8], err: Error) -> Result<()> {
+        self.discard_whitespace()?;
+        for (i, expected) in ident.iter().enumerate() {
+            match self.peek_nth(i)? {
+                Some(ch) if ch != *expected => return Err(err),
+                Some(_) => {}
+                None => return Err(Error::Eof),
+            }
+        }
+        if !self.peek_nth(ident.len())?.is_none_or(is_delimiter) {
+            return Err(err);
+        }
+
+        self.lookahead.drain(..ident.len());
+        Ok(())
     }
 
     fn parse_bool(&mut self) -> Result<bool> {
+        self.discard_whitespace()?;
         match self.peek_char()? {
             Some(b't') => {
-                self.discard_char();
-                if !self.parse_ident(b"rue")? {
-                    return Err(Error::ExpectedBoolean);
-                }
+                self.parse_ident(b"true", Error::ExpectedBoolean)?;
                 Ok(true)
             }
             Some(b'f') => {
-                self.discard_char();
-                if !self.parse_ident(b"alse")? {
-                    return Err(Error::ExpectedBoolean);
-                }
+                self.parse_ident(b"false", Error::ExpectedBoolean)?;
                 Ok(false)
             }
             _ => Err(Error::ExpectedBoolean),
@@ -122,19 +231,15 @@ where
     }
 
     fn parse_null(&mut self) -> Result<()> {
-        if self.peek_char()? == Some(b'n') {
-            self.discard_char();
-            if self.parse_ident(b"ull")? {
-                return Ok(());
-            }
-        }
-        Err(Error::ExpectedNull)
+        self.parse_ident(b"null", Error::ExpectedNull)
     }
 
     fn parse_unsigned<T>(&mut self, allow_decimal: bool) -> Result<T>
     where
         T: CheckedMul + CheckedAdd + TryFrom<u8>,
     {
+        self.discard_whitespace()?;
+
         if self.peek_char()? == Some(b'+') {
             self.discard_char();
         }
@@ -145,9 +250,11 @@ where
 
         let ten = T::try_from(10).or(Err(Error::ExpectedInteger))?;
         let mut val = T::try_from(0).or(Err(Error::ExpectedInteger))?;
+        let mut has_digits = false;
         loop {
             match self.peek_char()? {
                 Some(ch @ b'0'..=b'9') => {
+                    has_digits = true;
                     val = match val.checked_mul(&ten) {
                         Some(v) => v,
                         None => return Err(Error::NumberOverflow),
@@ -161,19 +268,28 @@ where
                     self.discard_char();
                 }
                 Some(b'.') => {
-                    if allow_decimal {
+                    // a real number requires at least one digit (e.g. `4.`, `-.002`)
+                    let fraction = matches!(self.peek_nth(1)?, Some(b'0'..=b'9'));
+                    if allow_decimal && (has_digits || fraction) {
                         return Ok(val);
                     } else {
                         return Err(Error::ExpectedFloat);
                     }
                 }
-                Some(ch) if (ch as char).is_whitespace() => {
+                // a sign that is not followed by any digits
+                Some(ch) if is_delimiter(ch) && !has_digits => {
+                    return Err(Error::ExpectedInteger);
+                }
+                None if !has_digits => {
+                    return Err(Error::ExpectedInteger);
+                }
+                Some(ch) if is_delimiter(ch) => {
                     return Ok(val);
                 }
-                Some(b']') | None => {
+                None => {
                     return Ok(val);
                 }
-                ch => {
+                _ => {
                     return Err(Error::ExpectedInteger);
                 }
             }
@@ -184,6 +300,8 @@ where
     where
         T: Neg<Output = T> + CheckedMul + CheckedAdd + TryFrom<u8>,
     {
+        self.discard_whitespace()?;
+
         match self.peek_char()? {
             Some(b'-') => {
                 self.discard_char();
@@ -230,7 +348,7 @@ where
                             None => return Err(Error::NumberOverflow),
                         };
                     }
-                    Some(ch) if (ch as char).is_whitespace() => {
+                    Some(ch) if is_delimiter(ch) => {
                         return Ok(val);
                     }
                     None => {
@@ -246,144 +364,214 @@ where
         Ok(val)
     }
 
-    fn parse_string(&mut self) -> Result<String> {
-        fn win1252_to_unicode(code: u32) -> Result<char> {
-            let ch = match code {
-                128 => '\u{20ac}',
-                130 => '\u{201a}',
-                131 => '\u{0192}',
-                132 => '\u{201e}',
-                133 => '\u{2026}',
-                134 => '\u{2020}',
-                135 => '\u{2021}',
-                136 => '\u{02c6}',
-                137 => '\u{2030}',
-                138 => '\u{0160}',
-                139 => '\u{2039}',
-                140 => '\u{0152}',
-                142 => '\u{017d}',
-                145 => '\u{2018}',
-                146 => '\u{2019}',
-                147 => '\u{201c}',
-                148 => '\u{201d}',
-                149 => '\u{2022}',
-                150 => '\u{2013}',
-                151 => '\u{2014}',
-                152 => '\u{02dc}',
-                153 => '\u{2122}',
-                154 => '\u{0161}',
-                155 => '\u{203a}',
-                156 => '\u{0153}',
-                158 => '\u{017e}',
-                159 => '\u{0178}',
-                _ => match std::char::from_u32(code) {
-                    Some(ch) => ch,
-                    None => return Err(Error::InvalidEscapeSequence),
-                },
-            };
-            Ok(ch)
-        }
-
-        if self.peek_char()? == Some(b'/') {
-            return self.parse_name();
-        }
-
-        if self.peek_char()? != Some(b'(') {
-            return Err(Error::ExpectedString);
-        }
-
-        self.discard_char();
+    /// Parses a literal (`(...)`) or hexadecimal (`<...>`) string and returns its bytes.
+    fn parse_string_bytes(&mut self) -> Result<Vec<u8>> {
+        self.discard_whitespace()?;
 
-        let mut chars: Vec<char> = Vec::new();
-        let mut opened_parentheses = 0;
-        while let Some(ch) = self.next_char()? {
-            match ch {
-                b'(' => {
-                    opened_parentheses += 1;
-                    chars.push(ch as char);
-                }
-                b')' => {
-                    if opened_parentheses == 0 {
-                        // we are done
-                        return Ok(chars.into_iter().collect());
+        let mut raw = Vec::new();
+        match self.peek_char()? {
+            Some(b'(') => {
+                let mut opened_parentheses = 0;
+                loop {
+                    let ch = self.next_char()?.ok_or(Error::Eof)?;
+                    raw.push(ch);
+                    match ch {
+                        b'\\' => raw.push(self.next_char()?.ok_or(Error::Eof)?),
+                        b'(' => opened_parentheses += 1,
+                        b')' => {
+                            opened_parentheses -= 1;
+                            if opened_parentheses == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
                     }
-
-                    opened_parentheses -= 1;
-                    chars.push(ch as char);
                 }
-                b'\\' => {
+            }
+            Some(b'<') => {
+                self.discard_char();
+                raw.push(b'<');
+                loop {
                     match self.next_char()? {
-                        Some(b'n') => chars.push(b'\n' as char),
-                        Some(b'r') => chars.push(b'\r' as char),
-                        Some(b't') => chars.push(b'\t' as char),
-                        Some(b'b') => chars.push(0x08 as char), // \b
-                        Some(b'f') => chars.push(0x0C as char), // \f
-                        Some(b'(') => chars.push(b'(' as char),
-                        Some(b')') => chars.push(b')' as char),
-                        Some(b'\\') => chars.push(b'\\' as char),
-                        Some(b'\n') => {}
-                        Some(c1 @ b'0'..=b'9') => {
-                            let mut bytes = Vec::with_capacity(3);
-                            bytes.push(c1);
-
-                            // we take up to three, ie, two more bytes
-                            for _ in 1..=2 {
-                                if let Some(c @ b'0'..=b'9') = self.peek_char()? {
-                                    bytes.push(self.next_char()?.unwrap());
-                                }
-                            }
-
-                            let octal = std::str::from_utf8(&bytes).unwrap();
-                            let code = match u32::from_str_radix(octal, 8) {
-                                Ok(c) => c,
-                                Err(_) => return Err(Error::InvalidEscapeSequence),
-                            };
-                            chars.push(win1252_to_unicode(code)?);
-                        }
-                        _ => return Err(Error::InvalidEscapeSequence),
+                        Some(b'>') => break,
+                        Some(ch) if ch.is_ascii_hexdigit() => raw.push(ch),
+                        Some(ch) if is_whitespace(ch) => {}
+                        Some(_) => return Err(Error::InvalidHexString),
+                        None => return Err(Error::Eof),
                     }
                 }
-                _ => chars.push(ch as char),
+                raw.push(b'>');
             }
+            _ => return Err(Error::ExpectedString),
+        }
+
+        Ok(decode_string(&raw))
+    }
+
+    /// Parses a name or a string. Strings are decoded as text strings, i.e. from UTF-16BE or UTF-8
+    /// if they start with the corresponding byte order mark, and from PDFDocEncoding otherwise.
+    fn parse_string(&mut self) -> Result<String> {
+        self.discard_whitespace()?;
+        if self.peek_char()? == Some(b'/') {
+            return self.parse_name();
         }
 
-        Err(Error::Eof)
+        let bytes = self.parse_string_bytes()?;
+        decode_text(&bytes).ok_or(Error::InvalidTextString)
     }
 
     fn parse_name(&mut self) -> Result<String> {
+        self.discard_whitespace()?;
         if self.peek_char()? != Some(b'/') {
             return Err(Error::ExpectedName);
         }
 
         self.discard_char();
 
-        let mut name = String::new();
+        let mut raw = Vec::new();
         while let Some(ch) = self.peek_char()? {
-            match ch {
-                // escape sequence
-                b'#' => {
-                    self.discard_char();
-                    if let (Some(n1), Some(n2)) = (self.next_char()?, self.next_char()?) {
-                        let bytes = &[n1, n2];
-                        let code = std::str::from_utf8(bytes).unwrap();
-                        let code = u8::from_str_radix(code, 16).unwrap();
-                        name.push(code as char);
-                    } else {
-                        return Err(Error::InvalidEscapeSequence);
-                    }
+            if is_delimiter(ch) {
+                // other characters cannot occur inside a name, so we are done here
+                break;
+            }
+            self.discard_char();
+            raw.push(ch);
+        }
+
+        decode_name(&raw)
+    }
+
+    /// Parses `stream ... endstream` and returns the raw stream data. The `/Length` of the
+    /// preceding dictionary is used to determine the end of the data if it is a direct object,
+    /// otherwise the data ends at the `endstream` keyword.
+    fn parse_stream(&mut self) -> Result<Vec<u8>> {
+        if !self.parse_keyword(b"stream")? {
+            return Err(Error::ExpectedStream);
+        }
+        // the keyword is followed by either CRLF or LF
+        if self.peek_char()? == Some(b'\r') {
+            self.discard_char();
+        }
+        if self.peek_char()? == Some(b'\n') {
+            self.discard_char();
+        }
+
+        let mut data = Vec::new();
+        match self.stream_length.take() {
+            Some(len) => {
+                data.extend(self.lookahead.drain(..len.min(self.lookahead.len())));
+                let rest = (len - data.len()) as u64;
+                (&mut self.input).take(rest).read_to_end(&mut data)?;
+                if data.len() < len {
+                    return Err(Error::Eof);
                 }
-                0x21..=0x22 | 0x24..=0x7E => {
-                    self.discard_char();
-                    name.push(ch as char);
+            }
+            None => {
+                while !self.peek_keyword(0, b"endstream")? {
+                    data.push(self.next_char()?.ok_or(Error::Eof)?);
                 }
-                _ => {
-                    // other characters cannot occur inside a name, so we are done here
-                    break;
+                // the end-of-line marker before `endstream` is not part of the data
+                if data.last() == Some(&b'\n') {
+                    data.pop();
                 }
+                if data.last() == Some(&b'\r') {
+                    data.pop();
+                }
+            }
+        }
+
+        if !self.parse_keyword(b"endstream")? {
+            return Err(Error::ExpectedStreamEnd);
+        }
+
+        Ok(data)
+    }
+
+    fn begin_dictionary(&mut self) -> Result<()> {
+        self.discard_whitespace()?;
+        if (self.peek_nth(0)?, self.peek_nth(1)?) != (Some(b'<'), Some(b'<')) {
+            return Err(Error::ExpectedMap);
+        }
+        self.lookahead.drain(..2);
+        self.lengths.push(None);
+        Ok(())
+    }
+
+    fn end_dictionary(&mut self) -> Result<()> {
+        self.discard_whitespace()?;
+        if (self.next_char()?, self.next_char()?) != (Some(b'>'), Some(b'>')) {
+            return Err(Error::ExpectedMapEnd);
+        }
+        self.stream_length = self.lengths.pop().flatten();
+        Ok(())
+    }
+
+    /// Remembers the `/Length` of the current dictionary (without consuming it), if it is a
+    /// direct object.
+    fn peek_length(&mut self) -> Result<()> {
+        self.discard_whitespace()?;
+        if self.peek_id(b"R")?.is_some() {
+            return Ok(());
+        }
+
+        let mut len = 0usize;
+        let mut i = 0;
+        while let Some(ch @ b'0'..=b'9') = self.peek_nth(i)? {
+            len = len
+                .checked_mul(10)
+                .and_then(|len| len.checked_add(usize::from(ch - b'0')))
+                .ok_or(Error::NumberOverflow)?;
+            i += 1;
+        }
+        if i > 0 {
+            if let Some(length) = self.lengths.last_mut() {
+                *length = Some(len);
             }
         }
+        Ok(())
+    }
+
+    /// Deserializes a dictionary. With `stream` set, the data of a stream that follows the
+    /// dictionary is provided as an additional entry with the key [`NAME_STREAM`].
+    fn deserialize_dictionary<'de, V>(&mut self, visitor: V, stream: bool) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // Parse the opening brace of the map.
+        self.begin_dictionary()?;
+
+        // Give the visitor access to each entry of the map.
+        let mut dict = Dictionary::new(self, stream);
+        let value = visitor.visit_map(&mut dict)?;
 
-        Ok(name)
+        // Parse the closing brace of the map (unless already done to look for a stream).
+        if !dict.closed {
+            self.end_dictionary()?;
+        }
+        Ok(value)
+    }
+
+    fn deserialize_number<'de, V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut i = 0;
+        let mut is_float = false;
+        while let Some(ch) = self.peek_nth(i)? {
+            if is_delimiter(ch) {
+                break;
+            }
+            is_float |= ch == b'.';
+            i += 1;
+        }
+
+        if is_float {
+            visitor.visit_f64(self.parse_float()?)
+        } else if self.peek_char()? == Some(b'-') {
+            visitor.visit_i64(self.parse_signed(false)?)
+        } else {
+            visitor.visit_u64(self.parse_unsigned(false)?)
+        }
     }
 }
 
@@ -400,22 +588,35 @@ where
     where
         V: Visitor<'de>,
     {
-        match self.peek_char()? {
-            Some(b'n') => self.deserialize_unit(visitor), // null
-            Some(b't') | Some(b'f') => self.deserialize_bool(visitor),
-            Some(b'(') | Some(b'/') => self.deserialize_str(visitor),
-            Some(b'0'..=b'9') | Some(b'+') => self.deserialize_u64(visitor),
-            Some(b'-') => self.deserialize_i64(visitor),
-            Some(b'.') => self.deserialize_f64(visitor),
-            Some(b'[') => self.deserialize_seq(visitor),
-            Some(b'<') => self.deserialize_map(visitor),
-            // TODO:
-            // - Reference (2 0 R)
-            // - <...> str
-            // - (...) other str
-            // - << >> map
-            // - 10 0 obj\n endobj
-            // - stream endstream
+        self.discard_whitespace()?;
+        match (self.peek_nth(0)?, self.peek_nth(1)?) {
+            (Some(b'n'), _) => self.deserialize_unit(visitor), // null
+            (Some(b't'), _) | (Some(b'f'), _) => self.deserialize_bool(visitor),
+            (Some(b'/'), _) => self.deserialize_string(visitor), // name
+            (Some(b'('), _) => self.deserialize_byte_buf(visitor), // string
+            (Some(b'<'), Some(b'<')) => self.deserialize_dictionary(visitor, true),
+            (Some(b'<'), _) => self.deserialize_byte_buf(visitor), // string
+            (Some(b'['), _) => self.deserialize_seq(visitor),
+            (Some(b's'), _) => self.deserialize_byte_buf(visitor), // stream
+            (Some(b'0'..=b'9'), _) => {
+                if self.peek_id(b"obj")?.is_some() {
+                    // an indirect object is self-described by its content
+                    self.parse_id(b"obj", Error::ExpectedObject)?;
+                    let value = self.deserialize_any(visitor)?;
+                    self.parse_object_end()?;
+                    Ok(value)
+                } else if let Some((id, rev, len)) = self.peek_id(b"R")? {
+                    self.lookahead.drain(..len);
+                    visitor.visit_map(Entry::new(
+                        NAME_REFERENCE,
+                        SeqDeserializer::new(vec![id, rev].into_iter()),
+                    ))
+                } else {
+                    self.deserialize_number(visitor)
+                }
+            }
+            (Some(b'+'), _) | (Some(b'-'), _) | (Some(b'.'), _) => self.deserialize_number(visitor),
+            (None, _) => Err(Error::Eof),
             _ => Err(Error::Syntax),
         }
     }
@@ -502,12 +703,11 @@ where
         V: Visitor<'de>,
     {
         let s = self.parse_string()?;
-        if s.len() != 1 {
-            return Err(Error::ExpectedChar);
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => visitor.visit_char(ch),
+            _ => Err(Error::ExpectedChar),
         }
-
-        let ch = s.chars().next().ok_or(Error::ExpectedChar)?;
-        visitor.visit_char(ch)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
@@ -524,24 +724,30 @@ where
         self.deserialize_str(visitor)
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value>
+    // Both strings and streams can be deserialized as bytes.
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.deserialize_byte_buf(visitor)
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
+        self.discard_whitespace()?;
+        match self.peek_char()? {
+            Some(b'(') | Some(b'<') => visitor.visit_byte_buf(self.parse_string_bytes()?),
+            _ => visitor.visit_byte_buf(self.parse_stream()?),
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.discard_whitespace()?;
         if self.peek_char()? == Some(b'n') {
             self.parse_null()?;
             visitor.visit_none()
@@ -562,30 +768,31 @@ where
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
-        //        self.deserialize_unit(visitor)
+        self.deserialize_unit(visitor)
     }
 
     fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
-        //        visitor.visit_newtype_struct(self)
+        visitor.visit_newtype_struct(self)
     }
 
-    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        self.discard_whitespace()?;
+
         // Parse the opening bracket of the sequence.
         if self.peek_char()? == Some(b'[') {
             self.discard_char();
 
             // Give the visitor access to each element of the sequence.
-            let value = visitor.visit_seq(SpaceSeparated::new(&mut self))?;
+            let value = visitor.visit_seq(Array::new(self))?;
 
             // Parse the closing bracket of the sequence.
+            self.discard_whitespace()?;
             if self.next_char()? == Some(b']') {
                 Ok(value)
             } else {
@@ -600,42 +807,44 @@ where
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
-        //        self.deserialize_seq(visitor)
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         _len: usize,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
-        //        self.deserialize_seq(visitor)
+        match name {
+            NAME_REFERENCE => {
+                let (id, rev) = self.parse_id(b"R", Error::ExpectedReference)?;
+                visitor.visit_seq(SeqDeserializer::new(vec![id, rev].into_iter()))
+            }
+            NAME_OBJECT => {
+                let (id, rev) = self.parse_id(b"obj", Error::ExpectedObject)?;
+                let value = visitor.visit_seq(IndirectObject {
+                    de: &mut *self,
+                    header: vec![id, rev].into_iter(),
+                    content: true,
+                })?;
+
+                self.parse_object_end()?;
+                Ok(value)
+            }
+            NAME_STREAM => visitor.visit_seq(Stream { de: self, ix: 0 }),
+            _ => self.deserialize_seq(visitor),
+        }
     }
 
-    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // Parse the opening brace of the map.
-        if (self.next_char()?, self.next_char()?) == (Some(b'<'), Some(b'<')) {
-            self.discard_whitespace()?;
-
-            // Give the visitor access to each entry of the map.
-            let value = visitor.visit_map(SpaceSeparated::new(&mut self))?;
-            // Parse the closing brace of the map.
-            if (self.next_char()?, self.next_char()?) == (Some(b'>'), Some(b'>')) {
-                Ok(value)
-            } else {
-                Err(Error::ExpectedMapEnd)
-            }
-        } else {
-            Err(Error::ExpectedMap)
-        }
+        self.deserialize_dictionary(visitor, false)
     }
 
     fn deserialize_struct<V>(
@@ -659,25 +868,21 @@ where
     where
         V: Visitor<'de>,
     {
-        if self.peek_char()? == Some(b'/') {
+        self.discard_whitespace()?;
+        match (self.peek_nth(0)?, self.peek_nth(1)?) {
             // Visit a unit variant.
-            visitor.visit_enum(self.parse_string()?.into_deserializer())
-        } else if (self.next_char()?, self.next_char()?) == (Some(b'<'), Some(b'<')) {
-            self.discard_whitespace()?;
-
-            // Visit a newtype variant, tuple variant, or struct variant.
-            let value = visitor.visit_enum(Enum::new(self))?;
+            (Some(b'/'), _) => visitor.visit_enum(self.parse_name()?.into_deserializer()),
+            (Some(b'<'), Some(b'<')) => {
+                self.begin_dictionary()?;
 
-            self.discard_whitespace()?;
+                // Visit a newtype variant, tuple variant, or struct variant.
+                let value = visitor.visit_enum(Enum::new(self))?;
 
-            // Parse the closing brace of the map.
-            if (self.next_char()?, self.next_char()?) == (Some(b'>'), Some(b'>')) {
+                // Parse the closing brace of the map.
+                self.end_dictionary()?;
                 Ok(value)
-            } else {
-                Err(Error::ExpectedMapEnd)
             }
-        } else {
-            Err(Error::ExpectedMap)
+            _ => Err(Error::ExpectedEnum),
         }
     }
 
@@ -692,26 +897,22 @@ where
     where
         V: Visitor<'de>,
     {
-        unimplemented!()
-        //        self.deserialize_any(visitor)
+        // a dictionary followed by the data of its stream is skipped as a whole
+        self.deserialize_any(visitor)
     }
 }
 
-struct SpaceSeparated<'a, R> {
+struct Array<'a, R> {
     de: &'a mut Deserializer<R>,
-    first: bool,
 }
 
-impl<'a, 'de, R> SpaceSeparated<'a, R>
-where
-    R: Read,
-{
+impl<'a, R> Array<'a, R> {
     fn new(de: &'a mut Deserializer<R>) -> Self {
-        SpaceSeparated { de, first: true }
+        Array { de }
     }
 }
 
-impl<'de, 'a, R> SeqAccess<'de> for SpaceSeparated<'a, R>
+impl<'de, 'a, R> SeqAccess<'de> for Array<'a, R>
 where
     R: Read,
 {
@@ -722,24 +923,39 @@ where
         T: DeserializeSeed<'de>,
     {
         // Check if there are no more elements.
-        if self.de.peek_char()? == Some(b']') {
-            return Ok(None);
+        self.de.discard_whitespace()?;
+        match self.de.peek_char()? {
+            Some(b']') => Ok(None),
+            None => Err(Error::ExpectedArrayEnd),
+            // Deserialize an array element.
+            Some(_) => seed.deserialize(&mut *self.de).map(Some),
         }
+    }
+}
 
-        // Space is required before every element except the first.
-        if !self.first && self.de.next_char()? != Some(b' ') {
-            return Err(Error::ExpectedArrayComma);
-        }
-        self.first = false;
+struct Dictionary<'a, R> {
+    de: &'a mut Deserializer<R>,
+    /// Whether to provide the data of a stream following the dictionary as an entry.
+    stream: bool,
+    /// Whether the closing brace has already been consumed.
+    closed: bool,
+    data: Option<Vec<u8>>,
+}
 
-        // Deserialize an array element.
-        seed.deserialize(&mut *self.de).map(Some)
+impl<'a, R> Dictionary<'a, R> {
+    fn new(de: &'a mut Deserializer<R>, stream: bool) -> Self {
+        Dictionary {
+            de,
+            stream,
+            closed: false,
+            data: None,
+        }
     }
 }
 
 // `MapAccess` is provided to the `Visitor` to give it the ability to iterate
 // through entries of the map.
-impl<'de, 'a, R> MapAccess<'de> for SpaceSeparated<'a, R>
+impl<'de, 'a, R> MapAccess<'de> for Dictionary<'a, R>
 where
     R: Read,
 {
@@ -749,30 +965,208 @@ where
     where
         K: DeserializeSeed<'de>,
     {
-        let had_whitespace = self.de.discard_whitespace()?;
+        if self.closed {
+            return Ok(None);
+        }
 
         // Check if there are no more entries.
-        if self.de.peek_char()? == Some(b'>') {
-            return Ok(None);
+        self.de.discard_whitespace()?;
+        match self.de.peek_char()? {
+            Some(b'>') if self.stream => {
+                self.de.end_dictionary()?;
+                self.closed = true;
+
+                self.de.discard_whitespace()?;
+                if !self.de.peek_keyword(0, b"stream")? {
+                    return Ok(None);
+                }
+                self.data = Some(self.de.parse_stream()?);
+                seed.deserialize(NAME_STREAM.into_deserializer()).map(Some)
+            }
+            Some(b'>') => Ok(None),
+            Some(b'/') => {
+                let key = self.de.parse_name()?;
+                if key == "Length" {
+                    self.de.peek_length()?;
+                }
+                seed.deserialize(MapKey(key)).map(Some)
+            }
+            None => Err(Error::ExpectedMapEnd),
+            Some(_) => Err(Error::ExpectedName),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.data.take() {
+            Some(data) => seed.deserialize(BytesDeserializer::new(&data)),
+            None => seed.deserialize(&mut *self.de),
         }
+    }
+}
+
+/// Provides a map with a single entry, which is used to represent references as part of the
+/// serde data model (keyed by [`NAME_REFERENCE`]).
+struct Entry<D> {
+    key: Option<&'static str>,
+    value: Option<D>,
+}
 
-        if !self.first && !had_whitespace {
-            return Err(Error::ExpectedMapComma);
+impl<D> Entry<D> {
+    fn new(key: &'static str, value: D) -> Self {
+        Entry {
+            key: Some(key),
+            value: Some(value),
         }
-        self.first = false;
+    }
+}
 
-        seed.deserialize(&mut *self.de).map(Some)
+impl<'de, D> MapAccess<'de> for Entry<D>
+where
+    D: de::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.key.take() {
+            Some(key) => seed.deserialize(key.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
     where
         V: DeserializeSeed<'de>,
     {
-        if !self.de.discard_whitespace()? {
-            return Err(Error::ExpectedMapColon);
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::custom("value is missing")),
         }
+    }
+}
+
+/// Provides the object number, generation number and the content of an indirect object.
+struct IndirectObject<'a, R> {
+    de: &'a mut Deserializer<R>,
+    header: std::vec::IntoIter<usize>,
+    content: bool,
+}
+
+impl<'de, 'a, R> SeqAccess<'de> for IndirectObject<'a, R>
+where
+    R: Read,
+{
+    type Error = Error;
 
-        seed.deserialize(&mut *self.de)
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(n) = self.header.next() {
+            return seed.deserialize(n.into_deserializer()).map(Some);
+        }
+        if self.content {
+            self.content = false;
+            return seed.deserialize(&mut *self.de).map(Some);
+        }
+        Ok(None)
+    }
+}
+
+/// Provides the dictionary and (if present) the data of a stream.
+struct Stream<'a, R> {
+    de: &'a mut Deserializer<R>,
+    ix: usize,
+}
+
+impl<'de, 'a, R> SeqAccess<'de> for Stream<'a, R>
+where
+    R: Read,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.ix += 1;
+        match self.ix {
+            1 => seed.deserialize(&mut *self.de).map(Some),
+            2 => {
+                self.de.discard_whitespace()?;
+                if self.de.peek_keyword(0, b"stream")? {
+                    seed.deserialize(&mut *self.de).map(Some)
+                } else {
+                    Ok(None)
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Deserializes dictionary keys, which are always names, but can also represent numbers or enum
+/// variants.
+struct MapKey(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.0.parse().or(Err(Error::ExpectedInteger))?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKey {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let key: de::value::StringDeserializer<Error> = self.0.into_deserializer();
+        key.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool str string bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
     }
 }
 
@@ -797,12 +1191,7 @@ where
     where
         V: DeserializeSeed<'de>,
     {
-        let val = seed.deserialize(&mut *self.de)?;
-
-        if !self.de.discard_whitespace()? {
-            return Err(Error::ExpectedMapColon);
-        }
-
+        let val = seed.deserialize(MapKey(self.de.parse_name()?))?;
         Ok((val, self))
     }
 }
@@ -816,7 +1205,7 @@ where
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
-        Err(Error::ExpectedString)
+        self.de.parse_null()
     }
 
     fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
@@ -841,6 +1230,47 @@ where
     }
 }
 
+pub(crate) fn is_whitespace(ch: u8) -> bool {
+    matches!(ch, 0x00 | 0x09 | 0x0A | 0x0C | 0x0D | 0x20)
+}
+
+pub(crate) fn is_delimiter(ch: u8) -> bool {
+    is_whitespace(ch)
+        || matches!(
+            ch,
+            b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+        )
+}
+
+/// Decodes the characters of a name (without its leading `/`), which may contain `#xx` escape
+/// sequences (PDF spec 1.7 page 57). The null character is not allowed in names, not even
+/// escaped. Names that are not valid UTF-8 are decoded as Latin-1 and are therefore not written
+/// back byte for byte (e.g. `/#E4` is read as `ä`, which is written as `/#c3#a4`).
+pub(crate) fn decode_name(raw: &[u8]) -> Result<String> {
+    let mut name = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'#' {
+            let code = raw
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .filter(|code| *code != 0)
+                .ok_or(Error::InvalidEscapeSequence)?;
+            name.push(code);
+            i += 3;
+        } else {
+            name.push(raw[i]);
+            i += 1;
+        }
+    }
+
+    // names are usually UTF-8 encoded, but are not required to be
+    Ok(String::from_utf8(name)
+        .unwrap_or_else(|err| err.as_bytes().iter().map(|b| char::from(*b)).collect()))
+}
+
 pub trait CheckedAdd: Sized + Add<Self, Output = Self> {
     /// Adds two numbers, checking for overflow. If overflow happens, `None` is
     /// returned.
@@ -934,8 +1364,14 @@ impl CheckedMul for f64 {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
+    use serde::de::IgnoredAny;
+    use serde_bytes::ByteBuf;
+
     use super::{from_str, Deserializer};
     use crate::error::Error;
+    use crate::{to_string, Object, ObjectId, PdfText, Reference, Stream, Value};
 
     #[test]
     fn test_boolean() {
@@ -977,6 +1413,12 @@ mod test {
         assert_eq!(from_str::<i32>("0"), Ok(0));
         assert_eq!(from_str::<i16>("+17"), Ok(17));
         assert_eq!(from_str::<i32>("-98"), Ok(-98));
+
+        // a sign without digits
+        assert_eq!(from_str::<i32>("-"), Err(Error::ExpectedInteger));
+        assert_eq!(from_str::<i32>("+ "), Err(Error::ExpectedInteger));
+        assert_eq!(from_str::<u32>("+"), Err(Error::ExpectedInteger));
+        assert_eq!(from_str::<Vec<i32>>("[1 -]"), Err(Error::ExpectedInteger));
     }
 
     #[test]
@@ -994,6 +1436,8 @@ mod test {
         // Errors
         assert_eq!(from_str::<u32>("-1"), Err(Error::ExpectedUnsignedInteger));
         assert_eq!(from_str::<u32>("1.3"), Err(Error::ExpectedFloat));
+        assert_eq!(from_str::<f64>("-."), Err(Error::ExpectedFloat));
+        assert_eq!(from_str::<f64>("-"), Err(Error::ExpectedInteger));
         assert_eq!(
             from_str::<f32>(&std::f64::MAX.to_string()),
             Err(Error::NumberOverflow)
@@ -1003,9 +1447,16 @@ mod test {
     #[test]
     fn test_string() {
         assert_eq!(
-            from_str(r#"(0ab\(\\fo\)?!\200)"#),
+            from_str(r#"(0ab\(\\fo\)?!\240)"#),
             Ok(r#"0ab(\fo)?!€"#.to_string())
         );
+        // PDFDocEncoding
+        assert_eq!(from_str(r#"(M\374ller \200)"#), Ok("Müller •".to_string()));
+        assert_eq!(from_str("<FEFF00FC>"), Ok("ü".to_string()));
+        assert_eq!(
+            from_str::<String>(r#"(\000\001)"#),
+            Err(Error::InvalidTextString)
+        );
         assert_eq!(
             from_str(
                 r#"(no new\
@@ -1069,8 +1520,6 @@ line)"#
 
     #[test]
     fn test_map() {
-        use std::collections::HashMap;
-
         let mut expected = HashMap::new();
         expected.insert("foo".to_string(), "bar".to_string());
 
@@ -1113,4 +1562,222 @@ line)"#
         let j = r#"[102 111 111 98 97 114]"#;
         assert_eq!(expected, from_str::<'_, Vec<u8>>(j).unwrap());
     }
+
+    #[test]
+    fn test_name_utf8() {
+        assert_eq!(from_str::<String>("/Gr#c3#bcn"), Ok("Grün".to_string()));
+        assert_eq!(
+            from_str::<String>("/A#4"),
+            Err(Error::InvalidEscapeSequence)
+        );
+        assert_eq!(
+            from_str::<String>("/A#00B"),
+            Err(Error::InvalidEscapeSequence)
+        );
+        // not valid UTF-8, thus decoded as Latin-1
+        assert_eq!(from_str::<String>("/#E4"), Ok("ä".to_string()));
+    }
+
+    #[test]
+    fn test_hex_string() {
+        assert_eq!(from_str::<String>("<48656C6C 6F>"), Ok("Hello".to_string()));
+        assert_eq!(from_str::<String>("<486>"), Ok("H`".to_string()));
+        assert_eq!(
+            from_str::<String>(&to_string(&PdfText("Grün")).unwrap()),
+            Ok("Grün".to_string())
+        );
+        assert_eq!(from_str::<String>("<4X>"), Err(Error::InvalidHexString));
+    }
+
+    #[test]
+    fn test_string_bytes() {
+        assert_eq!(
+            from_str::<ByteBuf>("<00FF10>").unwrap().into_vec(),
+            vec![0x00, 0xFF, 0x10]
+        );
+        assert_eq!(
+            from_str::<ByteBuf>(r#"(a\(b\)\0)"#).unwrap().into_vec(),
+            b"a(b)\0".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_dictionary_without_whitespace() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct Pages {
+            count: u32,
+            kids: Vec<Reference<()>>,
+            media_box: Vec<f64>,
+        }
+
+        let pages: Pages =
+            from_str("<</Type/Pages/Count 2/Kids[1 0 R 2 0 R]/MediaBox[0 0 595.5 842]>>").unwrap();
+        assert_eq!(pages.count, 2);
+        assert_eq!(
+            pages.kids,
+            vec![
+                Reference::new(ObjectId::new(1, 0)),
+                Reference::new(ObjectId::new(2, 0))
+            ]
+        );
+        assert_eq!(pages.media_box, vec![0.0, 0.0, 595.5, 842.0]);
+    }
+
+    #[test]
+    fn test_comments() {
+        let j = "% header\n<< /int 1 % comment\n /seq [(a) % other comment\n (b)] >>\n";
+        let map: HashMap<String, IgnoredAny> = from_str(j).unwrap();
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_reference() {
+        assert!(from_str::<Reference<()>>("12 0 R") == Ok(Reference::new(ObjectId::new(12, 0))));
+        assert!(from_str::<Reference<()>>("12 0 obj") == Err(Error::ExpectedReference));
+        assert!(from_str::<Reference<()>>("12 R") == Err(Error::ExpectedReference));
+    }
+
+    #[test]
+    fn test_ignored_any() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Test {
+            a: u32,
+        }
+
+        let j = r#"<< /Type /Test /Parent 3 0 R /b << /c [1 (x) <00>] >> /a 4 /d -1.5 >>"#;
+        assert_eq!(from_str(j), Ok(Test { a: 4 }));
+        assert_eq!(
+            from_str::<Test>("<< /a 4 >> 1"),
+            Err(Error::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn test_map_with_number_keys() {
+        let mut map = HashMap::new();
+        map.insert(1u32, "One".to_string());
+        map.insert(2u32, "Two".to_string());
+        assert_eq!(from_str(&to_string(&map).unwrap()), Ok(map));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        enum E {
+            Unit,
+            Newtype(u32),
+            Tuple(u32, u32),
+            Struct { a: u32 },
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Inner {
+            name: String,
+        }
+
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        #[serde(rename = "Test")]
+        struct Test {
+            bool: bool,
+            int: i32,
+            float: f64,
+            char: char,
+            none: Option<u32>,
+            some: Option<u32>,
+            seq: Vec<String>,
+            tuple: (u8, i8),
+            inner: Inner,
+            enums: Vec<E>,
+            map: HashMap<String, u32>,
+            unit: (),
+        }
+
+        let mut map = HashMap::new();
+        map.insert("Key".to_string(), 42);
+        let test = Test {
+            bool: true,
+            int: -3,
+            float: 1.25,
+            char: 'x',
+            none: None,
+            some: Some(7),
+            seq: vec!["Adobe Green".to_string(), "paired()".to_string()],
+            tuple: (1, -1),
+            inner: Inner {
+                name: "Inner".to_string(),
+            },
+            enums: vec![E::Unit, E::Newtype(1), E::Tuple(1, 2), E::Struct { a: 3 }],
+            map,
+            unit: (),
+        };
+        assert_eq!(from_str(&to_string(&test).unwrap()), Ok(test));
+    }
+
+    #[test]
+    fn test_object() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Test {
+            #[serde(rename = "Count")]
+            count: u32,
+        }
+
+        let obj = Object::new(3, 1, Test { count: 2 });
+        let obj: Object<Test> = from_str(&to_string(&obj).unwrap()).unwrap();
+        assert_eq!((obj.id(), obj.rev()), (3, 1));
+        assert_eq!(obj.content(), &Test { count: 2 });
+
+        assert_eq!(
+            from_str::<Object<u32>>("3 0 obj 1").err(),
+            Some(Error::ExpectedObjectEnd)
+        );
+
+        // self-describing deserialization yields the content of the object
+        assert_eq!(
+            from_str::<Value>("1 0 obj << /A 1 >> endobj"),
+            from_str::<Value>("<< /A 1 >>")
+        );
+        assert_eq!(
+            from_str::<Value>("1 0 obj [1 0 R] endobj"),
+            from_str::<Value>("[1 0 R]")
+        );
+        assert_eq!(
+            from_str::<Value>("1 0 obj 1"),
+            Err(Error::ExpectedObjectEnd)
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        #[serde(rename = "")]
+        struct Dict {
+            #[serde(rename = "Length")]
+            length: usize,
+        }
+
+        // the data itself contains the `endstream` keyword
+        let data = b"a\nendstream\nb".to_vec();
+        let stream = Stream {
+            dict: Dict { length: data.len() },
+            data: data.clone(),
+        };
+        let obj = Object::new(1, 0, stream);
+        let obj: Object<Stream<Dict>> = from_str(&to_string(&obj).unwrap()).unwrap();
+        assert_eq!(obj.content().data, data);
+
+        // without the stream data
+        let stream: Stream<Dict> = from_str("<< /Length 0 >>").unwrap();
+        assert!(stream.data.is_empty());
+
+        // the data of streams that are not deserialized as such is skipped
+        let obj: Object<Dict> =
+            from_str("1 0 obj\n<< /Length 3 >>\nstream\r\nabc\r\nendstream\nendobj").unwrap();
+        assert_eq!(obj.content(), &Dict { length: 3 });
+
+        // an indirect length
+        let stream: Stream<HashMap<String, Reference<()>>> =
+            from_str("<< /Length 2 0 R >>\nstream\nabc\nendstream").unwrap();
+        assert_eq!(stream.data, b"abc");
+    }
 }
@@ -23,7 +23,7 @@ pub enum Error {
 
     // Zero or more variants that can be created directly by the Serializer and
     // Deserializer without going through `ser::Error` and `de::Error`. These
-    // are specific to the format, in this case JSON.
+    // are specific to the format, in this case PDF.
     Eof,
     Syntax,
     ExpectedBoolean,
@@ -35,17 +35,25 @@ pub enum Error {
     ExpectedChar,
     ExpectedNull,
     ExpectedArray,
-    ExpectedArrayComma,
     ExpectedArrayEnd,
     ExpectedMap,
-    ExpectedMapColon,
-    ExpectedMapComma,
     ExpectedMapEnd,
     ExpectedEnum,
+    ExpectedReference,
+    ExpectedObject,
+    ExpectedObjectEnd,
+    ExpectedStreamEnd,
     TrailingCharacters,
     NumberOverflow,
     InvalidEscapeSequence,
+    InvalidHexString,
+    InvalidTextString,
     MapKeyMustBeAString,
+    MissingStartXref,
+    InvalidXref,
+    UnknownObject,
+    ExpectedStream,
+    UnsupportedFilter(String),
 }
 
 impl ser::Error for Error {
@@ -66,27 +74,37 @@ impl Display for Error {
             Error::Message(ref msg) => msg,
             Error::Io(_) => "IO error",
             Error::Eof => "unexpected end of input",
-            Error::Syntax => "Syntax (TODO)",
-            Error::ExpectedBoolean => "ExpectedBoolean (TODO)",
-            Error::ExpectedInteger => "ExpectedInteger (TODO)",
-            Error::ExpectedUnsignedInteger => "ExpectedUnsignedInteger (TODO)",
-            Error::ExpectedFloat => "ExpectedFloat (TODO)",
-            Error::ExpectedString => "ExpectedString (TODO)",
-            Error::ExpectedName => "ExpectedName (TODO)",
-            Error::ExpectedChar => "ExpectedChar (TODO)",
-            Error::ExpectedNull => "ExpectedNull (TODO)",
-            Error::ExpectedArray => "ExpectedArray (TODO)",
-            Error::ExpectedArrayComma => "ExpectedArrayComma (TODO)",
-            Error::ExpectedArrayEnd => "ExpectedArrayEnd (TODO)",
-            Error::ExpectedMap => "ExpectedMap (TODO)",
-            Error::ExpectedMapColon => "ExpectedMapColon (TODO)",
-            Error::ExpectedMapComma => "ExpectedMapComma (TODO)",
-            Error::ExpectedMapEnd => "ExpectedMapEnd (TODO)",
-            Error::ExpectedEnum => "ExpectedEnum (TODO)",
-            Error::TrailingCharacters => "TrailingCharacters (TODO)",
-            Error::NumberOverflow => "Number overflow",
-            Error::InvalidEscapeSequence => "Invalid escape sequence in string literal",
-            Error::MapKeyMustBeAString => "Map key must be a string",
+            Error::Syntax => "syntax error",
+            Error::ExpectedBoolean => "expected a boolean",
+            Error::ExpectedInteger => "expected an integer",
+            Error::ExpectedUnsignedInteger => "expected an unsigned integer",
+            Error::ExpectedFloat => "expected a real number",
+            Error::ExpectedString => "expected a string",
+            Error::ExpectedName => "expected a name",
+            Error::ExpectedChar => "expected a string with a single character",
+            Error::ExpectedNull => "expected null",
+            Error::ExpectedArray => "expected an array",
+            Error::ExpectedArrayEnd => "expected the end of an array",
+            Error::ExpectedMap => "expected a dictionary",
+            Error::ExpectedMapEnd => "expected the end of a dictionary",
+            Error::ExpectedEnum => "expected a name or a dictionary with a single entry",
+            Error::ExpectedReference => "expected a reference",
+            Error::ExpectedObject => "expected an indirect object",
+            Error::ExpectedObjectEnd => "expected endobj",
+            Error::ExpectedStreamEnd => "expected endstream",
+            Error::TrailingCharacters => "trailing characters",
+            Error::NumberOverflow => "number overflow",
+            Error::InvalidEscapeSequence => "invalid escape sequence in string literal",
+            Error::InvalidHexString => "invalid character in hexadecimal string",
+            Error::InvalidTextString => "string is not a valid text string",
+            Error::MapKeyMustBeAString => "map key must be a string",
+            Error::MissingStartXref => "startxref not found",
+            Error::InvalidXref => "invalid cross-reference section",
+            Error::UnknownObject => "unknown object",
+            Error::ExpectedStream => "expected a stream object",
+            Error::UnsupportedFilter(ref filter) => {
+                return write!(f, "unsupported stream filter: {}", filter)
+            }
         })
     }
 }
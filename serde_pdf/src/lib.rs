@@ -6,16 +6,20 @@ extern crate serde_derive;
 
 mod de;
 mod error;
+mod name;
 mod object;
+mod reader;
 mod ser;
 mod stream;
 mod string;
 mod value;
 
-pub use crate::de::{from_str, Deserializer};
+pub use crate::de::{from_slice, from_str, Deserializer};
 pub use crate::error::{Error, Result};
+pub use crate::name::Name;
 pub use crate::object::{Object, ObjectId, Reference};
-pub use crate::ser::{datetime, to_string, to_writer};
+pub use crate::reader::Reader;
+pub use crate::ser::{datetime, to_string, to_string_encrypted, to_writer};
 pub use crate::stream::Stream;
-pub use crate::string::{PdfStr, PdfString};
-pub use crate::value::Value;
+pub use crate::string::{PdfBytes, PdfStr, PdfString, PdfText};
+pub use crate::value::{Dictionary, Value};
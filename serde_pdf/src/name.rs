@@ -0,0 +1,38 @@
+use crate::ser::{write_name, NAME_RAW};
+use serde::{ser, Serialize, Serializer};
+
+/// A PDF name object (e.g. `/PANTONE#20300#20C`) for names that are only known at runtime.
+/// Characters that are not allowed in names are escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Name<'a>(pub &'a str);
+
+impl<'a> Serialize for Name<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buf = Vec::with_capacity(self.0.len() + 1);
+        write_name(&mut buf, self.0).map_err(ser::Error::custom)?;
+        let name = String::from_utf8(buf).map_err(ser::Error::custom)?;
+        serializer.serialize_newtype_struct(NAME_RAW, &name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::to_string;
+
+    #[test]
+    fn test_serialize_name() {
+        assert_eq!(to_string(&Name("DeviceRGB")).unwrap(), "/DeviceRGB");
+        assert_eq!(
+            to_string(&Name("PANTONE 300 C")).unwrap(),
+            "/PANTONE#20300#20C"
+        );
+        assert_eq!(
+            to_string(&(Name("Separation"), Name("Grün"))).unwrap(),
+            "[/Separation /Gr#c3#bcn]"
+        );
+    }
+}
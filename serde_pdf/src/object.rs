@@ -7,14 +7,16 @@ use crate::{
     ser::{NAME_OBJECT, NAME_REFERENCE},
     Value,
 };
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
 use serde::{ser::SerializeTupleStruct, Serialize, Serializer};
+use std::fmt;
 use std::io;
 use std::rc::Rc;
 
-#[derive(PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct ObjectId(Rc<ObjectIdInner>);
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 struct ObjectIdInner {
     id: usize,
     rev: usize,
@@ -55,6 +57,10 @@ where
         Reference::new(self.id.clone())
     }
 
+    pub fn content(&self) -> &D {
+        &self.content
+    }
+
     pub fn content_mut(&mut self) -> &mut D {
         &mut self.content
     }
@@ -109,6 +115,47 @@ where
     }
 }
 
+impl<'de, D> Deserialize<'de> for Object<D>
+where
+    D: Serialize + Deserialize<'de>,
+{
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        struct ObjectVisitor<D>(PhantomData<D>);
+
+        impl<'de, D> Visitor<'de> for ObjectVisitor<D>
+        where
+            D: Serialize + Deserialize<'de>,
+        {
+            type Value = Object<D>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an indirect object")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let rev = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let content = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                Ok(Object::new(id, rev, content))
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(NAME_OBJECT, 3, ObjectVisitor(PhantomData))
+    }
+}
+
 impl<D> Reference<D>
 where
     D: Serialize,
@@ -139,6 +186,53 @@ where
     }
 }
 
+impl<'de, D> Deserialize<'de> for Reference<D>
+where
+    D: Serialize,
+{
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        struct ReferenceVisitor<D>(PhantomData<D>);
+
+        impl<'de, D> Visitor<'de> for ReferenceVisitor<D>
+        where
+            D: Serialize,
+        {
+            type Value = Reference<D>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a reference")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let rev = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(Reference::new(ObjectId::new(id, rev)))
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(NAME_REFERENCE, 2, ReferenceVisitor(PhantomData))
+    }
+}
+
+impl<D> fmt::Debug for Reference<D>
+where
+    D: Serialize,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Reference").field(&self.0).finish()
+    }
+}
+
 impl<D> Hash for Reference<D>
 where
     D: Serialize,
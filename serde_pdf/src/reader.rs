@@ -0,0 +1,1151 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::rc::Rc;
+
+use flate2::read::ZlibDecoder;
+use serde::de::DeserializeOwned;
+
+use crate::de::{decode_name, is_delimiter, is_whitespace, Deserializer};
+use crate::error::{Error, Result};
+use crate::object::ObjectId;
+use crate::string::decode_string;
+use crate::value::{Dictionary, Value};
+
+/// Reads an existing PDF document.
+///
+/// All cross-reference sections (classic tables as well as cross-reference streams, following
+/// their `/Prev` chain) are parsed when the reader is created. Indirect objects are only parsed
+/// once they are requested, and cached afterwards.
+///
+/// Strings are represented as [`Value::String`] if they are text strings, and as
+/// [`Value::Bytes`] otherwise (see [`Value::from_string_bytes`]).
+pub struct Reader {
+    data: Vec<u8>,
+    xref: HashMap<usize, XrefEntry>,
+    trailer: Dictionary,
+    objects: RefCell<HashMap<(usize, usize), Rc<Loaded>>>,
+    object_streams: RefCell<HashMap<usize, Rc<ObjectStream>>>,
+    /// The objects that are currently being loaded, to detect objects that (indirectly) depend on
+    /// themselves, like an object stream that is compressed into itself.
+    loading: RefCell<HashSet<(usize, usize)>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum XrefEntry {
+    Free,
+    Offset { offset: usize, rev: usize },
+    Compressed { stream: usize, index: usize },
+}
+
+/// An indirect object that has already been parsed.
+struct Loaded {
+    value: Value,
+    source: Source,
+    /// The range of the object's content inside of its source.
+    content: Range<usize>,
+    /// The offset of the stream data if the object is a stream.
+    stream: Option<usize>,
+}
+
+enum Source {
+    File,
+    ObjectStream(Rc<ObjectStream>),
+}
+
+struct ObjectStream {
+    data: Vec<u8>,
+    /// The id and the offset inside of `data` of every contained object.
+    objects: Vec<(usize, usize)>,
+}
+
+impl Reader {
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        let mut xref = HashMap::new();
+        let mut trailer = None;
+        let mut visited = HashSet::new();
+        let mut next = Some(find_startxref(&data)?);
+
+        while let Some(offset) = next {
+            // guard against cyclic /Prev chains
+            if !visited.insert(offset) {
+                break;
+            }
+
+            let section = read_xref_section(&data, offset, &mut xref)?;
+            // hybrid-reference files list additional objects in a cross-reference stream
+            if let Some(offset) = section.get("XRefStm").and_then(as_usize) {
+                if visited.insert(offset) {
+                    read_xref_section(&data, offset, &mut xref)?;
+                }
+            }
+            next = section.get("Prev").and_then(as_usize);
+
+            if trailer.is_none() {
+                trailer = Some(section);
+            }
+        }
+
+        let mut trailer = trailer.unwrap_or_default();
+        // remove the entries that only describe the cross-reference stream itself
+        for key in &[
+            "Type",
+            "Length",
+            "Filter",
+            "DecodeParms",
+            "W",
+            "Index",
+            "Prev",
+            "XRefStm",
+        ] {
+            trailer.shift_remove(*key);
+        }
+
+        Ok(Reader {
+            data,
+            xref,
+            trailer,
+            objects: RefCell::new(HashMap::new()),
+            object_streams: RefCell::new(HashMap::new()),
+            loading: RefCell::new(HashSet::new()),
+        })
+    }
+
+    /// The trailer dictionary of the most recent revision of the document.
+    pub fn trailer(&self) -> &Dictionary {
+        &self.trailer
+    }
+
+    /// The document catalog referenced by the trailer's `/Root` entry.
+    pub fn catalog(&self) -> Result<Value> {
+        match self.trailer.get("Root") {
+            Some(root) => self.resolve(root),
+            None => Err(Error::UnknownObject),
+        }
+    }
+
+    /// The ids of all objects that are in use, in ascending order.
+    pub fn object_ids(&self) -> Vec<ObjectId> {
+        let mut ids = self
+            .xref
+            .iter()
+            .filter_map(|(id, entry)| match entry {
+                XrefEntry::Free => None,
+                XrefEntry::Offset { rev, .. } => Some(ObjectId::new(*id, *rev)),
+                XrefEntry::Compressed { .. } => Some(ObjectId::new(*id, 0)),
+            })
+            .collect::<Vec<_>>();
+        ids.sort_by_key(|id| (id.id(), id.rev()));
+        ids
+    }
+
+    /// Loads the indirect object with the given id. As required by the spec, references to
+    /// objects that do not exist resolve to [`Value::Null`]. Streams are returned with their
+    /// still encoded data; use [`Reader::stream`] to get their decoded data.
+    pub fn get(&self, id: &ObjectId) -> Result<Value> {
+        let loaded = match self.load(id)? {
+            Some(loaded) => loaded,
+            None => return Ok(Value::Null),
+        };
+        match self.raw_stream(&loaded)? {
+            Some((dict, data)) => Ok(Value::Stream {
+                dict: dict.clone(),
+                data: data.to_vec(),
+            }),
+            None => Ok(loaded.value.clone()),
+        }
+    }
+
+    /// Loads the object if `value` is a reference, or returns a copy of `value` otherwise.
+    pub fn resolve(&self, value: &Value) -> Result<Value> {
+        match value {
+            Value::Reference(id) => self.get(id),
+            value => Ok(value.clone()),
+        }
+    }
+
+    /// Returns the decoded data of the stream with the given id.
+    pub fn stream(&self, id: &ObjectId) -> Result<Vec<u8>> {
+        let loaded = self.load(id)?.ok_or(Error::UnknownObject)?;
+        let (dict, data) = self.raw_stream(&loaded)?.ok_or(Error::ExpectedStream)?;
+
+        // the filter and its parameters may be indirect objects
+        let mut params = Dictionary::new();
+        for key in &["Filter", "DecodeParms"] {
+            if let Some(value) = dict.get(*key) {
+                params.insert(key.to_string(), self.resolve(value)?);
+            }
+        }
+        decode(&params, data)
+    }
+
+    /// Deserializes the content of the indirect object with the given id into `T`.
+    pub fn deserialize<T>(&self, id: &ObjectId) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let loaded = self.load(id)?.ok_or(Error::UnknownObject)?;
+        let content = match &loaded.source {
+            Source::File => &self.data[loaded.content.clone()],
+            Source::ObjectStream(stream) => &stream.data[loaded.content.clone()],
+        };
+        T::deserialize(&mut Deserializer::new(content))
+    }
+
+    /// Returns the dictionary and the encoded data of `loaded` if it is a stream.
+    fn raw_stream<'a>(&'a self, loaded: &'a Loaded) -> Result<Option<(&'a Dictionary, &'a [u8])>> {
+        let (dict, start) = match (&loaded.value, loaded.stream) {
+            (Value::Dictionary(dict), Some(start)) => (dict, start),
+            _ => return Ok(None),
+        };
+
+        // the length may be an indirect object (which cannot be a stream itself)
+        let length = match dict.get("Length") {
+            Some(Value::Reference(id)) => self.load(id)?.and_then(|length| as_usize(&length.value)),
+            Some(length) => as_usize(length),
+            None => None,
+        };
+        Ok(Some((dict, stream_data(&self.data, start, length))))
+    }
+
+    fn load(&self, id: &ObjectId) -> Result<Option<Rc<Loaded>>> {
+        let key = (id.id(), id.rev());
+        if let Some(loaded) = self.objects.borrow().get(&key).cloned() {
+            return Ok(Some(loaded));
+        }
+
+        if !self.loading.borrow_mut().insert(key) {
+            return Err(Error::InvalidXref);
+        }
+        let loaded = match self.xref.get(&id.id()) {
+            Some(XrefEntry::Offset { offset, rev }) if *rev == id.rev() => {
+                self.load_from_file(id.id(), *offset).map(Some)
+            }
+            Some(XrefEntry::Compressed { stream, index }) if id.rev() == 0 => self
+                .load_from_object_stream(id.id(), *stream, *index)
+                .map(Some),
+            _ => Ok(None),
+        };
+        self.loading.borrow_mut().remove(&key);
+
+        let loaded = match loaded? {
+            Some(loaded) => Rc::new(loaded),
+            None => return Ok(None),
+        };
+        self.objects.borrow_mut().insert(key, Rc::clone(&loaded));
+        Ok(Some(loaded))
+    }
+
+    fn load_from_file(&self, id: usize, offset: usize) -> Result<Loaded> {
+        let mut parser = Parser::new(&self.data, offset);
+        let (obj_id, _) = parser.object_header()?;
+        if obj_id != id {
+            return Err(Error::InvalidXref);
+        }
+
+        parser.skip_whitespace();
+        let start = parser.pos;
+        let value = parser.value()?;
+        let end = parser.pos;
+        let stream = parser.stream_start();
+
+        Ok(Loaded {
+            value,
+            source: Source::File,
+            content: start..end,
+            stream,
+        })
+    }
+
+    fn load_from_object_stream(&self, id: usize, stream: usize, index: usize) -> Result<Loaded> {
+        let stream = self.object_stream(stream)?;
+        let offset = match stream.objects.get(index) {
+            Some((obj_id, offset)) if *obj_id == id => *offset,
+            _ => return Err(Error::InvalidXref),
+        };
+
+        let mut parser = Parser::new(&stream.data, offset);
+        parser.skip_whitespace();
+        let start = parser.pos;
+        let value = parser.value()?;
+        let end = parser.pos;
+
+        Ok(Loaded {
+            value,
+            source: Source::ObjectStream(Rc::clone(&stream)),
+            content: start..end,
+            stream: None,
+        })
+    }
+
+    fn object_stream(&self, id: usize) -> Result<Rc<ObjectStream>> {
+        if let Some(stream) = self.object_streams.borrow().get(&id).cloned() {
+            return Ok(stream);
+        }
+
+        let id = ObjectId::new(id, 0);
+        let object = self.get(&id)?;
+        let n = object.get("N").and_then(as_usize);
+        let first = object.get("First").and_then(as_usize);
+        let (n, first) = match (n, first) {
+            (Some(n), Some(first)) => (n, first),
+            _ => return Err(Error::InvalidXref),
+        };
+
+        let data = self.stream(&id)?;
+        let mut parser = Parser::new(&data, 0);
+        let mut objects = Vec::with_capacity(n);
+        for _ in 0..n {
+            let obj_id = parser.unsigned()?;
+            let offset = parser.unsigned()?;
+            objects.push((obj_id, first + offset));
+        }
+
+        let stream = Rc::new(ObjectStream { data, objects });
+        self.object_streams
+            .borrow_mut()
+            .insert(id.id(), Rc::clone(&stream));
+        Ok(stream)
+    }
+}
+
+fn find_startxref(data: &[u8]) -> Result<usize> {
+    const KEYWORD: &[u8] = b"startxref";
+
+    // the keyword is expected to be within the last few bytes of the file
+    let tail = data.len().saturating_sub(1024);
+    let pos = data[tail..]
+        .windows(KEYWORD.len())
+        .rposition(|w| w == KEYWORD)
+        .ok_or(Error::MissingStartXref)?;
+
+    let mut parser = Parser::new(data, tail + pos + KEYWORD.len());
+    parser.unsigned().map_err(|_| Error::MissingStartXref)
+}
+
+/// Reads the cross-reference section at the given `offset` into `xref` and returns its trailer
+/// dictionary. Entries that are already part of `xref` are left untouched, as sections are read
+/// from the most recent to the oldest one.
+fn read_xref_section(
+    data: &[u8],
+    offset: usize,
+    xref: &mut HashMap<usize, XrefEntry>,
+) -> Result<Dictionary> {
+    let mut parser = Parser::new(data, offset);
+    if parser.keyword(b"xref") {
+        read_xref_table(&mut parser, xref)
+    } else {
+        read_xref_stream(&mut parser, xref)
+    }
+}
+
+fn read_xref_table(
+    parser: &mut Parser<'_>,
+    xref: &mut HashMap<usize, XrefEntry>,
+) -> Result<Dictionary> {
+    while !parser.keyword(b"trailer") {
+        let start = parser.unsigned().map_err(|_| Error::InvalidXref)?;
+        let count = parser.unsigned().map_err(|_| Error::InvalidXref)?;
+        let end = start.checked_add(count).ok_or(Error::InvalidXref)?;
+        for id in start..end {
+            let offset = parser.unsigned().map_err(|_| Error::InvalidXref)?;
+            let rev = parser.unsigned().map_err(|_| Error::InvalidXref)?;
+            let entry = if parser.keyword(b"n") {
+                XrefEntry::Offset { offset, rev }
+            } else if parser.keyword(b"f") {
+                XrefEntry::Free
+            } else {
+                return Err(Error::InvalidXref);
+            };
+            xref.entry(id).or_insert(entry);
+        }
+    }
+
+    match parser.value()? {
+        Value::Dictionary(trailer) => Ok(trailer),
+        _ => Err(Error::InvalidXref),
+    }
+}
+
+fn read_xref_stream(
+    parser: &mut Parser<'_>,
+    xref: &mut HashMap<usize, XrefEntry>,
+) -> Result<Dictionary> {
+    parser.object_header().map_err(|_| Error::InvalidXref)?;
+    let dict = match parser.value()? {
+        Value::Dictionary(dict) => dict,
+        _ => return Err(Error::InvalidXref),
+    };
+    let start = parser.stream_start().ok_or(Error::InvalidXref)?;
+    // all entries of a cross-reference stream dictionary are required to be direct objects
+    let data = stream_data(parser.data, start, dict.get("Length").and_then(as_usize));
+    let data = decode(&dict, data)?;
+
+    let widths = match dict.get("W") {
+        Some(Value::Array(w)) => w.iter().filter_map(as_usize).collect::<Vec<_>>(),
+        _ => return Err(Error::InvalidXref),
+    };
+    if widths.len() != 3 || widths.iter().any(|w| *w > 8) {
+        return Err(Error::InvalidXref);
+    }
+    let index = match dict.get("Index") {
+        Some(Value::Array(index)) => index.iter().filter_map(as_usize).collect::<Vec<_>>(),
+        _ => vec![0, dict.get("Size").and_then(as_usize).unwrap_or(0)],
+    };
+
+    let entry_len = widths.iter().sum::<usize>();
+    let mut entries = data.chunks_exact(entry_len.max(1));
+    for subsection in index.chunks_exact(2) {
+        let (start, count) = (subsection[0], subsection[1]);
+        let end = start.checked_add(count).ok_or(Error::InvalidXref)?;
+        for id in start..end {
+            let entry = entries.next().ok_or(Error::InvalidXref)?;
+            let (kind, rest) = entry.split_at(widths[0]);
+            let (field2, field3) = rest.split_at(widths[1]);
+            // the type defaults to 1 if its field is omitted
+            let kind = if widths[0] == 0 { 1 } else { be_number(kind) };
+            let entry = match kind {
+                0 => XrefEntry::Free,
+                1 => XrefEntry::Offset {
+                    offset: be_number(field2),
+                    rev: be_number(field3),
+                },
+                2 => XrefEntry::Compressed {
+                    stream: be_number(field2),
+                    index: be_number(field3),
+                },
+                // unknown types are to be treated as references to the null object
+                _ => XrefEntry::Free,
+            };
+            xref.entry(id).or_insert(entry);
+        }
+    }
+
+    Ok(dict)
+}
+
+fn be_number(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |n, b| n << 8 | usize::from(*b))
+}
+
+/// Returns the raw data of a stream starting at `start`. If the `/Length` is missing or wrong,
+/// the data is determined by searching for the `endstream` keyword instead.
+fn stream_data(data: &[u8], start: usize, length: Option<usize>) -> &[u8] {
+    if let Some(length) = length {
+        if let Some(end) = start.checked_add(length).filter(|end| *end <= data.len()) {
+            let mut parser = Parser::new(data, end);
+            if parser.keyword(b"endstream") {
+                return &data[start..end];
+            }
+        }
+    }
+
+    let rest = &data[start.min(data.len())..];
+    let end = rest
+        .windows(b"endstream".len())
+        .position(|w| w == b"endstream")
+        .unwrap_or(rest.len());
+    let mut stream = &rest[..end];
+    if let Some(s) = stream.strip_suffix(b"\n") {
+        stream = s;
+    }
+    if let Some(s) = stream.strip_suffix(b"\r") {
+        stream = s;
+    }
+    stream
+}
+
+/// Applies the `/Filter`s (and their `/DecodeParms`) of the given stream dictionary to `data`.
+fn decode(dict: &Dictionary, data: &[u8]) -> Result<Vec<u8>> {
+    let filters = match dict.get("Filter") {
+        Some(Value::Name(filter)) => vec![filter.as_str()],
+        Some(Value::Array(filters)) => filters
+            .iter()
+            .map(|filter| match filter {
+                Value::Name(filter) => Ok(filter.as_str()),
+                _ => Err(Error::ExpectedName),
+            })
+            .collect::<Result<_>>()?,
+        _ => Vec::new(),
+    };
+    let params = match dict.get("DecodeParms") {
+        Some(Value::Array(params)) => params.iter().collect(),
+        Some(params) => vec![params],
+        None => Vec::new(),
+    };
+
+    let mut data = data.to_vec();
+    for (i, filter) in filters.into_iter().enumerate() {
+        data = match filter {
+            "FlateDecode" | "Fl" => {
+                let mut decoded = Vec::new();
+                // be lenient about streams that lack their final block, like most readers are
+                if let Err(err) = ZlibDecoder::new(data.as_slice()).read_to_end(&mut decoded) {
+                    if err.kind() != io::ErrorKind::UnexpectedEof {
+                        return Err(err.into());
+                    }
+                }
+                match params.get(i) {
+                    Some(Value::Dictionary(params)) => unpredict(params, decoded)?,
+                    _ => decoded,
+                }
+            }
+            filter => return Err(Error::UnsupportedFilter(filter.to_string())),
+        };
+    }
+
+    Ok(data)
+}
+
+/// Reverses the PNG predictors that can be applied to flate-compressed data.
+fn unpredict(params: &Dictionary, data: Vec<u8>) -> Result<Vec<u8>> {
+    let param = |key, default| params.get(key).and_then(as_usize).unwrap_or(default);
+    let predictor = param("Predictor", 1);
+    if predictor == 1 {
+        return Ok(data);
+    }
+    if predictor < 10 {
+        return Err(Error::UnsupportedFilter(format!("Predictor {}", predictor)));
+    }
+
+    let columns = param("Columns", 1);
+    let bits = param("Colors", 1).checked_mul(param("BitsPerComponent", 8));
+    let row_bits = bits.and_then(|bits| bits.checked_mul(columns));
+    let (bits, row_bits) = match (bits, row_bits) {
+        (Some(bits), Some(row_bits)) => (bits, row_bits),
+        _ => return Err(Error::UnsupportedFilter(format!("Columns {}", columns))),
+    };
+    let bpp = bits.div_ceil(8).max(1);
+    let row_len = row_bits.div_ceil(8);
+    // incomplete rows are ignored, so there is nothing to decode if not even one row is complete
+    if row_len >= data.len() {
+        return Ok(Vec::new());
+    }
+
+    let mut result = Vec::with_capacity(data.len());
+    let mut prev = vec![0; row_len];
+    for chunk in data.chunks_exact(row_len + 1) {
+        let (kind, row) = (chunk[0], &chunk[1..]);
+        let mut row = row.to_vec();
+        for i in 0..row_len {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = prev[i];
+            let c = if i >= bpp { prev[i - bpp] } else { 0 };
+            row[i] = row[i].wrapping_add(match kind {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((u16::from(a) + u16::from(b)) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(Error::UnsupportedFilter(format!("PNG filter {}", kind))),
+            });
+        }
+        result.extend_from_slice(&row);
+        prev = row;
+    }
+
+    Ok(result)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn as_usize(value: &Value) -> Option<usize> {
+    match value {
+        Value::Integer(i) => usize::try_from(*i).ok(),
+        _ => None,
+    }
+}
+
+/// Parses PDF objects (PDF spec 1.7 section 7.3) from a byte slice.
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Parser { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while !matches!(self.peek(), None | Some(b'\r') | Some(b'\n')) {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Consumes the given keyword, if it is next in the input.
+    fn keyword(&mut self, keyword: &[u8]) -> bool {
+        self.skip_whitespace();
+        let end = self.pos + keyword.len();
+        if self.data[self.pos.min(self.data.len())..].starts_with(keyword)
+            && self.data.get(end).is_none_or(|b| is_delimiter(*b))
+        {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| !is_delimiter(b)) {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn unsigned(&mut self) -> Result<usize> {
+        self.skip_whitespace();
+        let token = self.token();
+        if token.is_empty() || !token.iter().all(u8::is_ascii_digit) {
+            return Err(Error::ExpectedUnsignedInteger);
+        }
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(Error::NumberOverflow)
+    }
+
+    /// Parses the `n g obj` header of an indirect object.
+    fn object_header(&mut self) -> Result<(usize, usize)> {
+        let id = self.unsigned()?;
+        let rev = self.unsigned()?;
+        if !self.keyword(b"obj") {
+            return Err(Error::Syntax);
+        }
+        Ok((id, rev))
+    }
+
+    /// Consumes the `stream` keyword and its end-of-line marker and returns the offset of the
+    /// stream data, if the previously parsed dictionary is followed by a stream.
+    fn stream_start(&mut self) -> Option<usize> {
+        if !self.keyword(b"stream") {
+            return None;
+        }
+        if self.peek() == Some(b'\r') {
+            self.pos += 1;
+        }
+        if self.peek() == Some(b'\n') {
+            self.pos += 1;
+        }
+        Some(self.pos)
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(Error::Eof),
+            Some(b'/') => self.name().map(Value::Name),
+            Some(b'(') => self.literal_string(),
+            Some(b'<') if self.data.get(self.pos + 1) == Some(&b'<') => self.dictionary(),
+            Some(b'<') => self.hex_string(),
+            Some(b'[') => self.array(),
+            Some(b'+') | Some(b'-') | Some(b'.') | Some(b'0'..=b'9') => self.number(),
+            Some(_) => match self.token() {
+                b"true" => Ok(Value::Bool(true)),
+                b"false" => Ok(Value::Bool(false)),
+                b"null" => Ok(Value::Null),
+                _ => Err(Error::Syntax),
+            },
+        }
+    }
+
+    /// Parses a number, or a reference (`n g R`) if the number is followed by a generation
+    /// number and `R`.
+    fn number(&mut self) -> Result<Value> {
+        let token = std::str::from_utf8(self.token()).map_err(|_| Error::Syntax)?;
+        if token.contains('.') {
+            return token
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| Error::ExpectedFloat);
+        }
+        let n = token.parse().map_err(|_| Error::ExpectedInteger)?;
+
+        if token.as_bytes()[0].is_ascii_digit() {
+            let pos = self.pos;
+            if let Ok(rev) = self.unsigned() {
+                if self.keyword(b"R") {
+                    return Ok(Value::Reference(ObjectId::new(n as usize, rev)));
+                }
+            }
+            self.pos = pos;
+        }
+
+        Ok(Value::Integer(n))
+    }
+
+    fn name(&mut self) -> Result<String> {
+        self.pos += 1; // `/`
+        decode_name(self.token())
+    }
+
+    fn literal_string(&mut self) -> Result<Value> {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'\\' => self.pos += 1,
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let bytes = decode_string(&self.data[start..self.pos]);
+                        return Ok(Value::from_string_bytes(bytes));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(Error::Eof)
+    }
+
+    fn hex_string(&mut self) -> Result<Value> {
+        let start = self.pos;
+        let len = self.data[start..]
+            .iter()
+            .position(|b| *b == b'>')
+            .ok_or(Error::Eof)?;
+        let digits = &self.data[start + 1..start + len];
+        if !digits
+            .iter()
+            .all(|b| b.is_ascii_hexdigit() || is_whitespace(*b))
+        {
+            return Err(Error::InvalidHexString);
+        }
+        self.pos += len + 1;
+        let bytes = decode_string(&self.data[start..self.pos]);
+        Ok(Value::from_string_bytes(bytes))
+    }
+
+    fn array(&mut self) -> Result<Value> {
+        self.pos += 1; // `[`
+        let mut array = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(array));
+                }
+                None => return Err(Error::ExpectedArrayEnd),
+                Some(_) => array.push(self.value()?),
+            }
+        }
+    }
+
+    fn dictionary(&mut self) -> Result<Value> {
+        self.pos += 2; // `<<`
+        let mut dict = Dictionary::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'>') if self.data.get(self.pos + 1) == Some(&b'>') => {
+                    self.pos += 2;
+                    return Ok(Value::Dictionary(dict));
+                }
+                Some(b'/') => {
+                    let key = self.name()?;
+                    let value = self.value()?;
+                    dict.insert(key, value);
+                }
+                None => return Err(Error::ExpectedMapEnd),
+                Some(_) => return Err(Error::ExpectedName),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    use crate::Reference;
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+
+    /// Appends a revision containing the given objects and a classic cross-reference table to
+    /// `pdf`, and returns the offset of the table.
+    fn append_revision(pdf: &mut Vec<u8>, objects: &[(usize, &[u8])], trailer: &str) -> usize {
+        let mut offsets = Vec::new();
+        for (id, content) in objects {
+            offsets.push((*id, pdf.len()));
+            writeln!(pdf, "{} 0 obj", id).unwrap();
+            pdf.extend_from_slice(content);
+            pdf.extend_from_slice(b"\nendobj\n");
+        }
+
+        let startxref = pdf.len();
+        pdf.extend_from_slice(b"xref\n0 1\n0000000000 65535 f\r\n");
+        for (id, offset) in offsets {
+            write!(pdf, "{} 1\n{:010} 00000 n\r\n", id, offset).unwrap();
+        }
+        write!(
+            pdf,
+            "trailer\n{}\nstartxref\n{}\n%%EOF\n",
+            trailer, startxref
+        )
+        .unwrap();
+        startxref
+    }
+
+    fn document() -> (Vec<u8>, usize) {
+        let mut content = ZlibEncoder::new(Vec::new(), Compression::default());
+        content.write_all(b"BT ET").unwrap();
+        let content = content.finish().unwrap();
+        let mut stream = b"<< /Length 5 0 R /Filter /FlateDecode >>\nstream\n".to_vec();
+        stream.extend_from_slice(&content);
+        stream.extend_from_slice(b"\nendstream");
+        let length = content.len().to_string();
+
+        let mut pdf = b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let startxref = append_revision(
+            &mut pdf,
+            &[
+                (1, b"<< /Type /Catalog /Pages 2 0 R >>"),
+                (2, b"<</Type/Pages/Kids[3 0 R]/Count 1>>"),
+                (
+                    3,
+                    b"<< /Type /Page /Parent 2 0 R /Contents 4 0 R % comment\n \
+                      /Title (A \\(nested\\) (string)) /ID <48656C6C6F> >>",
+                ),
+                (4, &stream),
+                (5, length.as_bytes()),
+                (6, b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>"),
+            ],
+            "<< /Size 7 /Root 1 0 R >>",
+        );
+        (pdf, startxref)
+    }
+
+    fn dict(value: Value) -> Dictionary {
+        match value {
+            Value::Dictionary(dict) => dict,
+            value => panic!("expected dictionary, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_read_xref_table() {
+        let reader = Reader::new(document().0).unwrap();
+        assert_eq!(
+            reader.trailer().get("Root"),
+            Some(&Value::Reference(ObjectId::new(1, 0)))
+        );
+
+        let catalog = dict(reader.catalog().unwrap());
+        assert_eq!(catalog["Type"], Value::Name("Catalog".to_string()));
+
+        let pages = dict(reader.resolve(&catalog["Pages"]).unwrap());
+        assert_eq!(pages["Count"], Value::Integer(1));
+        assert_eq!(
+            pages["Kids"],
+            Value::Array(vec![Value::Reference(ObjectId::new(3, 0))])
+        );
+
+        let page = dict(reader.get(&ObjectId::new(3, 0)).unwrap());
+        assert_eq!(
+            page["Title"],
+            Value::String("A (nested) (string)".to_string())
+        );
+        assert_eq!(page["ID"], Value::String("Hello".to_string()));
+        assert_eq!(reader.stream(&ObjectId::new(4, 0)).unwrap(), b"BT ET");
+        match page["Contents"].resolve(&reader).unwrap() {
+            Value::Stream { dict, data } => {
+                assert_eq!(dict["Length"], Value::Reference(ObjectId::new(5, 0)));
+                // the data is still encoded
+                assert_eq!(dict["Filter"], Value::Name("FlateDecode".to_string()));
+                assert_eq!(decode(&dict, &data).unwrap(), b"BT ET");
+            }
+            contents => panic!("expected stream, got {:?}", contents),
+        }
+        assert_eq!(
+            reader.stream(&ObjectId::new(3, 0)),
+            Err(Error::ExpectedStream)
+        );
+        assert_eq!(reader.object_ids().len(), 6);
+    }
+
+    #[test]
+    fn test_read_prev_chain() {
+        let (mut pdf, prev) = document();
+        append_revision(
+            &mut pdf,
+            &[
+                (2, b"<< /Type /Pages /Kids [3 0 R 7 0 R] /Count 2 >>"),
+                (7, b"<< /Type /Page /Parent 2 0 R >>"),
+            ],
+            &format!("<< /Size 8 /Root 1 0 R /Prev {} >>", prev),
+        );
+
+        let reader = Reader::new(pdf).unwrap();
+        assert!(reader.trailer().get("Prev").is_none());
+
+        let pages = dict(reader.get(&ObjectId::new(2, 0)).unwrap());
+        assert_eq!(pages["Count"], Value::Integer(2));
+        let page = dict(reader.get(&ObjectId::new(7, 0)).unwrap());
+        assert_eq!(page["Type"], Value::Name("Page".to_string()));
+        // objects of the previous revision are still available
+        assert_eq!(reader.stream(&ObjectId::new(4, 0)).unwrap(), b"BT ET");
+        assert_eq!(reader.object_ids().len(), 7);
+        // references to unknown objects resolve to null
+        assert_eq!(reader.get(&ObjectId::new(9, 0)), Ok(Value::Null));
+        assert_eq!(reader.get(&ObjectId::new(2, 1)), Ok(Value::Null));
+    }
+
+    #[test]
+    fn test_read_xref_stream() {
+        let pdf = include_bytes!("../../pdfrs/tests/fixtures/compact.pdf").to_vec();
+        let reader = Reader::new(pdf).unwrap();
+
+        let catalog = dict(reader.catalog().unwrap());
+        assert_eq!(catalog["Type"], Value::Name("Catalog".to_string()));
+        let pages = dict(reader.resolve(&catalog["Pages"]).unwrap());
+        assert_eq!(pages["Count"], Value::Integer(2));
+
+        let kids = match &pages["Kids"] {
+            Value::Array(kids) => kids,
+            kids => panic!("expected array, got {:?}", kids),
+        };
+        for kid in kids {
+            let page = dict(reader.resolve(kid).unwrap());
+            assert_eq!(page["Type"], Value::Name("Page".to_string()));
+            let contents = match &page["Contents"] {
+                Value::Array(contents) => match &contents[0] {
+                    Value::Reference(id) => reader.stream(id).unwrap(),
+                    contents => panic!("expected reference, got {:?}", contents),
+                },
+                contents => panic!("expected reference, got {:?}", contents),
+            };
+            assert!(contents.starts_with(b"BT\n"));
+        }
+    }
+
+    #[test]
+    fn test_read_compact_page_tree() {
+        let pdf = include_bytes!("../../pdfrs/tests/fixtures/compact.pdf").to_vec();
+        let reader = Reader::new(pdf).unwrap();
+
+        let catalog = dict(reader.catalog().unwrap());
+        let pages = dict(reader.resolve(&catalog["Pages"]).unwrap());
+        assert_eq!(pages["Type"], Value::Name("Pages".to_string()));
+        let kids = match &pages["Kids"] {
+            Value::Array(kids) => kids,
+            kids => panic!("expected array, got {:?}", kids),
+        };
+        assert_eq!(kids.len(), 2);
+        for kid in kids {
+            let page = dict(reader.resolve(kid).unwrap());
+            assert_eq!(page["Type"], Value::Name("Page".to_string()));
+            // every page points back to the page tree
+            assert_eq!(
+                reader.resolve(&page["Parent"]).unwrap(),
+                Value::Dictionary(pages.clone())
+            );
+        }
+    }
+
+    /// Creates a document whose objects are only listed by an uncompressed cross-reference stream
+    /// with the given `/W`, `/Index` and `entries` (object 1 is the cross-reference stream).
+    fn xref_stream_document(w: &str, index: &str, entries: &[u8]) -> Vec<u8> {
+        let mut pdf = b"%PDF-1.7\n".to_vec();
+        let startxref = pdf.len();
+        write!(
+            pdf,
+            "1 0 obj\n<< /Type /XRef /Size 3 /W {} /Index {} /Length {} >>\nstream\n",
+            w,
+            index,
+            entries.len()
+        )
+        .unwrap();
+        pdf.extend_from_slice(entries);
+        write!(
+            pdf,
+            "\nendstream\nendobj\nstartxref\n{}\n%%EOF\n",
+            startxref
+        )
+        .unwrap();
+        pdf
+    }
+
+    #[test]
+    fn test_invalid_xref() {
+        let (pdf, startxref) = document();
+        assert_eq!(
+            Reader::new(pdf[..pdf.len() - 20].to_vec()).err(),
+            Some(Error::MissingStartXref)
+        );
+
+        // a cross-reference table that ends in the middle of a subsection
+        let mut truncated = pdf[..startxref + 40].to_vec();
+        write!(truncated, "\nstartxref\n{}\n%%EOF\n", startxref).unwrap();
+        assert_eq!(Reader::new(truncated).err(), Some(Error::InvalidXref));
+
+        let mut overflow = pdf[..startxref].to_vec();
+        write!(
+            overflow,
+            "xref\n{} 2\ntrailer\n<< >>\nstartxref\n{}\n%%EOF\n",
+            usize::MAX,
+            startxref
+        )
+        .unwrap();
+        assert_eq!(Reader::new(overflow).err(), Some(Error::InvalidXref));
+
+        let hostile = xref_stream_document("[1 1 1]", &format!("[{0} {0}]", i64::MAX), b"");
+        assert_eq!(Reader::new(hostile).err(), Some(Error::InvalidXref));
+        // more objects than entries
+        let truncated = xref_stream_document("[1 1 1]", "[0 3]", &[0, 0, 0, 1, 9, 0]);
+        assert_eq!(Reader::new(truncated).err(), Some(Error::InvalidXref));
+    }
+
+    #[test]
+    fn test_object_stream_containing_itself() {
+        // object 2 is listed as the first object of the object stream 2
+        let pdf = xref_stream_document("[1 1 1]", "[0 3]", &[0, 0, 0, 1, 9, 0, 2, 2, 0]);
+        let reader = Reader::new(pdf).unwrap();
+        assert_eq!(reader.get(&ObjectId::new(2, 0)), Err(Error::InvalidXref));
+        // the object can still be requested again
+        assert_eq!(reader.get(&ObjectId::new(2, 0)), Err(Error::InvalidXref));
+        assert!(reader.get(&ObjectId::new(1, 0)).is_ok());
+    }
+
+    #[test]
+    fn test_stream_length_referencing_itself() {
+        let mut pdf = b"%PDF-1.7\n".to_vec();
+        append_revision(
+            &mut pdf,
+            &[(1, b"<< /Length 1 0 R >>\nstream\nBT ET\nendstream")],
+            "<< /Size 2 >>",
+        );
+        let reader = Reader::new(pdf).unwrap();
+        assert_eq!(reader.stream(&ObjectId::new(1, 0)).unwrap(), b"BT ET");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct Font {
+            r#type: String,
+            subtype: String,
+            base_font: String,
+        }
+
+        let reader = Reader::new(document().0).unwrap();
+        assert_eq!(
+            reader.deserialize::<Font>(&ObjectId::new(6, 0)).unwrap(),
+            Font {
+                r#type: "Font".to_string(),
+                subtype: "Type1".to_string(),
+                base_font: "Helvetica".to_string(),
+            }
+        );
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct Page {
+            parent: Reference<()>,
+            contents: Reference<()>,
+        }
+
+        let page = reader.deserialize::<Page>(&ObjectId::new(3, 0)).unwrap();
+        assert_eq!(page.parent, Reference::new(ObjectId::new(2, 0)));
+        assert_eq!(page.contents, Reference::new(ObjectId::new(4, 0)));
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let mut parser = Parser::new(b"[1 2 -4 5.5 /A#20B (a\\)b) <FEFF00E4> null true 6 0 R]", 0);
+        assert_eq!(
+            parser.value(),
+            Ok(Value::Array(vec![
+                Value::Integer(1),
+                Value::Integer(2),
+                Value::Integer(-4),
+                Value::Float(5.5),
+                Value::Name("A B".to_string()),
+                Value::String("a)b".to_string()),
+                Value::String("ä".to_string()),
+                Value::Null,
+                Value::Bool(true),
+                Value::Reference(ObjectId::new(6, 0)),
+            ]))
+        );
+
+        assert_eq!(
+            Parser::new(b"<< /A 1", 0).value(),
+            Err(Error::ExpectedMapEnd)
+        );
+        assert_eq!(
+            Parser::new(b"<< 1 2 >>", 0).value(),
+            Err(Error::ExpectedName)
+        );
+
+        // the reader and the deserializer share the same lexical rules
+        for input in [&b"/A#2"[..], b"/A#+1", b"<4G>"] {
+            let expected = crate::from_slice::<Value>(input).map(|_| ());
+            assert!(expected.is_err());
+            assert_eq!(Parser::new(input, 0).value().map(|_| ()), expected);
+        }
+        assert_eq!(
+            Parser::new(b"/M#FCller", 0).value(),
+            Ok(Value::Name("M\u{fc}ller".to_string()))
+        );
+        assert_eq!(
+            Parser::new(b"(M\\374ller)", 0).value(),
+            Ok(Value::String("M\u{fc}ller".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unpredict() {
+        let mut params = Dictionary::new();
+        params.insert("Predictor".to_string(), Value::Integer(12));
+        params.insert("Columns".to_string(), Value::Integer(3));
+        // a row without filter followed by a row using the up filter
+        let data = vec![0, 1, 2, 3, 2, 1, 1, 1];
+        assert_eq!(unpredict(&params, data.clone()), Ok(vec![1, 2, 3, 2, 3, 4]));
+
+        params.insert("Columns".to_string(), Value::Integer(i64::MAX));
+        assert_eq!(
+            unpredict(&params, data.clone()),
+            Err(Error::UnsupportedFilter(format!("Columns {}", i64::MAX)))
+        );
+        // rows longer than the data are not allocated
+        params.insert("Columns".to_string(), Value::Integer(1 << 40));
+        assert_eq!(unpredict(&params, data), Ok(Vec::new()));
+    }
+}
@@ -6,12 +6,16 @@ pub mod datetime;
 mod raw;
 
 use crate::error::{Error, Result};
+use crate::string::decode_string;
 use raw::RawEmitter;
 
 pub(crate) const NAME_STREAM: &str = "$__pdf_stream";
 pub(crate) const NAME_OBJECT: &str = "$__pdf_object";
 pub(crate) const NAME_REFERENCE: &str = "$__pdf_reference";
 pub(crate) const NAME_RAW: &str = "$__pdf_raw";
+pub(crate) const NAME_STRING: &str = "$__pdf_string";
+
+type Encrypt = Box<dyn Fn(&[u8]) -> Vec<u8>>;
 
 pub struct Serializer<W>
 where
@@ -19,6 +23,8 @@ where
 {
     output: W,
     depth: usize,
+    /// Used to encrypt the bytes of all strings (which are then written as hexadecimal strings).
+    encrypt: Option<Encrypt>,
 }
 
 pub fn to_string<T>(value: &T) -> Result<String>
@@ -28,6 +34,23 @@ where
     let mut serializer = Serializer {
         output: Vec::new(),
         depth: 0,
+        encrypt: None,
+    };
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8_lossy(&serializer.output).to_string())
+}
+
+/// Serializes the given `value` like [`to_string`], but replaces the bytes of all its strings by
+/// the result of `encrypt` (used for the strings of objects of encrypted documents).
+pub fn to_string_encrypted<T, E>(value: &T, encrypt: E) -> Result<String>
+where
+    T: Serialize,
+    E: Fn(&[u8]) -> Vec<u8> + 'static,
+{
+    let mut serializer = Serializer {
+        output: Vec::new(),
+        depth: 0,
+        encrypt: Some(Box::new(encrypt)),
     };
     value.serialize(&mut serializer)?;
     Ok(String::from_utf8_lossy(&serializer.output).to_string())
@@ -41,6 +64,7 @@ where
     let mut serializer = Serializer {
         output: w,
         depth: 0,
+        encrypt: None,
     };
     value.serialize(&mut serializer)?;
     Ok(())
@@ -85,45 +109,46 @@ where
     W: io::Write,
 {
     fn serialize_name(&mut self, name: &str) -> Result<()> {
-        write!(self.output, "/")?;
-        let mut from = 0;
-        // Note: iterating over bytes will break utf8 characters
-        for (i, ch) in name.bytes().enumerate() {
-            match ch {
-                0x00 => return Err(Error::Eof), // TODO: other error?
-                // characters that need to be escaped (outside of ! to ~ and delimiter characters)
-                0x01..=0x20
-                | 0x7F..=0xFF
-                | b'('
-                | b')'
-                | b'<'
-                | b'>'
-                | b'['
-                | b']'
-                | b'{'
-                | b'}'
-                | b'/'
-                | b'%'
-                | b'#' => {
-                    if i > from {
-                        self.output
-                            .write_all(name.get(from..i).unwrap().as_bytes())?;
-                    }
-                    write!(self.output, "#{:x}", ch)?;
+        write_name(&mut self.output, name)
+    }
+}
 
-                    from = i + 1;
-                }
-                0x21..=0x7E => continue,
-                _ => {
-                    return Err(Error::ExpectedBoolean);
-                } // _ => return Err(Error::ExpectedBoolean), // TODO: other error
+/// Writes the given `name` as PDF name object (including its leading slash), escaping all
+/// characters that are not allowed in names.
+pub(crate) fn write_name<W: io::Write>(output: &mut W, name: &str) -> Result<()> {
+    write!(output, "/")?;
+    let bytes = name.as_bytes();
+    let mut from = 0;
+    for (i, ch) in bytes.iter().cloned().enumerate() {
+        match ch {
+            0x00 => return Err(Error::Eof), // TODO: other error?
+            // characters that need to be escaped (outside of ! to ~ and delimiter characters)
+            0x01..=0x20
+            | 0x7F..=0xFF
+            | b'('
+            | b')'
+            | b'<'
+            | b'>'
+            | b'['
+            | b']'
+            | b'{'
+            | b'}'
+            | b'/'
+            | b'%'
+            | b'#' => {
+                output.write_all(&bytes[from..i])?;
+                write!(output, "#{:02x}", ch)?;
+
+                from = i + 1;
             }
+            0x21..=0x7E => continue,
+            _ => {
+                return Err(Error::ExpectedBoolean);
+            } // _ => return Err(Error::ExpectedBoolean), // TODO: other error
         }
-        if let Some(remaining) = name.get(from..) {
-            self.output.write_all(remaining.as_bytes())?;
-        }
-        Ok(())
     }
+    output.write_all(&bytes[from..])?;
+    Ok(())
 }
 
 impl<'a, W> ser::Serializer for &'a mut Serializer<W>
@@ -240,6 +265,24 @@ where
     {
         match name {
             NAME_RAW => value.serialize(RawEmitter(self)),
+            NAME_STRING => match &self.encrypt {
+                Some(encrypt) => {
+                    let mut raw = Serializer {
+                        output: Vec::new(),
+                        depth: 0,
+                        encrypt: None,
+                    };
+                    value.serialize(RawEmitter(&mut raw))?;
+                    let encrypted = encrypt(&decode_string(&raw.output));
+                    write!(self.output, "<")?;
+                    for b in encrypted {
+                        write!(self.output, "{:02X}", b)?;
+                    }
+                    write!(self.output, ">")?;
+                    Ok(())
+                }
+                None => value.serialize(RawEmitter(self)),
+            },
             _ => value.serialize(self),
         }
     }
@@ -808,6 +851,7 @@ mod test {
             let mut ser = Serializer {
                 output: Vec::new(),
                 depth: 0,
+                encrypt: None,
             };
             ser.serialize_name(name).unwrap();
             String::from_utf8_lossy(&ser.output).to_string()
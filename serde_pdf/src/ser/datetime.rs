@@ -1,6 +1,6 @@
 use std::io;
 
-use super::{Serializer, NAME_RAW};
+use super::{Serializer, NAME_STRING};
 use crate::error::Error;
 use chrono::prelude::*;
 use serde::ser;
@@ -14,7 +14,7 @@ where
     let s = date.format("%Y%m%d%H%M%S").to_string();
     let mut tz = date.format("%z").to_string();
     let min = tz.split_off(3);
-    serializer.serialize_newtype_struct(NAME_RAW, &format!("(D:{}{}'{}')", s, tz, min))
+    serializer.serialize_newtype_struct(NAME_STRING, &format!("(D:{}{}'{}')", s, tz, min))
 }
 
 #[test]
@@ -1,9 +1,12 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::io;
+use std::marker::PhantomData;
 
 use crate::{ser::NAME_STREAM, Value};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
 use serde::{ser::SerializeTupleStruct, Serialize, Serializer};
-use serde_bytes::Bytes;
+use serde_bytes::{ByteBuf, Bytes};
 
 pub struct Stream<D>
 where
@@ -43,6 +46,45 @@ where
     }
 }
 
+impl<'de, D> Deserialize<'de> for Stream<D>
+where
+    D: Serialize + Deserialize<'de>,
+{
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        struct StreamVisitor<D>(PhantomData<D>);
+
+        impl<'de, D> Visitor<'de> for StreamVisitor<D>
+        where
+            D: Serialize + Deserialize<'de>,
+        {
+            type Value = Stream<D>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a stream")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let dict = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let data = seq
+                    .next_element::<ByteBuf>()?
+                    .map(ByteBuf::into_vec)
+                    .unwrap_or_default();
+                Ok(Stream { dict, data })
+            }
+        }
+
+        deserializer.deserialize_tuple_struct(NAME_STREAM, 2, StreamVisitor(PhantomData))
+    }
+}
+
 impl<D> io::Write for Stream<D>
 where
     D: Serialize,
@@ -64,7 +106,7 @@ mod test {
     #[test]
     fn test_serialize() {
         let mut dict: HashMap<String, Value> = HashMap::new();
-        dict.insert("foo".to_string(), Value::String("bar".to_string()));
+        dict.insert("foo".to_string(), Value::Name("bar".to_string()));
 
         let obj = Stream {
             dict,
@@ -79,7 +121,7 @@ mod test {
     #[test]
     fn test_serialize_dict_only() {
         let mut dict: HashMap<String, Value> = HashMap::new();
-        dict.insert("foo".to_string(), Value::String("bar".to_string()));
+        dict.insert("foo".to_string(), Value::Name("bar".to_string()));
 
         let obj = Stream { dict, data: vec![] };
         assert_eq!(to_string(&obj).unwrap(), "<< /foo /bar >>\n");
@@ -1,7 +1,6 @@
-use std::borrow::Cow;
 use std::fmt;
 
-use crate::ser::NAME_RAW;
+use crate::ser::NAME_STRING;
 use serde::{Serialize, Serializer};
 
 pub enum PdfString {
@@ -14,6 +13,14 @@ pub enum PdfStr<'a> {
     Literal(&'a str),
 }
 
+/// Arbitrary binary data serialized as a hexadecimal string (e.g. `<00FF10>`).
+pub struct PdfBytes<'a>(pub &'a [u8]);
+
+/// A text string (PDF spec 1.7 page 158), which can represent any unicode text (unlike
+/// [`PdfStr::Literal`]). ASCII text is serialized as a literal string, everything else is encoded as
+/// UTF-16BE with a leading byte order mark.
+pub struct PdfText<'a>(pub &'a str);
+
 impl Serialize for PdfString {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -23,7 +30,7 @@ impl Serialize for PdfString {
             PdfString::Hex(ref s) => to_hex(s),
             PdfString::Literal(ref s) => to_literal(s),
         };
-        serializer.serialize_newtype_struct(NAME_RAW, &s)
+        serializer.serialize_newtype_struct(NAME_STRING, &s)
     }
 }
 
@@ -36,7 +43,45 @@ impl<'a> Serialize for PdfStr<'a> {
             PdfStr::Hex(s) => to_hex(s),
             PdfStr::Literal(s) => to_literal(s),
         };
-        serializer.serialize_newtype_struct(NAME_RAW, &s)
+        serializer.serialize_newtype_struct(NAME_STRING, &s)
+    }
+}
+
+impl<'a> Serialize for PdfBytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = String::with_capacity(self.0.len() * 2 + 2);
+        s.push('<');
+        for b in self.0 {
+            s.push_str(&format!("{:02X}", b));
+        }
+        s.push('>');
+        serializer.serialize_newtype_struct(NAME_STRING, &s)
+    }
+}
+
+impl<'a> Serialize for PdfText<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.0.is_ascii() {
+            return serializer.serialize_newtype_struct(NAME_STRING, &to_literal(self.0));
+        }
+
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(self.0.encode_utf16().flat_map(u16::to_be_bytes));
+        PdfBytes(&bytes).serialize(serializer)
+    }
+}
+
+impl<'a> PdfStr<'a> {
+    /// The bytes of the string as they are written to the PDF (e.g. as used for the document's
+    /// file identifier).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        decode_string(self.to_string().as_bytes())
     }
 }
 
@@ -69,67 +114,204 @@ fn to_hex(s: &str) -> String {
     format!("<{}>", buf)
 }
 
+/// Serializes `s` as literal string in PDFDocEncoding. Characters that cannot be represented in
+/// PDFDocEncoding are replaced with `?` (use [`PdfText`] for arbitrary text instead).
 fn to_literal(s: &str) -> String {
-    let mut buf = s.to_string();
-
-    fn unicode_to_win1252(ch: char) -> Option<u32> {
+    let mut buf = String::with_capacity(s.len() + 2);
+    buf.push('(');
+    for ch in s.chars() {
         match ch {
-            '\u{20ac}' => Some(128),
-            '\u{201a}' => Some(130),
-            '\u{0192}' => Some(131),
-            '\u{201e}' => Some(132),
-            '\u{2026}' => Some(133),
-            '\u{2020}' => Some(134),
-            '\u{2021}' => Some(135),
-            '\u{02c6}' => Some(136),
-            '\u{2030}' => Some(137),
-            '\u{0160}' => Some(138),
-            '\u{2039}' => Some(139),
-            '\u{0152}' => Some(140),
-            '\u{017d}' => Some(142),
-            '\u{2018}' => Some(145),
-            '\u{2019}' => Some(146),
-            '\u{201c}' => Some(147),
-            '\u{201d}' => Some(148),
-            '\u{2022}' => Some(149),
-            '\u{2013}' => Some(150),
-            '\u{2014}' => Some(151),
-            '\u{02dc}' => Some(152),
-            '\u{2122}' => Some(153),
-            '\u{0161}' => Some(154),
-            '\u{203a}' => Some(155),
-            '\u{0153}' => Some(156),
-            '\u{017e}' => Some(158),
-            '\u{0178}' => Some(159),
-            _ => ch.to_digit(10),
+            '\\' | '(' | ')' => {
+                buf.push('\\');
+                buf.push(ch);
+            }
+            // readers normalize unescaped line endings to `\n`
+            '\r' => buf.push_str("\\r"),
+            ' '..='~' | '\t' | '\n' => buf.push(ch),
+            _ => match pdf_doc_code(ch) {
+                Some(code) => buf.push_str(&format!("\\{:03o}", code)),
+                None => buf.push('?'),
+            },
         }
     }
+    buf.push(')');
+    buf
+}
 
-    let mut i = 0;
+/// The characters of the PDFDocEncoding (PDF spec 1.7 page 656) that differ from ISO Latin-1.
+const PDF_DOC_ENCODING: [(u8, char); 40] = [
+    (0x18, '\u{02d8}'),
+    (0x19, '\u{02c7}'),
+    (0x1a, '\u{02c6}'),
+    (0x1b, '\u{02d9}'),
+    (0x1c, '\u{02dd}'),
+    (0x1d, '\u{02db}'),
+    (0x1e, '\u{02da}'),
+    (0x1f, '\u{02dc}'),
+    (0x80, '\u{2022}'),
+    (0x81, '\u{2020}'),
+    (0x82, '\u{2021}'),
+    (0x83, '\u{2026}'),
+    (0x84, '\u{2014}'),
+    (0x85, '\u{2013}'),
+    (0x86, '\u{0192}'),
+    (0x87, '\u{2044}'),
+    (0x88, '\u{2039}'),
+    (0x89, '\u{203a}'),
+    (0x8a, '\u{2212}'),
+    (0x8b, '\u{2030}'),
+    (0x8c, '\u{201e}'),
+    (0x8d, '\u{201c}'),
+    (0x8e, '\u{201d}'),
+    (0x8f, '\u{2018}'),
+    (0x90, '\u{2019}'),
+    (0x91, '\u{201a}'),
+    (0x92, '\u{2122}'),
+    (0x93, '\u{fb01}'),
+    (0x94, '\u{fb02}'),
+    (0x95, '\u{0141}'),
+    (0x96, '\u{0152}'),
+    (0x97, '\u{0160}'),
+    (0x98, '\u{0178}'),
+    (0x99, '\u{017d}'),
+    (0x9a, '\u{0131}'),
+    (0x9b, '\u{0142}'),
+    (0x9c, '\u{0153}'),
+    (0x9d, '\u{0161}'),
+    (0x9e, '\u{017e}'),
+    (0xa0, '\u{20ac}'),
+];
 
-    let replacements: Vec<(usize, usize, Cow<str>)> =
-        buf.char_indices()
-            .filter_map(|(i, ch)| match ch {
-                '\\' => Some((i, 1, Cow::Borrowed(r#"\\"#))),
-                '(' => Some((i, 1, Cow::Borrowed(r#"\("#))),
-                ')' => Some((i, 1, Cow::Borrowed(r#"\)"#))),
-                _ if !ch.is_ascii() => unicode_to_win1252(ch)
-                    .map(|d| (i, ch.len_utf8(), Cow::Owned(format!("\\{:o}", d)))),
-                _ => None,
-            })
-            .collect();
+/// Maps a character to its code in the PDFDocEncoding.
+fn pdf_doc_code(ch: char) -> Option<u8> {
+    match ch {
+        '\t' | '\n' | '\r' | ' '..='~' | '\u{a1}'..='\u{ac}' | '\u{ae}'..='\u{ff}' => {
+            Some(ch as u8)
+        }
+        _ => PDF_DOC_ENCODING
+            .iter()
+            .find(|(_, c)| *c == ch)
+            .map(|(code, _)| *code),
+    }
+}
 
-    for (i, l, rep) in replacements.iter().rev() {
-        buf.replace_range(i..&(i + l), rep);
+/// Maps a code of the PDFDocEncoding to its character (`None` for undefined codes).
+fn pdf_doc_char(code: u8) -> Option<char> {
+    match code {
+        b'\t' | b'\n' | b'\r' | 0x20..=0x7e | 0xa1..=0xac | 0xae..=0xff => Some(char::from(code)),
+        _ => PDF_DOC_ENCODING
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, ch)| *ch),
     }
+}
 
-    format!("({})", buf)
+/// Decodes the bytes of a text string (PDF spec 1.7 page 158), which are UTF-16BE (or UTF-8 as of
+/// PDF 2.0) if they start with the corresponding byte order mark, and PDFDocEncoding otherwise.
+/// Returns `None` if the bytes are not a valid text string.
+pub(crate) fn decode_text(bytes: &[u8]) -> Option<String> {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units = utf16
+            .chunks(2)
+            .map(|pair| u16::from(pair[0]) << 8 | u16::from(*pair.get(1).unwrap_or(&0)))
+            .collect::<Vec<_>>();
+        return String::from_utf16(&units).ok();
+    }
+    if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8(utf8.to_vec()).ok();
+    }
+    bytes.iter().map(|b| pdf_doc_char(*b)).collect()
+}
+
+/// Encodes `s` in PDFDocEncoding, or returns `None` if it contains characters that cannot be
+/// represented in it. Text that would be mistaken for a byte order mark is not encoded either.
+pub(crate) fn encode_pdf_doc(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.chars().map(pdf_doc_code).collect::<Option<Vec<_>>>()?;
+    if bytes.starts_with(&[0xFE, 0xFF]) || bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        None
+    } else {
+        Some(bytes)
+    }
+}
+
+/// Encodes `s` as text string the way [`Value::String`](crate::Value::String) is serialized: in
+/// PDFDocEncoding if possible, and as UTF-16BE with a leading byte order mark otherwise.
+pub(crate) fn encode_text(s: &str) -> Vec<u8> {
+    encode_pdf_doc(s).unwrap_or_else(|| {
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(s.encode_utf16().flat_map(u16::to_be_bytes));
+        bytes
+    })
+}
+
+/// Decodes the bytes of a serialized literal (`(...)`) or hexadecimal (`<...>`) string (PDF spec
+/// 1.7 page 53).
+pub(crate) fn decode_string(s: &[u8]) -> Vec<u8> {
+    fn hex_to_digit(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+
+    match s.first() {
+        Some(b'<') => {
+            let digits = s[1..]
+                .iter()
+                .take_while(|b| **b != b'>')
+                .filter_map(|b| hex_to_digit(*b))
+                .collect::<Vec<_>>();
+            // a missing final digit is assumed to be 0
+            digits
+                .chunks(2)
+                .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+                .collect()
+        }
+        Some(b'(') => {
+            let inner = s[1..].strip_suffix(b")").unwrap_or(&s[1..]);
+            let mut bytes = Vec::with_capacity(inner.len());
+            let mut iter = inner.iter().copied().peekable();
+            while let Some(b) = iter.next() {
+                if b != b'\\' {
+                    bytes.push(b);
+                    continue;
+                }
+                match iter.next() {
+                    Some(b'n') => bytes.push(b'\n'),
+                    Some(b'r') => bytes.push(b'\r'),
+                    Some(b't') => bytes.push(b'\t'),
+                    Some(b'b') => bytes.push(0x08),
+                    Some(b'f') => bytes.push(0x0C),
+                    Some(d @ b'0'..=b'7') => {
+                        let mut code = u32::from(d - b'0');
+                        for _ in 0..2 {
+                            match iter.peek() {
+                                Some(d @ b'0'..=b'7') => {
+                                    code = code * 8 + u32::from(d - b'0');
+                                    iter.next();
+                                }
+                                _ => break,
+                            }
+                        }
+                        bytes.push(code as u8);
+                    }
+                    // escaped line breaks are not part of the string
+                    Some(b'\r') => {
+                        iter.next_if_eq(&b'\n');
+                    }
+                    Some(b'\n') => {}
+                    Some(b) => bytes.push(b),
+                    None => {}
+                }
+            }
+            bytes
+        }
+        _ => s.to_vec(),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::ser::to_string;
+    use crate::de::from_str;
+    use crate::ser::{to_string, to_string_encrypted};
 
     #[test]
     fn test_serialize_hex_string() {
@@ -143,15 +325,72 @@ mod test {
         assert_eq!(to_string(&s).unwrap(), "<475050434253>");
     }
 
+    #[test]
+    fn test_serialize_bytes() {
+        let s = PdfBytes(&[0, 0x7f, 0xff]);
+        assert_eq!(to_string(&s).unwrap(), "<007FFF>");
+    }
+
+    #[test]
+    fn test_serialize_text() {
+        let s = PdfText("Ä𝄞");
+        assert_eq!(to_string(&s).unwrap(), "<FEFF00C4D834DD1E>");
+        let s = PdfText("Annual Report (2019)");
+        assert_eq!(to_string(&s).unwrap(), r#"(Annual Report \(2019\))"#);
+    }
+
     #[test]
     fn test_serialize_literal_string() {
         let s = PdfString::Literal(String::from(r#"0ab(\fo)?!€"#));
-        assert_eq!(to_string(&s).unwrap(), r#"(0ab\(\\fo\)?!\200)"#);
+        assert_eq!(to_string(&s).unwrap(), r#"(0ab\(\\fo\)?!\240)"#);
     }
 
     #[test]
     fn test_serialize_literal_str() {
         let s = PdfStr::Literal(r#"0ab(\fo)?!€"#);
-        assert_eq!(to_string(&s).unwrap(), r#"(0ab\(\\fo\)?!\200)"#);
+        assert_eq!(to_string(&s).unwrap(), r#"(0ab\(\\fo\)?!\240)"#);
+        // characters that cannot be represented in PDFDocEncoding
+        let s = PdfStr::Literal("Ä𝄞\r");
+        assert_eq!(to_string(&s).unwrap(), r#"(\304?\r)"#);
+    }
+
+    #[test]
+    fn test_literal_round_trip() {
+        for text in ["Grün", "Größe: 5€ • ½", "\u{2dc}1"] {
+            let serialized = to_string(&PdfStr::Literal(text)).unwrap();
+            assert_eq!(from_str::<String>(&serialized).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(decode_text(b"M\xfcller").as_deref(), Some("Müller"));
+        assert_eq!(decode_text(b"\x80 \xa0").as_deref(), Some("• €"));
+        assert_eq!(decode_text(b"\xfe\xff\x00\xe4").as_deref(), Some("ä"));
+        assert_eq!(decode_text(b"\xef\xbb\xbf\xc3\xa4").as_deref(), Some("ä"));
+        // undefined codes
+        assert_eq!(decode_text(b"\x00\x01"), None);
+        assert_eq!(decode_text(b"\x9f"), None);
+    }
+
+    #[test]
+    fn test_decode_string() {
+        assert_eq!(decode_string(br#"(0ab\(\\fo\)?!\200)"#), b"0ab(\\fo)?!\x80");
+        assert_eq!(decode_string(b"(a\\nb\\\nc\\53)"), b"a\nbc+");
+        assert_eq!(decode_string(b"<007FFF>"), [0x00, 0x7F, 0xFF]);
+        assert_eq!(decode_string(b"<90 1FA>"), [0x90, 0x1F, 0xA0]);
+        assert_eq!(
+            PdfStr::Hex("foobar").to_bytes(),
+            [0x47, 0x50, 0x50, 0x43, 0x42, 0x53]
+        );
+    }
+
+    #[test]
+    fn test_serialize_encrypted() {
+        let reverse = |data: &[u8]| data.iter().rev().copied().collect();
+        let s = PdfStr::Literal("ab(c");
+        assert_eq!(to_string_encrypted(&s, reverse).unwrap(), "<63286261>");
+        let s = PdfBytes(&[1, 2]);
+        assert_eq!(to_string_encrypted(&s, reverse).unwrap(), "<0201>");
     }
 }
@@ -1,13 +1,22 @@
-use std::collections::HashMap;
-use std::fmt::{self, Debug};
-use std::io;
-use std::mem;
-use std::str;
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter;
 
-use serde::de::DeserializeOwned;
-use serde::ser::{self, Serialize};
+use indexmap::IndexMap;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeTupleStruct, Serializer};
+use serde_bytes::{ByteBuf, Bytes};
 
-use self::ser::Serializer;
+use crate::error;
+use crate::object::{ObjectId, Reference};
+use crate::reader::Reader;
+use crate::ser::{NAME_REFERENCE, NAME_STREAM};
+use crate::string::{decode_text, encode_pdf_doc, encode_text};
+use crate::{Name, PdfBytes, PdfStr};
+
+/// A dictionary that keeps its entries in insertion order (which is also the order in which they
+/// are serialized).
+pub type Dictionary = IndexMap<String, Value>;
 
 /// Represents any valid PDF value.
 #[derive(Debug, Clone, PartialEq)]
@@ -16,9 +25,21 @@ pub enum Value {
     Bool(bool),
     Integer(i64),
     Float(f64),
+    /// A text string.
     String(String),
+    /// A string that is not a text string (e.g. a file identifier).
+    Bytes(Vec<u8>),
+    /// A name. Names that are not valid UTF-8 are read as Latin-1 and are thus not written back
+    /// byte for byte.
+    Name(String),
     Array(Vec<Value>),
-    Dictionary(HashMap<String, Value>),
+    Dictionary(Dictionary),
+    Reference(ObjectId),
+    /// A stream with its (still encoded) data.
+    Stream {
+        dict: Dictionary,
+        data: Vec<u8>,
+    },
 }
 
 impl Default for Value {
@@ -27,19 +48,429 @@ impl Default for Value {
     }
 }
 
-impl ser::Serialize for Value {
+impl Value {
+    /// Creates a string from the bytes of a PDF string. The bytes are treated as a text string
+    /// (UTF-16BE or UTF-8 with a byte order mark, or PDFDocEncoding) if possible, and are kept as
+    /// [`Value::Bytes`] otherwise (e.g. if they contain codes that are undefined in
+    /// PDFDocEncoding). Since [`Value::String`] is serialized in PDFDocEncoding whenever possible,
+    /// strings without byte order mark (like binary file identifiers) are written back byte for
+    /// byte.
+    pub fn from_string_bytes(bytes: Vec<u8>) -> Value {
+        match decode_text(&bytes) {
+            Some(s) => Value::String(s),
+            None => Value::Bytes(bytes),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Integer(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the number for both integers and real numbers.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Integer(v) => Some(v as f64),
+            Value::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            Value::Name(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the dictionary of both dictionaries and streams.
+    pub fn as_dict(&self) -> Option<&Dictionary> {
+        match self {
+            Value::Dictionary(dict) | Value::Stream { dict, .. } => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// Returns the dictionary of both dictionaries and streams.
+    pub fn as_dict_mut(&mut self) -> Option<&mut Dictionary> {
+        match self {
+            Value::Dictionary(dict) | Value::Stream { dict, .. } => Some(dict),
+            _ => None,
+        }
+    }
+
+    pub fn as_reference(&self) -> Option<&ObjectId> {
+        match self {
+            Value::Reference(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Looks up `key` if the value is a dictionary or a stream.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_dict()?.get(key)
+    }
+
+    /// Looks up the name stored under `key` (e.g. `/Type`) if the value is a dictionary or a
+    /// stream.
+    pub fn get_name(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_name()
+    }
+
+    /// Loads the referenced object from `reader` if the value is a reference, or returns a copy
+    /// of the value otherwise.
+    pub fn resolve(&self, reader: &Reader) -> error::Result<Value> {
+        reader.resolve(self)
+    }
+}
+
+impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: ser::Serializer,
+        S: Serializer,
     {
         match *self {
             Value::Null => serializer.serialize_unit(),
             Value::Bool(v) => serializer.serialize_bool(v),
             Value::Integer(v) => serializer.serialize_i64(v),
             Value::Float(v) => serializer.serialize_f64(v),
-            Value::String(ref v) => serializer.serialize_str(v),
+            // written as PDFDocEncoding literal if possible, to keep strings read from a PDF as is
+            Value::String(ref v) => match encode_pdf_doc(v) {
+                Some(_) => PdfStr::Literal(v).serialize(serializer),
+                None => PdfBytes(&encode_text(v)).serialize(serializer),
+            },
+            Value::Bytes(ref v) => PdfBytes(v).serialize(serializer),
+            Value::Name(ref v) => Name(v).serialize(serializer),
             Value::Array(ref v) => v.serialize(serializer),
-            Value::Dictionary(ref v) => v.serialize(serializer),
+            Value::Dictionary(ref v) => serializer.collect_map(v),
+            Value::Reference(ref id) => Reference::<()>::new(id.clone()).serialize(serializer),
+            Value::Stream { ref dict, ref data } => {
+                // unlike `Stream`, empty data is still written as stream to keep it a stream
+                let mut s = serializer.serialize_tuple_struct(NAME_STREAM, 2)?;
+                s.serialize_field(&StreamDict { dict, data })?;
+                s.serialize_field(Bytes::new(data))?;
+                s.end()
+            }
         }
     }
 }
+
+/// Serializes the dictionary of a stream with a `/Length` that matches its data.
+struct StreamDict<'a> {
+    dict: &'a Dictionary,
+    data: &'a [u8],
+}
+
+impl<'a> Serialize for StreamDict<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let length = Value::Integer(i64::try_from(self.data.len()).map_err(ser::Error::custom)?);
+        let entries = self
+            .dict
+            .iter()
+            .filter(|(key, _)| *key != "Length")
+            .map(|(key, value)| (key.as_str(), value));
+        serializer.collect_map(iter::once(("Length", &length)).chain(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any PDF value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Integer(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(match i64::try_from(v) {
+            Ok(v) => Value::Integer(v),
+            Err(_) => Value::Float(v as f64),
+        })
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    // Names are provided as strings, ...
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::Name(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::Name(v))
+    }
+
+    // ... and strings as bytes.
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::from_string_bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::from_string_bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut dict = Dictionary::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                NAME_REFERENCE => {
+                    let (id, rev) = map.next_value()?;
+                    return Ok(Value::Reference(ObjectId::new(id, rev)));
+                }
+                NAME_STREAM => {
+                    let data = map.next_value::<ByteBuf>()?.into_vec();
+                    return Ok(Value::Stream { dict, data });
+                }
+                _ => {
+                    let value = map.next_value()?;
+                    dict.insert(key, value);
+                }
+            }
+        }
+        Ok(Value::Dictionary(dict))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{from_str, to_string, Error};
+
+    #[test]
+    fn test_serialize() {
+        let mut dict = Dictionary::new();
+        dict.insert("Type".to_string(), Value::Name("Annot".to_string()));
+        dict.insert("Contents".to_string(), Value::String("Grün".to_string()));
+        dict.insert("ID".to_string(), Value::Bytes(vec![0x00, 0xFF]));
+        dict.insert("P".to_string(), Value::Reference(ObjectId::new(3, 0)));
+        dict.insert(
+            "Rect".to_string(),
+            Value::Array(vec![Value::Integer(0), Value::Float(1.5)]),
+        );
+        assert_eq!(
+            to_string(&Value::Dictionary(dict)).unwrap(),
+            "<< /Type /Annot /Contents (Gr\\374n) /ID <00FF> /P 3 0 R /Rect [0 1.5] >>"
+        );
+        assert_eq!(
+            to_string(&Value::String("Ä𝄞".to_string())).unwrap(),
+            "<FEFF00C4D834DD1E>"
+        );
+        // text that could be mistaken for a byte order mark
+        assert_eq!(
+            to_string(&Value::String("þÿ".to_string())).unwrap(),
+            "<FEFF00FE00FF>"
+        );
+    }
+
+    #[test]
+    fn test_serialize_stream() {
+        let mut dict = Dictionary::new();
+        dict.insert("Length".to_string(), Value::Integer(42));
+        dict.insert("Subtype".to_string(), Value::Name("XML".to_string()));
+        let stream = Value::Stream {
+            dict,
+            data: b"abc".to_vec(),
+        };
+        assert_eq!(
+            to_string(&stream).unwrap(),
+            "<< /Length 3 /Subtype /XML >>\nstream\nabc\nendstream\n"
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let value: Value = from_str(
+            "<< /Type /Page /Title (Hello) /ID <00FF> /Text <FEFF00E4> /Author (M\\374ller) \
+             /Parent 2 0 R \
+             /Box [0 -1 2.5 true null] /Empty << >> >>",
+        )
+        .unwrap();
+        assert_eq!(value.get_name("Type"), Some("Page"));
+        assert_eq!(
+            value.get("Title"),
+            Some(&Value::String("Hello".to_string()))
+        );
+        assert_eq!(value.get("ID"), Some(&Value::Bytes(vec![0x00, 0xFF])));
+        assert_eq!(value.get("Text"), Some(&Value::String("ä".to_string())));
+        // strings without byte order mark are decoded from PDFDocEncoding
+        assert_eq!(
+            value.get("Author"),
+            Some(&Value::String("Müller".to_string()))
+        );
+        assert_eq!(
+            value.get("Parent"),
+            Some(&Value::Reference(ObjectId::new(2, 0)))
+        );
+        assert_eq!(
+            value.get("Box").and_then(Value::as_array),
+            Some(
+                &[
+                    Value::Integer(0),
+                    Value::Integer(-1),
+                    Value::Float(2.5),
+                    Value::Bool(true),
+                    Value::Null
+                ][..]
+            )
+        );
+        assert_eq!(
+            value.get("Empty"),
+            Some(&Value::Dictionary(Dictionary::new()))
+        );
+
+        // the order of the entries is preserved
+        let keys = value.as_dict().unwrap().keys().collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            ["Type", "Title", "ID", "Text", "Author", "Parent", "Box", "Empty"]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_stream() {
+        let value: Value = from_str("<< /Length 3 >>\nstream\nabc\nendstream").unwrap();
+        let mut dict = Dictionary::new();
+        dict.insert("Length".to_string(), Value::Integer(3));
+        assert_eq!(
+            value,
+            Value::Stream {
+                dict,
+                data: b"abc".to_vec()
+            }
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut inner = Dictionary::new();
+        inner.insert("N".to_string(), Value::Name("Adobe Green".to_string()));
+        let mut dict = Dictionary::new();
+        dict.insert("Length".to_string(), Value::Integer(4));
+        dict.insert("Inner".to_string(), Value::Dictionary(inner));
+        dict.insert("Bytes".to_string(), Value::Bytes(vec![0x80, 0x00]));
+        let value = Value::Array(vec![
+            Value::Stream {
+                dict,
+                data: vec![0x00, 0x01, 0x02, 0x03],
+            },
+            Value::String("(nested) \\".to_string()),
+            Value::Reference(ObjectId::new(12, 1)),
+            Value::Stream {
+                dict: iter::once(("Length".to_string(), Value::Integer(0))).collect(),
+                data: Vec::new(),
+            },
+        ]);
+
+        let serialized = to_string(&value).unwrap();
+        assert_eq!(from_str::<Value>(&serialized), Ok(value));
+    }
+
+    #[test]
+    fn test_string_round_trip() {
+        let value = from_str::<Value>("<E4E4E4E4>").unwrap();
+        assert_eq!(value, Value::String("ääää".to_string()));
+        assert_eq!(to_string(&value).unwrap(), r"(\344\344\344\344)");
+
+        // binary data that happens to be valid PDFDocEncoding, like a file identifier
+        for data in ["<E4E4E4E4>", "<0A0D09202841A0FF>", "<C3A4E282AC>"] {
+            let value = from_str::<Value>(data).unwrap();
+            assert!(matches!(value, Value::String(_)));
+            let serialized = to_string(&value).unwrap();
+            assert_eq!(
+                from_str::<ByteBuf>(&serialized).unwrap(),
+                from_str::<ByteBuf>(data).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_name_round_trip() {
+        let value = from_str::<Value>("/Gr#c3#bcn#20Name").unwrap();
+        assert_eq!(value, Value::Name("Grün Name".to_string()));
+        assert_eq!(to_string(&value).unwrap(), "/Gr#c3#bcn#20Name");
+
+        // names that are not valid UTF-8 are not preserved byte for byte
+        let value = from_str::<Value>("/#E4").unwrap();
+        assert_eq!(value, Value::Name("ä".to_string()));
+        assert_eq!(to_string(&value).unwrap(), "/#c3#a4");
+
+        assert_eq!(
+            from_str::<Value>("/A#00B"),
+            Err(Error::InvalidEscapeSequence)
+        );
+    }
+}